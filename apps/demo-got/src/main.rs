@@ -209,7 +209,7 @@ fn run_with_backend<B: GotBackend>(
                     );
                     let texts: Vec<&str> = texts_with_ids.iter().map(|(_, t)| t.as_str()).collect();
                     let embeddings = embedder
-                        .embed_batch(&texts)
+                        .embed_documents(&texts)
                         .map_err(|e| anyhow::anyhow!("Failed to generate embeddings: {e}"))?;
 
                     // Create a map of person_id -> embedding
@@ -250,7 +250,7 @@ fn run_with_backend<B: GotBackend>(
             let embedder = ix_embeddings::Embedder::new()
                 .map_err(|e| anyhow::anyhow!("Failed to create embedder: {e}"))?;
             let query_embedding = embedder
-                .embed(&query)
+                .embed_query(&query)
                 .map_err(|e| anyhow::anyhow!("Failed to embed query: {e}"))?;
 
             // Perform semantic search
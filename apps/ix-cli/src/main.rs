@@ -1,10 +1,11 @@
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use clap::Args;
 use clap::Parser;
 use clap::Subcommand;
 use serde_json::json;
-use serde_yaml::Value as YamlValue;
 
 #[derive(Parser, Debug)]
 #[command(name = "ixchel", version)]
@@ -32,6 +33,9 @@ enum Command {
         title: String,
         #[arg(long)]
         status: Option<String>,
+        /// Skip the warning about existing entities that look like duplicates.
+        #[arg(long)]
+        no_dupe_check: bool,
     },
 
     Show {
@@ -56,6 +60,21 @@ enum Command {
         command: TagCommand,
     },
 
+    /// List open checklist items across entities.
+    Tasks {
+        #[arg(long, default_value = "issue")]
+        kind: ix_core::entity::EntityKind,
+        /// Include completed items.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Check off or reopen a checklist item of an entity.
+    Task {
+        #[command(subcommand)]
+        command: TaskCommand,
+    },
+
     Link {
         from: String,
         rel: String,
@@ -68,24 +87,99 @@ enum Command {
         to: String,
     },
 
+    /// Replace an old decision with a new one.
+    Supersede {
+        old: String,
+        new: String,
+    },
+
     Check,
 
+    /// Check the search index against the current embedding config and
+    /// prune the embedding cache.
+    Doctor,
+
+    /// Maintain entity IDs.
+    Ids {
+        #[command(subcommand)]
+        command: IdsCommand,
+    },
+
+    /// Manage embedding and reranker models under .ixchel/models/.
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommand,
+    },
+
+    /// List open issues with no open blockers.
+    Ready,
+
+    /// List open issues and the issues they are waiting on.
+    Blocked,
+
     Sync,
 
-    Search {
-        query: String,
+    Search(SearchArgs),
+
+    /// List the entities most similar to an existing one.
+    Related {
+        id: String,
         #[arg(short, long, default_value_t = 10)]
         limit: usize,
     },
 
-    Graph {
-        id: String,
+    /// Measure search quality against golden queries.
+    Eval(EvalArgs),
+
+    /// Report pairs of entities that are likely duplicates.
+    Dupes {
+        /// Minimum cosine similarity of a pair.
+        #[arg(long, default_value_t = ix_core::similarity::DUPLICATE_THRESHOLD)]
+        threshold: f32,
+        /// Also pair entities of different kinds.
+        #[arg(long)]
+        cross_kind: bool,
     },
 
-    Context {
+    /// Suggest tags from the tags of the most similar entities.
+    SuggestTags {
+        #[arg(required_unless_present = "untagged", conflicts_with = "untagged")]
+        id: Option<String>,
+        /// Suggest tags for every untagged entity.
+        #[arg(long)]
+        untagged: bool,
+        /// Apply the suggestions without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Suggest relationships to similar entities of linkable kinds.
+    SuggestLinks {
         id: String,
+        #[arg(short, long, default_value_t = 5)]
+        limit: usize,
+        /// Minimum cosine similarity of a suggested pair.
+        #[arg(long, default_value_t = ix_core::similarity::LINK_SUGGESTION_THRESHOLD)]
+        min_similarity: f32,
+        /// Add the suggested links without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Cluster entities into topics labelled by their common tags.
+    Topics {
+        /// Number of topics (default: about the square root of half the
+        /// entity count).
+        #[arg(short = 'k', long)]
+        count: Option<usize>,
     },
 
+    /// Show the relationships around an entity, or the path between two.
+    Graph(GraphArgs),
+
+    /// Gather a ranked context pack around an entity or a question.
+    Context(ContextArgs),
+
     Delete {
         id: String,
     },
@@ -120,6 +214,186 @@ enum TagCommand {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum TaskCommand {
+    /// Mark the n-th checklist item of an entity as done.
+    Done { id: String, n: usize },
+    /// Mark the n-th checklist item of an entity as not done.
+    Undo { id: String, n: usize },
+}
+
+#[derive(Subcommand, Debug)]
+enum IdsCommand {
+    /// Lengthen short IDs and rewrite every reference to them.
+    Widen {
+        /// Target hash length in bytes (two hex characters each).
+        #[arg(long, default_value_t = 4)]
+        bytes: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ModelsCommand {
+    /// Show installed models with their size and checksum.
+    List,
+    /// Download the configured models, or MODEL instead of the embedding model.
+    Pull { model: Option<String> },
+    /// Re-hash installed models against their recorded checksums.
+    Verify { model: Option<String> },
+    /// Install a pre-downloaded model directory, e.g. on an air-gapped machine.
+    Import {
+        dir: PathBuf,
+        /// Hub repository of the model (inferred for `models--org--name` dirs).
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove installed models the configuration no longer uses.
+    Prune {
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+struct SearchArgs {
+    query: String,
+    #[arg(short, long, default_value_t = 10)]
+    limit: usize,
+    /// Retrieval mode: vector, lexical (BM25) or hybrid (both, rank-fused).
+    #[arg(short, long, default_value = "vector")]
+    mode: ix_core::index::SearchMode,
+    /// Only return entities of this kind (repeatable).
+    #[arg(long)]
+    kind: Vec<ix_core::entity::EntityKind>,
+    /// Only return entities with this status (repeatable).
+    #[arg(long)]
+    status: Vec<String>,
+    /// Only return entities carrying this tag (repeatable; all must match).
+    #[arg(long)]
+    tag: Vec<String>,
+    /// Created on or after this date (YYYY-MM-DD or RFC 3339).
+    #[arg(long, value_parser = ix_core::index::parse_date)]
+    created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Created before this date.
+    #[arg(long, value_parser = ix_core::index::parse_date)]
+    created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Updated on or after this date.
+    #[arg(long, value_parser = ix_core::index::parse_date)]
+    updated_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Updated before this date.
+    #[arg(long, value_parser = ix_core::index::parse_date)]
+    updated_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Skip this many hits.
+    #[arg(long, default_value_t = 0, conflicts_with = "cursor")]
+    offset: usize,
+    /// Continue from the `next_cursor` of a previous page.
+    #[arg(long)]
+    cursor: Option<String>,
+    /// Drop hits scoring below this.
+    #[arg(long)]
+    min_score: Option<f32>,
+}
+
+impl SearchArgs {
+    fn into_request(self) -> Result<ix_core::index::SearchRequest> {
+        let filters = ix_core::index::SearchFilters {
+            kinds: self.kind,
+            statuses: self.status,
+            tags: self.tag,
+            created: ix_core::index::DateRange {
+                after: self.created_after,
+                before: self.created_before,
+            },
+            updated: ix_core::index::DateRange {
+                after: self.updated_after,
+                before: self.updated_before,
+            },
+        };
+        let request = ix_core::index::SearchRequest::new(self.query, self.limit)
+            .with_mode(self.mode)
+            .with_filters(filters)
+            .with_offset(self.offset)
+            .with_min_score(self.min_score);
+        match self.cursor {
+            Some(cursor) => Ok(request.with_cursor(&cursor)?),
+            None => Ok(request),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct EvalArgs {
+    /// Golden queries file (default: `.ixchel/eval.yaml`).
+    #[arg(long)]
+    golden: Option<PathBuf>,
+    /// Also evaluate this config overlay, synced into its own index
+    /// (repeatable).
+    #[arg(long = "config", value_name = "TOML")]
+    configs: Vec<PathBuf>,
+    /// Search mode for queries that don't set one.
+    #[arg(short, long, default_value = "vector")]
+    mode: ix_core::index::SearchMode,
+    /// Hits considered per query (default: the golden file's `k`).
+    #[arg(short)]
+    k: Option<usize>,
+    /// Fail when mean recall@k is below this.
+    #[arg(long)]
+    min_recall: Option<f32>,
+    /// Fail when mean reciprocal rank is below this.
+    #[arg(long)]
+    min_mrr: Option<f32>,
+    /// Fail when mean nDCG@k is below this.
+    #[arg(long)]
+    min_ndcg: Option<f32>,
+}
+
+#[derive(Args, Debug)]
+struct GraphArgs {
+    id: String,
+    /// Relationship hops to follow.
+    #[arg(long, default_value_t = 1)]
+    hops: usize,
+    /// Only follow this relationship (repeatable).
+    #[arg(long)]
+    rel: Vec<String>,
+    /// Follow outgoing, incoming or both directions of relationships.
+    #[arg(short, long, default_value = "both")]
+    direction: ix_core::graph::Direction,
+    /// Show the shortest path to this entity instead.
+    #[arg(long, conflicts_with = "hops")]
+    to: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ContextArgs {
+    /// Entity to gather context around.
+    #[arg(required_unless_present = "query")]
+    id: Option<String>,
+    /// Gather context for a question, seeded from its search hits.
+    #[arg(short, long, conflicts_with = "id")]
+    query: Option<String>,
+    /// Relationship hops to follow (default: `[context] hops`).
+    #[arg(long)]
+    hops: Option<usize>,
+    /// Approximate token budget (default: `[context] budget_tokens`).
+    #[arg(long)]
+    budget: Option<usize>,
+    /// Search hits used as seeds for `--query` (default: `[context] seeds`).
+    #[arg(long)]
+    seeds: Option<usize>,
+}
+
+impl ContextArgs {
+    fn config(&self, base: &ix_core::context::ContextConfig) -> ix_core::context::ContextConfig {
+        ix_core::context::ContextConfig {
+            hops: self.hops.unwrap_or(base.hops),
+            budget_tokens: self.budget.unwrap_or(base.budget_tokens),
+            seeds: self.seeds.unwrap_or(base.seeds),
+            ..base.clone()
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
     let start = cli.repo.clone().unwrap_or(std::env::current_dir()?);
@@ -133,18 +407,52 @@ fn run(command: Command, start: &Path, json_output: bool) -> Result<()> {
             kind,
             title,
             status,
-        } => cmd_create(start, kind, &title, status.as_deref(), json_output),
+            no_dupe_check,
+        } => cmd_create(
+            start,
+            kind,
+            &title,
+            status.as_deref(),
+            !no_dupe_check,
+            json_output,
+        ),
         Command::Show { id } => cmd_show(start, &id, json_output),
         Command::List { kind, sort } => cmd_list(start, kind, sort, json_output),
         Command::Tags { kind, untagged } => cmd_tags(start, kind, untagged, json_output),
         Command::Tag { command } => cmd_tag(start, command, json_output),
+        Command::Tasks { kind, all } => cmd_tasks(start, kind, all, json_output),
+        Command::Task { command } => cmd_task(start, command, json_output),
         Command::Link { from, rel, to } => cmd_link(start, &from, &rel, &to, json_output),
         Command::Unlink { from, rel, to } => cmd_unlink(start, &from, &rel, &to, json_output),
+        Command::Supersede { old, new } => cmd_supersede(start, &old, &new, json_output),
         Command::Check => cmd_check(start, json_output),
+        Command::Doctor => cmd_doctor(start, json_output),
+        Command::Ids {
+            command: IdsCommand::Widen { bytes },
+        } => cmd_ids_widen(start, bytes, json_output),
+        Command::Models { command } => cmd_models(start, command, json_output),
+        Command::Ready => cmd_ready(start, json_output),
+        Command::Blocked => cmd_blocked(start, json_output),
         Command::Sync => cmd_sync(start, json_output),
-        Command::Search { query, limit } => cmd_search(start, &query, limit, json_output),
-        Command::Graph { id } => cmd_graph(start, &id, json_output),
-        Command::Context { id } => cmd_context(start, &id, json_output),
+        Command::Search(args) => cmd_search(start, args, json_output),
+        Command::Related { id, limit } => cmd_related(start, &id, limit, json_output),
+        Command::Eval(args) => cmd_eval(start, &args, json_output),
+        Command::Dupes {
+            threshold,
+            cross_kind,
+        } => cmd_dupes(start, threshold, cross_kind, json_output),
+        Command::SuggestTags { id, untagged, yes } => {
+            cmd_suggest_tags(start, id.as_deref(), untagged, yes, json_output)
+        }
+        Command::SuggestLinks {
+            id,
+            limit,
+            min_similarity,
+            yes,
+        } => cmd_suggest_links(start, &id, limit, min_similarity, yes, json_output),
+        Command::Topics { count } => cmd_topics(start, count, json_output),
+        Command::Graph(args) => cmd_graph(start, &args, json_output),
+        Command::Context(args) => cmd_context(start, &args, json_output),
         Command::Delete { id } => cmd_delete(start, &id, json_output),
         Command::Edit { id } => cmd_edit(start, &id, json_output),
         Command::Watch {
@@ -169,16 +477,37 @@ fn cmd_create(
     kind: ix_core::entity::EntityKind,
     title: &str,
     status: Option<&str>,
+    dupe_check: bool,
     json_output: bool,
 ) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    // Advisory only: an unreadable entity or index shouldn't block create
+    let similar = if dupe_check {
+        ix_app::similar_titles(&repo, kind, title).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    if !json_output {
+        for hit in &similar {
+            eprintln!(
+                "warning: {} \"{}\" looks like a duplicate (similarity {:.3})",
+                hit.id, hit.title, hit.score
+            );
+        }
+    }
+
     let created = repo.create_entity(kind, title, status)?;
     if json_output {
+        let similar = similar
+            .iter()
+            .map(|hit| json!({ "id": hit.id, "title": hit.title, "similarity": hit.score }))
+            .collect::<Vec<_>>();
         print_json(&json!({
             "id": created.id,
             "kind": created.kind.as_str(),
             "title": created.title,
             "path": created.path,
+            "similar": similar,
         }))?;
     } else {
         println!("Created {} ({})", created.id, created.path.display());
@@ -188,11 +517,14 @@ fn cmd_create(
 
 fn cmd_show(start: &Path, id: &str, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
-    let raw = repo.read_raw(id)?;
+    let id = &repo.resolve_id(id)?;
     if json_output {
-        print_json(&json!({ "id": id, "raw": raw }))?;
+        print_json(&ix_app::json::show_json(&repo, id)?)?;
     } else {
-        print!("{raw}");
+        print!("{}", repo.read_raw(id)?);
+        if let Some(current) = repo.superseding_decision(id) {
+            eprintln!("note: {id} has been superseded; the current decision is {current}");
+        }
     }
     Ok(())
 }
@@ -209,23 +541,38 @@ fn cmd_list(
         let items = items
             .into_iter()
             .map(|i| {
+                let tasks = issue_progress(&i).map(ix_app::json::task_progress_json);
                 json!({
                     "id": i.id,
                     "kind": i.kind.as_str(),
                     "title": i.title,
                     "path": i.path,
+                    "tasks": tasks,
                 })
             })
             .collect::<Vec<_>>();
         print_json(&json!({ "items": items }))?;
     } else {
         for item in items {
-            println!("{}\t{}\t{}", item.id, item.kind.as_str(), item.title);
+            match issue_progress(&item).and_then(ix_core::repo::TaskProgress::percent) {
+                Some(percent) => println!(
+                    "{}\t{}\t{}\t{percent}%",
+                    item.id,
+                    item.kind.as_str(),
+                    item.title
+                ),
+                None => println!("{}\t{}\t{}", item.id, item.kind.as_str(), item.title),
+            }
         }
     }
     Ok(())
 }
 
+/// Checklist progress for issues; other kinds don't track tasks.
+fn issue_progress(item: &ix_core::repo::EntitySummary) -> Option<ix_core::repo::TaskProgress> {
+    (item.kind == ix_core::entity::EntityKind::Issue).then_some(item.tasks)
+}
+
 fn cmd_tags(
     start: &Path,
     kind: Option<ix_core::entity::EntityKind>,
@@ -300,6 +647,7 @@ fn cmd_tag(start: &Path, command: TagCommand, json_output: bool) -> Result<()> {
 
 fn cmd_tag_add(start: &Path, id: &str, tags: &[String], json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = &repo.resolve_id(id)?;
     let changed = repo.add_tags(id, tags)?;
 
     if json_output {
@@ -319,6 +667,7 @@ fn cmd_tag_add(start: &Path, id: &str, tags: &[String], json_output: bool) -> Re
 
 fn cmd_tag_remove(start: &Path, id: &str, tags: &[String], json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = &repo.resolve_id(id)?;
     let changed = repo.remove_tags(id, tags)?;
 
     if json_output {
@@ -336,8 +685,81 @@ fn cmd_tag_remove(start: &Path, id: &str, tags: &[String], json_output: bool) ->
     Ok(())
 }
 
+fn cmd_tasks(
+    start: &Path,
+    kind: ix_core::entity::EntityKind,
+    all: bool,
+    json_output: bool,
+) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let tasks = repo.list_tasks(kind, all)?;
+
+    if json_output {
+        let items = tasks
+            .iter()
+            .map(|task| {
+                json!({
+                    "id": task.id,
+                    "title": task.title,
+                    "n": task.item.index,
+                    "done": task.item.checked,
+                    "text": task.item.text,
+                    "section": task.item.section,
+                })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "total": items.len(), "items": items }))?;
+        return Ok(());
+    }
+
+    let id_width = tasks.iter().map(|task| task.id.len()).max().unwrap_or(0);
+    for task in tasks {
+        let mark = if task.item.checked { "x" } else { " " };
+        println!(
+            "{id:id_width$}  {n:>2}  [{mark}] {text}",
+            id = task.id,
+            n = task.item.index,
+            text = task.item.text,
+        );
+    }
+    Ok(())
+}
+
+fn cmd_task(start: &Path, command: TaskCommand, json_output: bool) -> Result<()> {
+    let (id, n, checked) = match command {
+        TaskCommand::Done { id, n } => (id, n, true),
+        TaskCommand::Undo { id, n } => (id, n, false),
+    };
+
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = repo.resolve_id(&id)?;
+    let changed = repo.set_task(&id, n, checked)?;
+    let progress = repo.task_progress(&id)?;
+
+    if json_output {
+        print_json(&json!({
+            "id": id,
+            "n": n,
+            "done": checked,
+            "changed": changed,
+            "tasks": ix_app::json::task_progress_json(progress),
+        }))?;
+    } else if changed {
+        let state = if checked { "done" } else { "not done" };
+        println!(
+            "Marked task {n} of {id} as {state} ({}/{})",
+            progress.done, progress.total
+        );
+    } else {
+        println!("No task changes for {id}");
+    }
+    Ok(())
+}
+
 fn cmd_link(start: &Path, from: &str, rel: &str, to: &str, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let from = &repo.resolve_id(from)?;
+    let to = &repo.resolve_id(to)?;
     repo.link(from, rel, to)?;
     if json_output {
         print_json(&json!({ "from": from, "rel": rel, "to": to, "changed": true }))?;
@@ -349,6 +771,13 @@ fn cmd_link(start: &Path, from: &str, rel: &str, to: &str, json_output: bool) ->
 
 fn cmd_unlink(start: &Path, from: &str, rel: &str, to: &str, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let from = &repo.resolve_id(from)?;
+    // The target may already be deleted; a well-formed ID is taken as given.
+    let to = &if ix_core::entity::looks_like_entity_id(to) {
+        to.to_string()
+    } else {
+        repo.resolve_id(to)?
+    };
     let removed = repo.unlink(from, rel, to)?;
     if json_output {
         print_json(&json!({ "from": from, "rel": rel, "to": to, "changed": removed }))?;
@@ -360,6 +789,19 @@ fn cmd_unlink(start: &Path, from: &str, rel: &str, to: &str, json_output: bool)
     Ok(())
 }
 
+fn cmd_supersede(start: &Path, old: &str, new: &str, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let old = &repo.resolve_id(old)?;
+    let new = &repo.resolve_id(new)?;
+    repo.supersede(old, new)?;
+    if json_output {
+        print_json(&json!({ "old": old, "new": new, "status": "superseded" }))?;
+    } else {
+        println!("{old} superseded by {new}");
+    }
+    Ok(())
+}
+
 fn cmd_check(start: &Path, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
     let report = repo.check_with_suggestions()?;
@@ -396,6 +838,336 @@ fn cmd_check(start: &Path, json_output: bool) -> Result<()> {
     Ok(())
 }
 
+fn cmd_models(start: &Path, command: ModelsCommand, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    match command {
+        ModelsCommand::List => {
+            let models = ix_app::model_store(&repo).list()?;
+            let required = ix_app::required_models(&repo).unwrap_or_default();
+            print_models(&models, &required, json_output)
+        }
+        ModelsCommand::Pull { model } => {
+            let pulled = ix_app::pull_models(&repo, model.as_deref())?;
+            if pulled.is_empty() && !json_output {
+                println!(
+                    "Provider {} uses no local model files",
+                    repo.config.embedding.provider
+                );
+                return Ok(());
+            }
+            print_models(&pulled, &[], json_output)
+        }
+        ModelsCommand::Verify { model } => cmd_models_verify(&repo, model.as_deref(), json_output),
+        ModelsCommand::Import { dir, name } => {
+            let model = ix_app::model_store(&repo).import(&dir, name.as_deref())?;
+            print_models(&[model], &[], json_output)
+        }
+        ModelsCommand::Prune { dry_run } => {
+            let pruned = ix_app::prune_models(&repo, dry_run)?;
+            if json_output {
+                let models = pruned.iter().map(|m| &m.repo_id).collect::<Vec<_>>();
+                print_json(&json!({ "dry_run": dry_run, "removed": models }))?;
+            } else if pruned.is_empty() {
+                println!("No unused models");
+            } else {
+                let verb = if dry_run { "Would remove" } else { "Removed" };
+                for model in &pruned {
+                    println!(
+                        "{verb} {} ({})",
+                        model.repo_id,
+                        format_size(model.size_bytes)
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_models(
+    models: &[ix_app::InstalledModel],
+    required: &[String],
+    json_output: bool,
+) -> Result<()> {
+    if json_output {
+        let models = models
+            .iter()
+            .map(|m| {
+                json!({
+                    "model": m.repo_id,
+                    "path": m.path,
+                    "size_bytes": m.size_bytes,
+                    "files": m.files,
+                    "checksum": m.checksum,
+                    "in_use": required.contains(&m.repo_id),
+                })
+            })
+            .collect::<Vec<_>>();
+        return print_json(&json!({ "models": models }));
+    }
+
+    if models.is_empty() {
+        println!("No models installed");
+    }
+    for model in models {
+        let checksum = model
+            .checksum
+            .as_deref()
+            .map_or("unrecorded", |checksum| &checksum[..16.min(checksum.len())]);
+        let marker = if required.contains(&model.repo_id) {
+            "\t(in use)"
+        } else {
+            ""
+        };
+        println!(
+            "{}\t{}\t{} files\tb3:{checksum}{marker}",
+            model.repo_id,
+            format_size(model.size_bytes),
+            model.files
+        );
+    }
+    Ok(())
+}
+
+fn cmd_models_verify(
+    repo: &ix_core::repo::IxchelRepo,
+    model: Option<&str>,
+    json_output: bool,
+) -> Result<()> {
+    let store = ix_app::model_store(repo);
+    let models = match model {
+        Some(model) => vec![model.to_string()],
+        None => store.list()?.into_iter().map(|m| m.repo_id).collect(),
+    };
+
+    let mut reports = Vec::new();
+    for model in &models {
+        if !store.model_dir(model).is_dir() {
+            anyhow::bail!(
+                "Model {model} is not installed in {}",
+                store.dir().display()
+            );
+        }
+        reports.push(store.verify(model)?);
+    }
+    let ok = reports.iter().all(ix_app::ModelVerification::is_ok);
+
+    if json_output {
+        let reports = reports
+            .iter()
+            .map(|r| {
+                json!({
+                    "model": r.repo_id,
+                    "ok": r.is_ok(),
+                    "checked": r.checked,
+                    "mismatched": r.mismatched,
+                    "missing": r.missing,
+                    "unrecorded": r.unrecorded,
+                })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "ok": ok, "models": reports }))?;
+    } else {
+        for report in &reports {
+            if report.unrecorded {
+                eprintln!(
+                    "{}: no checksums recorded; run `ixchel models pull` to record them",
+                    report.repo_id
+                );
+            } else if report.is_ok() {
+                println!("{}: OK ({} files)", report.repo_id, report.checked);
+            }
+            for file in &report.mismatched {
+                eprintln!("{}: checksum mismatch: {file}", report.repo_id);
+            }
+            for file in &report.missing {
+                eprintln!("{}: missing: {file}", report.repo_id);
+            }
+        }
+    }
+
+    if !ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Human-readable byte count, e.g. `127.4 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    #[allow(clippy::cast_precision_loss)]
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn cmd_doctor(start: &Path, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let status = ix_app::index_metadata(&repo)?;
+    let cache = ix_app::prune_embedding_cache(&repo)?;
+
+    let mut problems = status
+        .differences()
+        .into_iter()
+        .map(|difference| format!("index built with a different {difference}"))
+        .collect::<Vec<_>>();
+    if status.stored.is_none() {
+        problems.push("index has no metadata; run `ixchel sync` to build it".to_string());
+    } else if problems.is_empty()
+        && let Err(err) = ix_app::health_check(&repo)
+    {
+        problems.push(format!("{err:#}"));
+    }
+
+    if json_output {
+        print_json(&json!({
+            "ok": problems.is_empty(),
+            "backend": repo.config.storage.backend,
+            "expected": status.expected,
+            "stored": status.stored,
+            "embedding_cache": {
+                "entries": cache.entries,
+                "bytes": cache.bytes,
+                "removed": cache.removed,
+                "removed_bytes": cache.removed_bytes,
+            },
+            "problems": problems,
+        }))?;
+    } else {
+        let expected = &status.expected;
+        println!(
+            "backend={} provider={} model={} dimension={} recipe={} schema_version={}",
+            expected.backend,
+            expected.provider,
+            expected.model,
+            expected.dimension,
+            expected.recipe,
+            expected.schema_version
+        );
+        println!(
+            "embedding_cache entries={} bytes={} removed={} removed_bytes={}",
+            cache.entries, cache.bytes, cache.removed, cache.removed_bytes
+        );
+        if problems.is_empty() {
+            println!("OK");
+        }
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+    }
+
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_ids_widen(start: &Path, bytes: usize, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let renames = repo.widen_ids(bytes)?;
+
+    if json_output {
+        let items = renames
+            .iter()
+            .map(|r| json!({ "old": r.old, "new": r.new, "path": r.path }))
+            .collect::<Vec<_>>();
+        print_json(&json!({ "bytes": bytes, "renamed": items }))?;
+        return Ok(());
+    }
+
+    if renames.is_empty() {
+        println!("All IDs are already at least {bytes} bytes");
+        return Ok(());
+    }
+    for rename in &renames {
+        println!("{} -> {}", rename.old, rename.new);
+    }
+    println!(
+        "Widened {} IDs. Set `bytes = {bytes}` under [ids] in {} so new IDs match, \
+         then run `ixchel sync`.",
+        renames.len(),
+        repo.paths.config_path().display()
+    );
+    Ok(())
+}
+
+fn cmd_ready(start: &Path, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let report = repo.issue_dependencies()?;
+
+    if json_output {
+        let items = report
+            .ready
+            .iter()
+            .map(ix_app::json::issue_ref_json)
+            .collect::<Vec<_>>();
+        print_json(&json!({ "total": items.len(), "items": items }))?;
+        return Ok(());
+    }
+
+    let id_width = report
+        .ready
+        .iter()
+        .map(|issue| issue.id.len())
+        .max()
+        .unwrap_or(0);
+    for issue in report.ready {
+        println!(
+            "{id:id_width$}  {status}  {title}",
+            id = issue.id,
+            status = issue.status.as_deref().unwrap_or("-"),
+            title = issue.title,
+        );
+    }
+    Ok(())
+}
+
+fn cmd_blocked(start: &Path, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let report = repo.issue_dependencies()?;
+
+    if json_output {
+        let items = report
+            .blocked
+            .iter()
+            .map(|blocked| {
+                let mut item = ix_app::json::issue_ref_json(&blocked.issue);
+                item["waiting_on"] = json!(
+                    blocked
+                        .blockers
+                        .iter()
+                        .map(ix_app::json::issue_ref_json)
+                        .collect::<Vec<_>>()
+                );
+                item
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "total": items.len(), "items": items }))?;
+        return Ok(());
+    }
+
+    for blocked in report.blocked {
+        println!("{}: {}", blocked.issue.id, blocked.issue.title);
+        for blocker in blocked.blockers {
+            println!(
+                "  waiting on {} [{}]: {}",
+                blocker.id,
+                blocker.status.as_deref().unwrap_or("-"),
+                blocker.title
+            );
+        }
+    }
+    Ok(())
+}
+
 fn cmd_sync(start: &Path, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
     let stats = ix_app::sync(&repo)?;
@@ -406,33 +1178,113 @@ fn cmd_sync(start: &Path, json_output: bool) -> Result<()> {
             "modified": stats.modified,
             "deleted": stats.deleted,
             "unchanged": stats.unchanged,
+            "cache_hits": stats.cache_hits,
+            "cache_misses": stats.cache_misses,
         }))?;
     } else {
         println!(
-            "Synced: scanned={} added={} modified={} deleted={} unchanged={}",
-            stats.scanned, stats.added, stats.modified, stats.deleted, stats.unchanged
+            "Synced: scanned={} added={} modified={} deleted={} unchanged={} cache_hits={} cache_misses={}",
+            stats.scanned,
+            stats.added,
+            stats.modified,
+            stats.deleted,
+            stats.unchanged,
+            stats.cache_hits,
+            stats.cache_misses
         );
     }
     Ok(())
 }
 
-fn cmd_search(start: &Path, query: &str, limit: usize, json_output: bool) -> Result<()> {
+fn cmd_search(start: &Path, args: SearchArgs, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
-    let hits = ix_app::search(&repo, query, limit)?;
+    let request = args.into_request()?;
+    let hits = ix_app::search(&repo, &request)?;
+    let next_cursor = request.next_cursor(hits.len());
+    // Bold on terminals, Markdown emphasis when piped
+    let highlight = if std::io::stdout().is_terminal() {
+        ("\x1b[1m", "\x1b[0m")
+    } else {
+        ("**", "**")
+    };
+
+    if json_output {
+        let hits = hits
+            .iter()
+            .map(|hit| ix_app::json::search_hit_json(&repo, hit))
+            .collect::<Vec<_>>();
+        print_json(&json!({ "hits": hits, "next_cursor": next_cursor }))?;
+        return Ok(());
+    }
+
+    for hit in hits {
+        let kind = hit
+            .kind
+            .map_or("unknown", ix_core::entity::EntityKind::as_str);
+        // Reranked results are ordered by the reranker's score
+        let score = hit.rerank_score.unwrap_or(hit.score);
+        match repo.superseding_decision(&hit.id) {
+            Some(current) => println!(
+                "{score:.3}\t{}\t{}\t{}\t(superseded by {current})",
+                hit.id, kind, hit.title
+            ),
+            None => println!("{score:.3}\t{}\t{}\t{}", hit.id, kind, hit.title),
+        }
+        let details = [
+            hit.status.clone().unwrap_or_default(),
+            hit.tags.join(", "),
+            hit.path.clone().unwrap_or_default(),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+        if !details.is_empty() {
+            println!("\t{}", details.join(" | "));
+        }
+        if let Some(passage) = hit.passage.filter(|p| !p.text.is_empty()) {
+            let heading = if passage.heading_path.is_empty() {
+                "(top)".to_string()
+            } else {
+                passage.heading_path.join(" > ")
+            };
+            println!(
+                "\t{heading}, lines {}-{}",
+                passage.start_line, passage.end_line
+            );
+        }
+        if let Some(snippet) = &hit.snippet {
+            println!("\t{}", snippet.render(highlight.0, highlight.1));
+        }
+    }
+    if let Some(cursor) = next_cursor {
+        eprintln!("More results: --cursor {cursor}");
+    }
+
+    Ok(())
+}
+
+fn cmd_related(start: &Path, id: &str, limit: usize, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = repo.resolve_id(id)?;
+    let hits = ix_app::related(&repo, &id, limit)?;
 
     if json_output {
         let hits = hits
             .into_iter()
             .map(|h| {
                 json!({
-                    "score": h.score,
+                    "similarity": h.score,
+                    "distance": h.distance,
                     "id": h.id,
                     "kind": h.kind.map(ix_core::entity::EntityKind::as_str),
                     "title": h.title,
+                    "status": h.status,
+                    "tags": h.tags,
+                    "path": h.path,
                 })
             })
             .collect::<Vec<_>>();
-        print_json(&json!({ "hits": hits }))?;
+        print_json(&json!({ "id": id, "hits": hits }))?;
         return Ok(());
     }
 
@@ -442,34 +1294,427 @@ fn cmd_search(start: &Path, query: &str, limit: usize, json_output: bool) -> Res
             .map_or("unknown", ix_core::entity::EntityKind::as_str);
         println!("{:.3}\t{}\t{}\t{}", hit.score, hit.id, kind, hit.title);
     }
+    Ok(())
+}
+
+fn cmd_eval(start: &Path, args: &EvalArgs, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let golden_path = args
+        .golden
+        .clone()
+        .unwrap_or_else(|| repo.paths.eval_path());
+    let mut golden = ix_core::eval::GoldenSet::load(&golden_path)?;
+    if let Some(k) = args.k {
+        anyhow::ensure!(k > 0, "-k must be at least 1");
+        golden.k = k;
+    }
+    let thresholds = ix_core::eval::EvalThresholds {
+        recall: args.min_recall.or(golden.thresholds.recall),
+        mrr: args.min_mrr.or(golden.thresholds.mrr),
+        ndcg: args.min_ndcg.or(golden.thresholds.ndcg),
+    };
+
+    let mut results = vec![(
+        "current".to_string(),
+        ix_app::evaluate(&repo, &golden, args.mode)?,
+    )];
+    for path in &args.configs {
+        let variant = ix_app::config_variant(&repo, path)?;
+        ix_app::sync(&variant).with_context(|| format!("sync index for {}", path.display()))?;
+        let report = ix_app::evaluate(&variant, &golden, args.mode)?;
+        results.push((path.display().to_string(), report));
+    }
+    let failures = results
+        .iter()
+        .flat_map(|(config, report)| {
+            thresholds
+                .failures(report)
+                .into_iter()
+                .map(move |failure| format!("{config}: {failure}"))
+        })
+        .collect::<Vec<_>>();
+
+    if json_output {
+        print_json(&json!({
+            "ok": failures.is_empty(),
+            "golden": golden_path,
+            "k": golden.k,
+            "mode": args.mode.as_str(),
+            "thresholds": {
+                "recall": thresholds.recall,
+                "mrr": thresholds.mrr,
+                "ndcg": thresholds.ndcg,
+            },
+            "results": results
+                .iter()
+                .map(|(config, report)| eval_report_json(config, report))
+                .collect::<Vec<_>>(),
+            "failures": failures,
+        }))?;
+    } else {
+        print_eval_reports(&results);
+        for failure in &failures {
+            eprintln!("FAIL {failure}");
+        }
+    }
+
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn eval_report_json(config: &str, report: &ix_core::eval::EvalReport) -> serde_json::Value {
+    json!({
+        "config": config,
+        "recall": report.recall,
+        "mrr": report.mrr,
+        "ndcg": report.ndcg,
+        "queries": report.queries.iter().map(|query| json!({
+            "query": query.query,
+            "expected": query.expected,
+            "returned": query.returned,
+            "missed": query.missed(),
+            "recall": query.recall,
+            "reciprocal_rank": query.reciprocal_rank,
+            "ndcg": query.ndcg,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn print_eval_reports(results: &[(String, ix_core::eval::EvalReport)]) {
+    let k = results.first().map_or(0, |(_, report)| report.k);
+    let width = results
+        .iter()
+        .map(|(config, _)| config.len())
+        .max()
+        .unwrap_or(0)
+        .max("config".len());
+    println!(
+        "{:<width$}  {:>9}  {:>6}  {:>9}",
+        "config",
+        format!("recall@{k}"),
+        "MRR",
+        format!("nDCG@{k}")
+    );
+    for (config, report) in results {
+        println!(
+            "{config:<width$}  {:>9.3}  {:>6.3}  {:>9.3}",
+            report.recall, report.mrr, report.ndcg
+        );
+    }
+
+    for (config, report) in results {
+        for query in report.queries.iter().filter(|query| query.recall < 1.0) {
+            println!(
+                "{config}: {:?} missed {}",
+                query.query,
+                query.missed().join(", ")
+            );
+        }
+    }
+}
+
+fn cmd_dupes(start: &Path, threshold: f32, cross_kind: bool, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let pairs = ix_app::duplicates(&repo, threshold, cross_kind)?;
+
+    if json_output {
+        let entity = |entity: &ix_core::similarity::EntityRef| {
+            json!({
+                "id": entity.id,
+                "kind": entity.kind.map(ix_core::entity::EntityKind::as_str),
+                "title": entity.title,
+            })
+        };
+        let pairs = pairs
+            .iter()
+            .map(|pair| {
+                json!({
+                    "similarity": pair.similarity,
+                    "first": entity(&pair.first),
+                    "second": entity(&pair.second),
+                })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "threshold": threshold, "pairs": pairs }))?;
+        return Ok(());
+    }
+
+    if pairs.is_empty() {
+        println!("No likely duplicates at similarity >= {threshold}");
+    }
+    for pair in pairs {
+        println!(
+            "{:.3}\t{}\t{}\t{} / {}",
+            pair.similarity, pair.first.id, pair.second.id, pair.first.title, pair.second.title
+        );
+    }
+    Ok(())
+}
+
+fn cmd_suggest_tags(
+    start: &Path,
+    id: Option<&str>,
+    untagged: bool,
+    yes: bool,
+    json_output: bool,
+) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let ids = match id {
+        Some(id) => vec![repo.resolve_id(id)?],
+        None if untagged => repo
+            .list_untagged(None)?
+            .into_iter()
+            .map(|item| item.id)
+            .collect(),
+        None => Vec::new(),
+    };
+    let suggestions = ix_app::suggest_tags(&repo, &ids)?;
+    let interactive = !json_output && !yes && std::io::stdin().is_terminal();
+
+    let mut results = Vec::new();
+    for (entity, tags) in suggestions {
+        if tags.is_empty() {
+            continue;
+        }
+        let names = tags.iter().map(|s| s.tag.clone()).collect::<Vec<_>>();
+        if !json_output {
+            println!("{}\t{}", entity.id, entity.title);
+            for suggestion in &tags {
+                println!(
+                    "  {:.3}\t{}\t(from {})",
+                    suggestion.support,
+                    suggestion.tag,
+                    suggestion.sources.join(", ")
+                );
+            }
+        }
+        let apply = yes || (interactive && confirm(&format!("Add {}?", names.join(", ")))?);
+        let applied = apply && repo.add_tags(&entity.id, &names)?;
+        if applied && !json_output {
+            println!("Added tags to {}: {}", entity.id, names.join(", "));
+        }
+        results.push((entity, tags, applied));
+    }
+
+    if json_output {
+        let items = results
+            .iter()
+            .map(|(entity, tags, applied)| {
+                let tags = tags
+                    .iter()
+                    .map(|s| json!({ "tag": s.tag, "support": s.support, "sources": s.sources }))
+                    .collect::<Vec<_>>();
+                json!({
+                    "id": entity.id,
+                    "kind": entity.kind.map(ix_core::entity::EntityKind::as_str),
+                    "title": entity.title,
+                    "tags": tags,
+                    "applied": applied,
+                })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "total": items.len(), "items": items }))?;
+    } else if results.is_empty() {
+        println!("No tag suggestions");
+    } else if !yes && !interactive {
+        println!("Re-run with --yes to apply");
+    }
+    Ok(())
+}
+
+fn cmd_suggest_links(
+    start: &Path,
+    id: &str,
+    limit: usize,
+    min_similarity: f32,
+    yes: bool,
+    json_output: bool,
+) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = repo.resolve_id(id)?;
+    let suggestions = ix_app::suggest_links(&repo, &id, limit, min_similarity)?;
+    let interactive = !json_output && !yes && std::io::stdin().is_terminal();
+
+    let mut results = Vec::new();
+    for suggestion in suggestions {
+        if !json_output {
+            println!(
+                "{:.3}\t{} {} {}\t{}",
+                suggestion.similarity,
+                suggestion.from,
+                suggestion.rel,
+                suggestion.to,
+                suggestion.other.title
+            );
+        }
+        let prompt = format!(
+            "Link {} {} {}?",
+            suggestion.from, suggestion.rel, suggestion.to
+        );
+        let applied = yes || (interactive && confirm(&prompt)?);
+        if applied {
+            repo.link(&suggestion.from, &suggestion.rel, &suggestion.to)?;
+            if !json_output {
+                println!(
+                    "Linked {} {} {}",
+                    suggestion.from, suggestion.rel, suggestion.to
+                );
+            }
+        }
+        results.push((suggestion, applied));
+    }
 
+    if json_output {
+        let items = results
+            .iter()
+            .map(|(suggestion, applied)| {
+                json!({
+                    "from": suggestion.from,
+                    "rel": suggestion.rel,
+                    "to": suggestion.to,
+                    "similarity": suggestion.similarity,
+                    "other": {
+                        "id": suggestion.other.id,
+                        "kind": suggestion.other.kind.map(ix_core::entity::EntityKind::as_str),
+                        "title": suggestion.other.title,
+                    },
+                    "applied": applied,
+                })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "id": id, "total": items.len(), "items": items }))?;
+    } else if results.is_empty() {
+        println!("No link suggestions at similarity >= {min_similarity}");
+    } else if !yes && !interactive {
+        println!("Re-run with --yes to apply");
+    }
     Ok(())
 }
 
-fn cmd_graph(start: &Path, id: &str, json_output: bool) -> Result<()> {
+/// Ask a yes/no question on the terminal; anything but yes is no.
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::Write as _;
+
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+fn cmd_topics(start: &Path, count: Option<usize>, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let topics = ix_app::topics(&repo, count)?;
+
     if json_output {
-        let graph = build_graph_json(&repo, id)?;
-        print_json(&graph)?;
+        let topics = topics
+            .iter()
+            .map(|topic| {
+                let tags = topic
+                    .tags
+                    .iter()
+                    .map(|(tag, count)| json!({ "tag": tag, "count": count }))
+                    .collect::<Vec<_>>();
+                let members = topic
+                    .members
+                    .iter()
+                    .map(|entity| {
+                        json!({
+                            "id": entity.id,
+                            "kind": entity.kind.map(ix_core::entity::EntityKind::as_str),
+                            "title": entity.title,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "label": topic.label, "tags": tags, "members": members })
+            })
+            .collect::<Vec<_>>();
+        print_json(&json!({ "total": topics.len(), "topics": topics }))?;
         return Ok(());
     }
 
-    print_graph(&repo, id)
+    for (index, topic) in topics.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        let label = if topic.label.is_empty() {
+            "(unlabelled)"
+        } else {
+            topic.label.as_str()
+        };
+        println!("{label} ({})", topic.members.len());
+        for entity in &topic.members {
+            println!("  {}\t{}", entity.id, entity.title);
+        }
+    }
+    Ok(())
 }
 
-fn cmd_context(start: &Path, id: &str, json_output: bool) -> Result<()> {
+fn cmd_graph(start: &Path, args: &GraphArgs, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = repo.resolve_id(&args.id)?;
+    let filter = ix_core::graph::EdgeFilter::new(args.direction).with_labels(args.rel.clone());
+
+    if let Some(to) = &args.to {
+        let to = repo.resolve_id(to)?;
+        let path = ix_app::shortest_path(&repo, &id, &to, &filter)?;
+        if json_output {
+            return print_json(&ix_app::json::path_json(&id, &to, path.as_deref()));
+        }
+        match path {
+            Some(path) => {
+                for edge in path {
+                    println!("{} {} {}", edge.from, edge.rel, edge.to);
+                }
+            }
+            None => println!(
+                "No path from {id} to {to} within {} hops",
+                ix_core::graph::MAX_PATH_HOPS
+            ),
+        }
+        return Ok(());
+    }
+
+    let graph = ix_app::graph(&repo, &id, &filter, args.hops)?;
     if json_output {
-        let context = build_context_json(&repo, id)?;
-        print_json(&context)?;
+        return print_json(&ix_app::json::graph_json(&id, &graph, &filter, args.hops));
+    }
+    print_graph(&graph);
+    Ok(())
+}
+
+fn cmd_context(start: &Path, args: &ContextArgs, json_output: bool) -> Result<()> {
+    let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let config = args.config(&repo.config.context);
+    let (id, pack) = if let Some(query) = &args.query {
+        (None, ix_app::retrieve_context(&repo, query, &config)?)
+    } else {
+        let id = repo.resolve_id(args.id.as_deref().unwrap_or_default())?;
+        let pack = ix_app::entity_context(&repo, &id, &config)?;
+        (Some(id), pack)
+    };
+
+    if json_output {
+        print_json(&ix_app::json::context_pack_json(
+            id.as_deref(),
+            args.query.as_deref(),
+            &pack,
+        ))?;
         return Ok(());
     }
 
-    print_context(&repo, id)
+    print_context(&pack);
+    Ok(())
 }
 
 fn cmd_delete(start: &Path, id: &str, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = &repo.resolve_id(id)?;
     repo.delete_entity(id)?;
     if json_output {
         print_json(&json!({ "id": id, "deleted": true }))?;
@@ -481,6 +1726,7 @@ fn cmd_delete(start: &Path, id: &str, json_output: bool) -> Result<()> {
 
 fn cmd_edit(start: &Path, id: &str, json_output: bool) -> Result<()> {
     let repo = ix_core::repo::IxchelRepo::open_from(start)?;
+    let id = &repo.resolve_id(id)?;
     let path = repo
         .paths
         .entity_path(id)
@@ -514,175 +1760,56 @@ fn print_json(value: &serde_json::Value) -> Result<()> {
     Ok(())
 }
 
-const METADATA_KEYS: &[&str] = &[
-    "id",
-    "type",
-    "title",
-    "status",
-    "date",
-    "created_at",
-    "updated_at",
-    "created_by",
-    "tags",
-];
-
-fn print_graph(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<()> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-    println!("{id}: {title}");
-
-    for (rel, targets) in extract_relationships(&doc.frontmatter) {
-        println!("{rel}:");
-        for target in targets {
-            let target_title = repo
-                .paths
-                .entity_path(&target)
-                .and_then(|p| std::fs::read_to_string(&p).ok().map(|raw| (p, raw)))
-                .and_then(|(p, raw)| ix_core::markdown::parse_markdown(&p, &raw).ok())
-                .and_then(|d| ix_core::markdown::get_string(&d.frontmatter, "title"))
-                .unwrap_or_default();
-
-            if target_title.is_empty() {
-                println!("  - {target}");
-            } else {
-                println!("  - {target}: {target_title}");
-            }
+fn print_graph(graph: &ix_core::graph::Subgraph) {
+    for node in &graph.nodes {
+        let entity = &node.entity;
+        match node.depth {
+            0 => println!("{}: {}", entity.id, entity.title),
+            1 => println!("  {}: {} (1 hop)", entity.id, entity.title),
+            depth => println!("  {}: {} ({depth} hops)", entity.id, entity.title),
         }
     }
-
-    Ok(())
-}
-
-fn print_context(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<()> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let mut ids = vec![id.to_string()];
-    for (_, targets) in extract_relationships(&doc.frontmatter) {
-        ids.extend(targets);
+    if !graph.edges.is_empty() {
+        println!();
     }
+    for edge in &graph.edges {
+        println!("{} {} {}", edge.from, edge.rel, edge.to);
+    }
+}
 
-    ids.sort();
-    ids.dedup();
-
-    for entity_id in ids {
-        let path = repo
-            .paths
-            .entity_path(&entity_id)
-            .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {entity_id}"))?;
-        let raw = std::fs::read_to_string(&path)?;
-        let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-        let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-
+fn print_context(pack: &ix_core::context::ContextPack) {
+    for item in &pack.items {
         println!("---");
-        println!("{entity_id}: {title}");
+        println!("{}: {}", item.id, item.title);
+        match &item.via {
+            Some(step) if step.outgoing => println!(
+                "({:.3}, {} hop(s): {} {} {})",
+                item.score, item.hops, step.from, step.rel, item.id
+            ),
+            Some(step) => println!(
+                "({:.3}, {} hop(s): {} {} {})",
+                item.score, item.hops, item.id, step.rel, step.from
+            ),
+            None => println!("({:.3}, seed)", item.score),
+        }
         println!();
-        print!("{}", doc.body);
-        if !doc.body.ends_with('\n') {
+        print!("{}", item.body);
+        if !item.body.ends_with('\n') {
             println!();
         }
-    }
-
-    Ok(())
-}
-
-fn build_graph_json(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<serde_json::Value> {
-    let (root_title, outgoing) = collect_graph(repo, id)?;
-    Ok(json!({
-        "id": id,
-        "title": root_title,
-        "outgoing": outgoing.into_iter().map(|(rel, targets)| {
-            json!({
-                "rel": rel,
-                "targets": targets.into_iter().map(|(id, title)| json!({ "id": id, "title": title })).collect::<Vec<_>>(),
-            })
-        }).collect::<Vec<_>>()
-    }))
-}
-
-fn build_context_json(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<serde_json::Value> {
-    let items = collect_context(repo, id)?;
-    Ok(json!({
-        "id": id,
-        "items": items.into_iter().map(|(id, title, body)| json!({ "id": id, "title": title, "body": body })).collect::<Vec<_>>(),
-    }))
-}
-
-type GraphEdgeTarget = (String, Option<String>);
-type GraphOutgoing = Vec<(String, Vec<GraphEdgeTarget>)>;
-type CollectedGraph = (String, GraphOutgoing);
-
-fn collect_graph(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<CollectedGraph> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-    let mut outgoing = Vec::new();
-
-    for (rel, targets) in extract_relationships(&doc.frontmatter) {
-        let mut items = Vec::new();
-        for target in targets {
-            let target_title = repo
-                .paths
-                .entity_path(&target)
-                .and_then(|p| std::fs::read_to_string(&p).ok().map(|raw| (p, raw)))
-                .and_then(|(p, raw)| ix_core::markdown::parse_markdown(&p, &raw).ok())
-                .and_then(|d| ix_core::markdown::get_string(&d.frontmatter, "title"));
-            items.push((target, target_title));
+        if item.truncated {
+            println!("[truncated to fit the {}-token budget]", pack.budget_tokens);
         }
-        outgoing.push((rel, items));
-    }
-
-    Ok((title, outgoing))
-}
-
-fn collect_context(
-    repo: &ix_core::repo::IxchelRepo,
-    id: &str,
-) -> Result<Vec<(String, String, String)>> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let mut ids = vec![id.to_string()];
-    for (_, targets) in extract_relationships(&doc.frontmatter) {
-        ids.extend(targets);
     }
 
-    ids.sort();
-    ids.dedup();
-
-    let mut out = Vec::new();
-    for entity_id in ids {
-        let path = repo
-            .paths
-            .entity_path(&entity_id)
-            .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {entity_id}"))?;
-        let raw = std::fs::read_to_string(&path)?;
-        let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-        let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-        out.push((entity_id, title, doc.body));
+    if !pack.omitted.is_empty() {
+        eprintln!(
+            "Omitted {} entities over the {}-token budget: {}",
+            pack.omitted.len(),
+            pack.budget_tokens,
+            pack.omitted.join(", ")
+        );
     }
-
-    Ok(out)
 }
 
 fn cmd_watch(start: &Path, unwatch: bool, foreground: bool, json_output: bool) -> Result<()> {
@@ -833,43 +1960,3 @@ fn is_in_ignored_dir(repo_root: &Path, path: &Path) -> bool {
 
     false
 }
-
-fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<String>)> {
-    let mut rels = Vec::new();
-
-    for (key, value) in frontmatter {
-        let YamlValue::String(key) = key else {
-            continue;
-        };
-
-        if METADATA_KEYS.contains(&key.as_str()) {
-            continue;
-        }
-
-        let targets = match value {
-            YamlValue::Sequence(seq) => seq
-                .iter()
-                .filter_map(|v| match v {
-                    YamlValue::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .collect::<Vec<_>>(),
-            YamlValue::String(s) => vec![s.clone()],
-            _ => Vec::new(),
-        };
-
-        let targets = targets
-            .into_iter()
-            .filter(|t| ix_core::entity::looks_like_entity_id(t))
-            .collect::<Vec<_>>();
-
-        if targets.is_empty() {
-            continue;
-        }
-
-        rels.push((key.clone(), targets));
-    }
-
-    rels.sort_by(|a, b| a.0.cmp(&b.0));
-    rels
-}
@@ -72,13 +72,15 @@ impl SyncWorker {
                     duration_ms,
                 };
                 tracing::info!(
-                    "Sync job {} completed: scanned={}, added={}, modified={}, deleted={}, unchanged={}, duration={}ms",
+                    "Sync job {} completed: scanned={}, added={}, modified={}, deleted={}, unchanged={}, cache_hits={}, cache_misses={}, duration={}ms",
                     job_id,
                     core_stats.scanned,
                     core_stats.added,
                     core_stats.modified,
                     core_stats.deleted,
                     core_stats.unchanged,
+                    core_stats.cache_hits,
+                    core_stats.cache_misses,
                     duration_ms
                 );
                 self.queue.complete(job_id, stats).await;
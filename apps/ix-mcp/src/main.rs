@@ -138,51 +138,50 @@ fn tools_list_result() -> Value {
             },
             {
                 "name": "ixchel_search",
-                "description": "Semantic search over Ixchel entities",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
-                        "query": { "type": "string" },
-                        "limit": { "type": "integer", "minimum": 1, "default": 10 }
-                    },
-                    "required": ["query"]
-                }
+                "description": "Semantic, full-text or hybrid search over Ixchel entities",
+                "inputSchema": search_input_schema()
             },
             {
                 "name": "ixchel_show",
-                "description": "Read an entity Markdown file by id",
+                "description": "Read an entity Markdown file by id, with checklist progress for issues",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
-                        "id": { "type": "string" }
+                        "id": { "type": "string", "description": "Entity ID, unique ID prefix, or title slug" }
                     },
                     "required": ["id"]
                 }
             },
             {
                 "name": "ixchel_graph",
-                "description": "Return outgoing relationships for an entity",
+                "description": "Return the entities and relationships around an entity from the index, or the shortest path to another entity",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
-                        "id": { "type": "string" }
+                        "id": { "type": "string", "description": "Entity ID, unique ID prefix, or title slug" },
+                        "hops": { "type": "integer", "minimum": 0, "description": "Relationship hops to follow (default 1)" },
+                        "direction": { "type": "string", "enum": ["outgoing", "incoming", "both"], "description": "Which way to follow relationships (default both)" },
+                        "rels": { "type": "array", "items": { "type": "string" }, "description": "Only follow these relationships" },
+                        "to": { "type": "string", "description": "Return the shortest path to this entity instead" }
                     },
                     "required": ["id"]
                 }
             },
             {
                 "name": "ixchel_context",
-                "description": "Return a basic 1-hop context pack for an entity",
+                "description": "Return a ranked context pack around an entity, or seeded from search hits for a query, expanded through relationships within a token budget",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
-                        "id": { "type": "string" }
-                    },
-                    "required": ["id"]
+                        "id": { "type": "string", "description": "Entity ID, unique ID prefix, or title slug" },
+                        "query": { "type": "string", "description": "Question to seed the context from, instead of an id" },
+                        "hops": { "type": "integer", "minimum": 0, "description": "Relationship hops to follow (default: [context] hops)" },
+                        "budget_tokens": { "type": "integer", "minimum": 1, "description": "Approximate token budget (default: [context] budget_tokens)" },
+                        "seeds": { "type": "integer", "minimum": 1, "description": "Search hits used as seeds for a query (default: [context] seeds)" }
+                    }
                 }
             },
             {
@@ -196,6 +195,28 @@ fn tools_list_result() -> Value {
                         "untagged": { "type": "boolean", "description": "List entities missing tags instead of tag counts" }
                     }
                 }
+            },
+            {
+                "name": "ixchel_ready",
+                "description": "List open issues with no open blockers, plus blocked issues and what they wait on",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" }
+                    }
+                }
+            },
+            {
+                "name": "ixchel_tasks",
+                "description": "List open checklist items and per-entity completion",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
+                        "kind": { "type": "string", "description": "Entity kind to scan (defaults to issue)" },
+                        "all": { "type": "boolean", "description": "Include completed items" }
+                    }
+                }
             }
         ]
     })
@@ -219,10 +240,36 @@ fn handle_tools_call(params: Option<Value>) -> Result<Value> {
         "ixchel_graph" => tool_graph(&args),
         "ixchel_context" => tool_context(&args),
         "ixchel_tags" => tool_tags(&args),
+        "ixchel_tasks" => tool_tasks(&args),
+        "ixchel_ready" => tool_ready(&args),
         _ => anyhow::bail!("Unknown tool: {name}"),
     }
 }
 
+/// Input schema of `ixchel_search`, including its filters and paging.
+fn search_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "repo": { "type": "string", "description": "Path inside the target git repository (defaults to CWD)" },
+            "query": { "type": "string" },
+            "limit": { "type": "integer", "minimum": 1, "default": 10 },
+            "mode": { "type": "string", "enum": ["vector", "lexical", "hybrid"], "default": "vector" },
+            "kinds": { "type": "array", "items": { "type": "string" }, "description": "Only return entities of these kinds" },
+            "statuses": { "type": "array", "items": { "type": "string" }, "description": "Only return entities with one of these statuses" },
+            "tags": { "type": "array", "items": { "type": "string" }, "description": "Only return entities carrying all of these tags" },
+            "created_after": { "type": "string", "description": "YYYY-MM-DD or RFC 3339, inclusive" },
+            "created_before": { "type": "string", "description": "YYYY-MM-DD or RFC 3339, exclusive" },
+            "updated_after": { "type": "string", "description": "YYYY-MM-DD or RFC 3339, inclusive" },
+            "updated_before": { "type": "string", "description": "YYYY-MM-DD or RFC 3339, exclusive" },
+            "offset": { "type": "integer", "minimum": 0, "default": 0 },
+            "cursor": { "type": "string", "description": "next_cursor of a previous page" },
+            "min_score": { "type": "number" }
+        },
+        "required": ["query"]
+    })
+}
+
 fn resolve_repo_path(args: &Value) -> Result<PathBuf> {
     if let Some(path) = args.get("repo").and_then(Value::as_str) {
         return Ok(PathBuf::from(path));
@@ -240,7 +287,9 @@ fn tool_sync(args: &Value) -> Result<Value> {
         "added": stats.added,
         "modified": stats.modified,
         "deleted": stats.deleted,
-        "unchanged": stats.unchanged
+        "unchanged": stats.unchanged,
+        "cache_hits": stats.cache_hits,
+        "cache_misses": stats.cache_misses
     }))
 }
 
@@ -255,23 +304,77 @@ fn tool_search(args: &Value) -> Result<Value> {
         .and_then(Value::as_u64)
         .and_then(|n| usize::try_from(n).ok())
         .unwrap_or(10);
+    let mode = args
+        .get("mode")
+        .and_then(Value::as_str)
+        .map(str::parse::<ix_core::index::SearchMode>)
+        .transpose()?
+        .unwrap_or_default();
+    let offset = args
+        .get("offset")
+        .and_then(Value::as_u64)
+        .and_then(|n| usize::try_from(n).ok())
+        .unwrap_or(0);
+    #[allow(clippy::cast_possible_truncation)]
+    let min_score = args
+        .get("min_score")
+        .and_then(Value::as_f64)
+        .map(|n| n as f32);
+
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let date = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_str)
+            .map(ix_core::index::parse_date)
+            .transpose()
+    };
+    let filters = ix_core::index::SearchFilters {
+        kinds: strings("kinds")
+            .iter()
+            .map(|kind| kind.parse::<ix_core::entity::EntityKind>())
+            .collect::<Result<_, _>>()?,
+        statuses: strings("statuses"),
+        tags: strings("tags"),
+        created: ix_core::index::DateRange {
+            after: date("created_after")?,
+            before: date("created_before")?,
+        },
+        updated: ix_core::index::DateRange {
+            after: date("updated_after")?,
+            before: date("updated_before")?,
+        },
+    };
+
+    let mut request = ix_core::index::SearchRequest::new(query, limit)
+        .with_mode(mode)
+        .with_filters(filters)
+        .with_offset(offset)
+        .with_min_score(min_score);
+    if let Some(cursor) = args.get("cursor").and_then(Value::as_str) {
+        request = request.with_cursor(cursor)?;
+    }
 
     let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
-    let hits = ix_app::search(&repo, query, limit)?;
+    let hits = ix_app::search(&repo, &request)?;
+    let next_cursor = request.next_cursor(hits.len());
 
     let hits = hits
-        .into_iter()
-        .map(|h| {
-            json!({
-                "score": h.score,
-                "id": h.id,
-                "kind": h.kind.map(ix_core::entity::EntityKind::as_str),
-                "title": h.title,
-            })
-        })
+        .iter()
+        .map(|hit| ix_app::json::search_hit_json(&repo, hit))
         .collect::<Vec<_>>();
 
-    tool_text(&json!({ "hits": hits }))
+    tool_text(&json!({ "hits": hits, "next_cursor": next_cursor }))
 }
 
 fn tool_show(args: &Value) -> Result<Value> {
@@ -282,9 +385,9 @@ fn tool_show(args: &Value) -> Result<Value> {
         .ok_or_else(|| anyhow::anyhow!("ixchel_show missing arguments.id"))?;
 
     let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
-    let raw = repo.read_raw(id)?;
+    let id = repo.resolve_id(id)?;
 
-    tool_text(&json!({ "id": id, "raw": raw }))
+    tool_text(&ix_app::json::show_json(&repo, &id)?)
 }
 
 fn tool_graph(args: &Value) -> Result<Value> {
@@ -293,24 +396,77 @@ fn tool_graph(args: &Value) -> Result<Value> {
         .get("id")
         .and_then(Value::as_str)
         .ok_or_else(|| anyhow::anyhow!("ixchel_graph missing arguments.id"))?;
+    let hops = args
+        .get("hops")
+        .and_then(Value::as_u64)
+        .and_then(|n| usize::try_from(n).ok())
+        .unwrap_or(1);
+    let direction = args
+        .get("direction")
+        .and_then(Value::as_str)
+        .map(str::parse::<ix_core::graph::Direction>)
+        .transpose()?
+        .unwrap_or_default();
+    let rels = args
+        .get("rels")
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let filter = ix_core::graph::EdgeFilter::new(direction).with_labels(rels);
 
     let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
-    let graph = build_graph_json(&repo, id)?;
+    let id = repo.resolve_id(id)?;
+    if let Some(to) = args.get("to").and_then(Value::as_str) {
+        let to = repo.resolve_id(to)?;
+        let path = ix_app::shortest_path(&repo, &id, &to, &filter)?;
+        return tool_text(&ix_app::json::path_json(&id, &to, path.as_deref()));
+    }
 
-    tool_text(&graph)
+    let graph = ix_app::graph(&repo, &id, &filter, hops)?;
+    tool_text(&ix_app::json::graph_json(&id, &graph, &filter, hops))
 }
 
 fn tool_context(args: &Value) -> Result<Value> {
     let repo_path = resolve_repo_path(args)?;
-    let id = args
-        .get("id")
-        .and_then(Value::as_str)
-        .ok_or_else(|| anyhow::anyhow!("ixchel_context missing arguments.id"))?;
-
     let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
-    let context = build_context_json(&repo, id)?;
-
-    tool_text(&context)
+    let count = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_u64)
+            .and_then(|n| usize::try_from(n).ok())
+    };
+    let base = &repo.config.context;
+    let config = ix_core::context::ContextConfig {
+        hops: count("hops").unwrap_or(base.hops),
+        budget_tokens: count("budget_tokens").unwrap_or(base.budget_tokens),
+        seeds: count("seeds").unwrap_or(base.seeds),
+        ..base.clone()
+    };
+
+    let query = args.get("query").and_then(Value::as_str);
+    let (id, pack) = match (args.get("id").and_then(Value::as_str), query) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("ixchel_context takes arguments.id or arguments.query, not both")
+        }
+        (None, Some(query)) => (None, ix_app::retrieve_context(&repo, query, &config)?),
+        (Some(id), None) => {
+            let id = repo.resolve_id(id)?;
+            let pack = ix_app::entity_context(&repo, &id, &config)?;
+            (Some(id), pack)
+        }
+        (None, None) => anyhow::bail!("ixchel_context missing arguments.id or arguments.query"),
+    };
+
+    tool_text(&ix_app::json::context_pack_json(
+        id.as_deref(),
+        query,
+        &pack,
+    ))
 }
 
 fn tool_tags(args: &Value) -> Result<Value> {
@@ -362,149 +518,86 @@ fn tool_tags(args: &Value) -> Result<Value> {
     tool_text(&json!({ "total": tags.len(), "tags": tags }))
 }
 
-fn tool_text(payload: &Value) -> Result<Value> {
-    let text = serde_json::to_string_pretty(payload)?;
-    Ok(json!({
-        "content": [{ "type": "text", "text": text }],
-    }))
-}
+fn tool_tasks(args: &Value) -> Result<Value> {
+    let repo_path = resolve_repo_path(args)?;
+    let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
+    let kind = args
+        .get("kind")
+        .and_then(Value::as_str)
+        .map(|value| {
+            value
+                .parse::<ix_core::entity::EntityKind>()
+                .map_err(|err| anyhow::anyhow!("ixchel_tasks invalid kind: {err}"))
+        })
+        .transpose()?
+        .unwrap_or(ix_core::entity::EntityKind::Issue);
+    let all = args.get("all").and_then(Value::as_bool).unwrap_or(false);
 
-const METADATA_KEYS: &[&str] = &[
-    "id",
-    "type",
-    "title",
-    "status",
-    "date",
-    "created_at",
-    "updated_at",
-    "created_by",
-    "tags",
-];
-
-fn build_graph_json(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<Value> {
-    let (root_title, outgoing) = collect_graph(repo, id)?;
-    Ok(json!({
-        "id": id,
-        "title": root_title,
-        "outgoing": outgoing.into_iter().map(|(rel, targets)| {
+    let items = repo
+        .list_tasks(kind, all)?
+        .into_iter()
+        .map(|task| {
             json!({
-                "rel": rel,
-                "targets": targets.into_iter().map(|(id, title)| json!({ "id": id, "title": title })).collect::<Vec<_>>(),
+                "id": task.id,
+                "title": task.title,
+                "n": task.item.index,
+                "done": task.item.checked,
+                "text": task.item.text,
+                "section": task.item.section,
             })
-        }).collect::<Vec<_>>()
-    }))
-}
-
-fn build_context_json(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<Value> {
-    let items = collect_context(repo, id)?;
-    Ok(json!({
-        "id": id,
-        "items": items.into_iter().map(|(id, title, body)| json!({ "id": id, "title": title, "body": body })).collect::<Vec<_>>(),
-    }))
-}
+        })
+        .collect::<Vec<_>>();
 
-type GraphEdgeTarget = (String, Option<String>);
-type GraphOutgoing = Vec<(String, Vec<GraphEdgeTarget>)>;
-type CollectedGraph = (String, GraphOutgoing);
-
-fn collect_graph(repo: &ix_core::repo::IxchelRepo, id: &str) -> Result<CollectedGraph> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-    let mut outgoing = Vec::new();
-
-    for (rel, targets) in extract_relationships(&doc.frontmatter) {
-        let mut items = Vec::new();
-        for target in targets {
-            let target_title = repo
-                .paths
-                .entity_path(&target)
-                .and_then(|p| std::fs::read_to_string(&p).ok().map(|raw| (p, raw)))
-                .and_then(|(p, raw)| ix_core::markdown::parse_markdown(&p, &raw).ok())
-                .and_then(|d| ix_core::markdown::get_string(&d.frontmatter, "title"));
-            items.push((target, target_title));
-        }
-        outgoing.push((rel, items));
-    }
+    let progress = repo
+        .list(Some(kind), ix_core::repo::ListSort::default())?
+        .into_iter()
+        .filter(|entity| entity.tasks.total > 0)
+        .map(|entity| {
+            json!({
+                "id": entity.id,
+                "title": entity.title,
+                "done": entity.tasks.done,
+                "total": entity.tasks.total,
+                "percent": entity.tasks.percent(),
+            })
+        })
+        .collect::<Vec<_>>();
 
-    Ok((title, outgoing))
+    tool_text(&json!({ "total": items.len(), "items": items, "progress": progress }))
 }
 
-fn collect_context(
-    repo: &ix_core::repo::IxchelRepo,
-    id: &str,
-) -> Result<Vec<(String, String, String)>> {
-    let path = repo
-        .paths
-        .entity_path(id)
-        .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {id}"))?;
-    let raw = std::fs::read_to_string(&path)?;
-    let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-
-    let mut ids = vec![id.to_string()];
-    for (_, targets) in extract_relationships(&doc.frontmatter) {
-        ids.extend(targets);
-    }
+fn tool_ready(args: &Value) -> Result<Value> {
+    let repo_path = resolve_repo_path(args)?;
+    let repo = ix_core::repo::IxchelRepo::open_from(&repo_path)?;
+    let report = repo.issue_dependencies()?;
 
-    ids.sort();
-    ids.dedup();
-
-    let mut out = Vec::new();
-    for entity_id in ids {
-        let path = repo
-            .paths
-            .entity_path(&entity_id)
-            .ok_or_else(|| anyhow::anyhow!("Unknown entity id prefix: {entity_id}"))?;
-        let raw = std::fs::read_to_string(&path)?;
-        let doc = ix_core::markdown::parse_markdown(&path, &raw)?;
-        let title = ix_core::markdown::get_string(&doc.frontmatter, "title").unwrap_or_default();
-        out.push((entity_id, title, doc.body));
-    }
+    let ready = report
+        .ready
+        .iter()
+        .map(ix_app::json::issue_ref_json)
+        .collect::<Vec<_>>();
+    let blocked = report
+        .blocked
+        .iter()
+        .map(|blocked| {
+            let mut item = ix_app::json::issue_ref_json(&blocked.issue);
+            item["waiting_on"] = json!(
+                blocked
+                    .blockers
+                    .iter()
+                    .map(ix_app::json::issue_ref_json)
+                    .collect::<Vec<_>>()
+            );
+            item
+        })
+        .collect::<Vec<_>>();
 
-    Ok(out)
+    tool_text(&json!({ "ready": ready, "blocked": blocked }))
 }
 
-fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<String>)> {
-    let mut rels = Vec::new();
-
-    for (key, value) in frontmatter {
-        let serde_yaml::Value::String(key) = key else {
-            continue;
-        };
-
-        if METADATA_KEYS.contains(&key.as_str()) {
-            continue;
-        }
-
-        let targets = match value {
-            serde_yaml::Value::Sequence(seq) => seq
-                .iter()
-                .filter_map(|v| match v {
-                    serde_yaml::Value::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .collect::<Vec<_>>(),
-            serde_yaml::Value::String(s) => vec![s.clone()],
-            _ => Vec::new(),
-        };
-
-        let targets = targets
-            .into_iter()
-            .filter(|t| ix_core::entity::looks_like_entity_id(t))
-            .collect::<Vec<_>>();
-
-        if targets.is_empty() {
-            continue;
-        }
-
-        rels.push((key.clone(), targets));
-    }
-
-    rels.sort_by(|a, b| a.0.cmp(&b.0));
-    rels
+fn tool_text(payload: &Value) -> Result<Value> {
+    let text = serde_json::to_string_pretty(payload)?;
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+    }))
 }
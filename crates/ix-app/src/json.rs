@@ -0,0 +1,184 @@
+//! JSON shapes shared by the CLI's `--json` output and the MCP tools, so
+//! both surfaces report the same fields.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ix_core::chunk::Chunk;
+use ix_core::context::ContextPack;
+use ix_core::entity::{EntityKind, kind_from_id};
+use ix_core::graph::{EdgeFilter, Subgraph};
+use ix_core::index::SearchHit;
+use ix_core::repo::{IssueRef, IxchelRepo, Relationship, TaskProgress};
+use ix_core::snippet::Snippet;
+use serde_json::{Value, json};
+
+/// JSON for a search hit, with the decision superseding it as `current`.
+#[must_use]
+pub fn search_hit_json(repo: &IxchelRepo, hit: &SearchHit) -> Value {
+    json!({
+        "score": hit.score,
+        "distance": hit.distance,
+        "rerank_score": hit.rerank_score,
+        "id": hit.id,
+        "kind": hit.kind.map(EntityKind::as_str),
+        "title": hit.title,
+        "status": hit.status,
+        "tags": hit.tags,
+        "path": hit.path,
+        "current": repo.superseding_decision(&hit.id),
+        "passage": hit.passage.as_ref().map(passage_json),
+        "snippet": hit.snippet.as_ref().map(snippet_json),
+    })
+}
+
+/// JSON for the best-matching passage of a search hit.
+#[must_use]
+pub fn passage_json(passage: &Chunk) -> Value {
+    json!({
+        "heading_path": passage.heading_path,
+        "start_line": passage.start_line,
+        "end_line": passage.end_line,
+        "text": passage.text,
+    })
+}
+
+/// JSON for a search snippet; highlights are byte ranges into `text`.
+#[must_use]
+pub fn snippet_json(snippet: &Snippet) -> Value {
+    json!({
+        "text": snippet.text,
+        "highlights": snippet.highlights,
+        "highlighted": snippet.render("**", "**"),
+    })
+}
+
+/// JSON for an entity's checklist completion.
+#[must_use]
+pub fn task_progress_json(progress: TaskProgress) -> Value {
+    json!({
+        "done": progress.done,
+        "total": progress.total,
+        "percent": progress.percent(),
+    })
+}
+
+/// JSON for `show`: the raw file of `id`, the decision superseding it as
+/// `current`, and checklist progress as `tasks` for issues.
+pub fn show_json(repo: &IxchelRepo, id: &str) -> Result<Value> {
+    let raw = repo.read_raw(id)?;
+    let tasks = if kind_from_id(id) == Some(EntityKind::Issue) {
+        Some(task_progress_json(repo.task_progress(id)?))
+    } else {
+        None
+    };
+    Ok(json!({
+        "id": id,
+        "raw": raw,
+        "current": repo.superseding_decision(id),
+        "tasks": tasks,
+    }))
+}
+
+/// JSON for an issue in a dependency report.
+#[must_use]
+pub fn issue_ref_json(issue: &IssueRef) -> Value {
+    json!({
+        "id": issue.id,
+        "title": issue.title,
+        "status": issue.status,
+        "path": issue.path,
+    })
+}
+
+/// JSON for a context pack built around `id` or for `query`.
+#[must_use]
+pub fn context_pack_json(id: Option<&str>, query: Option<&str>, pack: &ContextPack) -> Value {
+    json!({
+        "id": id,
+        "query": query,
+        "tokens": pack.tokens,
+        "budget_tokens": pack.budget_tokens,
+        "omitted": pack.omitted,
+        "items": pack.items.iter().map(|item| json!({
+            "id": item.id,
+            "kind": item.kind.map(EntityKind::as_str),
+            "title": item.title,
+            "body": item.body,
+            "score": item.score,
+            "hops": item.hops,
+            "via": item.via.as_ref().map(|step| json!({
+                "from": step.from,
+                "rel": step.rel,
+                "direction": if step.outgoing { "outgoing" } else { "incoming" },
+            })),
+            "tokens": item.tokens,
+            "truncated": item.truncated,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// JSON for a traversal from `id`: the entities reached, the relationships
+/// between them, and `id`'s own relationships grouped by name.
+#[must_use]
+pub fn graph_json(id: &str, graph: &Subgraph, filter: &EdgeFilter, hops: usize) -> Value {
+    let title = |id: &str| {
+        graph
+            .nodes
+            .iter()
+            .find(|node| node.entity.id == id)
+            .map(|node| node.entity.title.clone())
+    };
+    let grouped = |outgoing: bool| {
+        let mut by_rel: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+        for edge in &graph.edges {
+            let other = match (outgoing, edge.from == id, edge.to == id) {
+                (true, true, _) => &edge.to,
+                (false, _, true) => &edge.from,
+                _ => continue,
+            };
+            by_rel
+                .entry(&edge.rel)
+                .or_default()
+                .push(json!({ "id": other, "title": title(other) }));
+        }
+        let key = if outgoing { "targets" } else { "sources" };
+        by_rel
+            .into_iter()
+            .map(|(rel, entities)| json!({ "rel": rel, key: entities }))
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "id": id,
+        "title": title(id),
+        "direction": filter.direction.as_str(),
+        "rels": filter.labels,
+        "hops": hops,
+        "outgoing": grouped(true),
+        "incoming": grouped(false),
+        "nodes": graph.nodes.iter().map(|node| json!({
+            "id": node.entity.id,
+            "kind": node.entity.kind.map(EntityKind::as_str),
+            "title": node.entity.title,
+            "depth": node.depth,
+        })).collect::<Vec<_>>(),
+        "edges": graph.edges.iter().map(relationship_json).collect::<Vec<_>>(),
+    })
+}
+
+/// JSON for the shortest path from `from` to `to`, `null` when there is none.
+#[must_use]
+pub fn path_json(from: &str, to: &str, path: Option<&[Relationship]>) -> Value {
+    json!({
+        "from": from,
+        "to": to,
+        "hops": path.map(<[_]>::len),
+        "path": path.map(|path| path.iter().map(relationship_json).collect::<Vec<_>>()),
+    })
+}
+
+/// JSON for one relationship, as declared on `from`.
+#[must_use]
+pub fn relationship_json(edge: &Relationship) -> Value {
+    json!({ "from": edge.from, "rel": edge.rel, "to": edge.to })
+}
@@ -1,7 +1,8 @@
 //! Ixchel application wiring layer.
 //!
 //! This crate provides the high-level API for interacting with Ixchel storage backends.
-//! Currently only `SurrealDB` is supported in the published crate.
+//! The published crate supports `SurrealDB` and the dependency-free `flat`
+//! backend.
 //!
 //! # `HelixDB` Support
 //!
@@ -9,61 +10,465 @@
 //! dependency. If you need `HelixDB` support, build from source:
 //! <https://github.com/kevinmichaelchen/ixchel>
 
+pub mod json;
+
+use std::collections::HashMap;
+use std::path::Path;
+
 use anyhow::{Context, Result};
-use ix_core::index::{IndexBackend, SearchHit, SyncStats};
-use ix_core::repo::IxchelRepo;
+use ix_core::chunk::Chunk;
+use ix_core::context::{ContextConfig, ContextPack, build_context};
+use ix_core::entity::EntityKind;
+use ix_core::eval::{EvalReport, GoldenSet, QueryEval};
+use ix_core::graph::{Direction, EdgeFilter, Subgraph};
+use ix_core::index::{
+    IndexBackend, IndexMetadata, IndexMetadataStatus, SearchHit, SearchMode, SearchRequest,
+    SyncStats, apply_rerank_scores,
+};
+use ix_core::repo::{IxchelRepo, ListSort, Relationship};
+use ix_core::similarity::{
+    DUPLICATE_THRESHOLD, DuplicatePair, EntityRef, EntityVector, LinkSuggestion,
+    SIMILAR_TITLE_THRESHOLD, find_duplicates, title_similarity,
+};
+use ix_core::topics::{TagSuggestion, Topic, cluster_topics, default_topic_count};
+pub use ix_embeddings::{InstalledModel, ModelStore, ModelVerification, PruneStats};
 
 fn backend_name(repo: &IxchelRepo) -> String {
     repo.config.storage.backend.trim().to_ascii_lowercase()
 }
 
 pub fn sync(repo: &IxchelRepo) -> Result<SyncStats> {
-    match backend_name(repo).as_str() {
-        "surrealdb" => {
-            let mut index =
-                ix_storage_surrealdb::SurrealDbIndex::open(repo).context("open surrealdb index")?;
-            IndexBackend::sync(&mut index, repo).context("sync surrealdb index")
-        }
-        "helixdb" => anyhow::bail!(
-            "HelixDB backend is not available in this build. \
-             The crates.io version only supports SurrealDB because helix-db \
-             uses a git dependency. Build from source for HelixDB support: \
-             https://github.com/kevinmichaelchen/ixchel"
-        ),
-        backend => anyhow::bail!("Unsupported storage backend: {backend}"),
+    open_index(repo)?.sync(repo).context("sync index")
+}
+
+/// Search the index, reranking the top candidates when `[rerank]` is enabled.
+///
+/// With reranking the page is cut after reranking, so the backend is asked
+/// for the first candidates from offset zero.
+pub fn search(repo: &IxchelRepo, request: &SearchRequest) -> Result<Vec<SearchHit>> {
+    let rerank = &repo.config.rerank;
+    if !rerank.enabled {
+        return search_index(repo, request);
     }
+
+    let reranker = ix_embeddings::Reranker::with_model_store(rerank, &model_store(repo))
+        .map_err(|e| anyhow::anyhow!("Failed to initialize reranker: {e}"))?;
+    let candidates = SearchRequest {
+        limit: request.window().max(rerank.candidates),
+        offset: 0,
+        ..request.clone()
+    };
+    let hits = search_index(repo, &candidates)?;
+    let texts = hits.iter().map(SearchHit::rerank_text).collect::<Vec<_>>();
+    let scores = reranker
+        .rerank(
+            &request.query,
+            &texts.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+        .map_err(|e| anyhow::anyhow!("Reranking failed: {e}"))?;
+    let hits = apply_rerank_scores(hits, &scores, request.window())?;
+    Ok(hits.into_iter().skip(request.offset).collect())
+}
+
+fn search_index(repo: &IxchelRepo, request: &SearchRequest) -> Result<Vec<SearchHit>> {
+    open_index(repo)?.search(request).context("search index")
+}
+
+/// Entities most similar to `id`, from its stored vector.
+pub fn related(repo: &IxchelRepo, id: &str, limit: usize) -> Result<Vec<SearchHit>> {
+    open_index(repo)?
+        .related(id, limit)
+        .context("find related entities")
 }
 
-pub fn search(repo: &IxchelRepo, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
-    match backend_name(repo).as_str() {
-        "surrealdb" => {
-            let index =
-                ix_storage_surrealdb::SurrealDbIndex::open(repo).context("open surrealdb index")?;
-            IndexBackend::search(&index, query, limit).context("search surrealdb index")
+/// Pairs of entities at or above `threshold` cosine similarity.
+pub fn duplicates(
+    repo: &IxchelRepo,
+    threshold: f32,
+    cross_kind: bool,
+) -> Result<Vec<DuplicatePair>> {
+    let entities = entity_vectors(repo)?;
+    Ok(find_duplicates(&entities, threshold, cross_kind))
+}
+
+/// Tag suggestions for each of `ids` (see [`ix_core::topics::suggest_tags`]).
+///
+/// Entities without a stored vector, e.g. created since the last sync, are
+/// left out.
+pub fn suggest_tags(
+    repo: &IxchelRepo,
+    ids: &[String],
+) -> Result<Vec<(EntityRef, Vec<TagSuggestion>)>> {
+    let entities = entity_vectors(repo)?;
+    let tags = entity_tags(repo)?;
+    Ok(ids
+        .iter()
+        .filter_map(|id| entities.iter().find(|entity| &entity.id == id))
+        .map(|target| {
+            let suggestions = ix_core::topics::suggest_tags(target, &entities, &tags);
+            (EntityRef::from(target), suggestions)
+        })
+        .collect())
+}
+
+/// Indexed entities clustered into `count` topics, or
+/// [`default_topic_count`] of them.
+pub fn topics(repo: &IxchelRepo, count: Option<usize>) -> Result<Vec<Topic>> {
+    let entities = entity_vectors(repo)?;
+    let count = count.unwrap_or_else(|| default_topic_count(entities.len()));
+    Ok(cluster_topics(&entities, count, &entity_tags(repo)?))
+}
+
+/// Up to `limit` relationships to add between `id` and similar entities
+/// (see [`ix_core::similarity::suggest_links`]).
+pub fn suggest_links(
+    repo: &IxchelRepo,
+    id: &str,
+    limit: usize,
+    threshold: f32,
+) -> Result<Vec<LinkSuggestion>> {
+    let index = open_index(repo)?;
+    let entities = index.entity_vectors().context("read entity vectors")?;
+    let target = entities
+        .iter()
+        .find(|entity| entity.id == id)
+        .with_context(|| format!("{id} is not indexed; run `ixchel sync` first"))?;
+    let existing = index
+        .neighbors(id, &EdgeFilter::new(Direction::Both))
+        .context("read relationships")?
+        .iter()
+        .map(|neighbor| neighbor.edge(id))
+        .collect::<Vec<_>>();
+    let mut suggestions =
+        ix_core::similarity::suggest_links(target, &entities, &existing, threshold);
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+fn entity_vectors(repo: &IxchelRepo) -> Result<Vec<EntityVector>> {
+    open_index(repo)?
+        .entity_vectors()
+        .context("read entity vectors")
+}
+
+/// Tags of every entity, by ID.
+fn entity_tags(repo: &IxchelRepo) -> Result<HashMap<String, Vec<String>>> {
+    let mut out: HashMap<String, Vec<String>> = HashMap::new();
+    for (tag, ids) in repo.collect_tags(None)? {
+        for id in ids {
+            out.entry(id).or_default().push(tag.clone());
         }
+    }
+    for tags in out.values_mut() {
+        tags.sort();
+    }
+    Ok(out)
+}
+
+/// Index candidates [`similar_titles`] checks for a new entity.
+const SIMILAR_TITLE_CANDIDATES: usize = 20;
+
+/// Existing entities of `kind` that a new entity titled `title` likely
+/// duplicates, most similar first.
+///
+/// With a synced index, the entities nearest to the new entity's vector (see
+/// [`IndexBackend::nearest_entities`]) are kept when their vector similarity
+/// reaches [`DUPLICATE_THRESHOLD`] or their title similarity (see
+/// [`title_similarity`]) reaches [`SIMILAR_TITLE_THRESHOLD`], scored by the
+/// higher of the two. Before the first sync, or when the index needs a
+/// rebuild, every title of `kind` is compared instead.
+pub fn similar_titles(repo: &IxchelRepo, kind: EntityKind, title: &str) -> Result<Vec<SearchHit>> {
+    // Checking the sidecar first avoids loading the embedder for nothing
+    if IndexMetadata::load(&repo.index_path())?.is_none() {
+        return similar_titles_by_text(repo, kind, title);
+    }
+    let index = open_index(repo)?;
+    if index.metadata()?.needs_rebuild() {
+        return similar_titles_by_text(repo, kind, title);
+    }
+
+    let text = Chunk::default().embedding_text(title, &[], kind);
+    let mut hits = index
+        .nearest_entities(&text, SIMILAR_TITLE_CANDIDATES)?
+        .into_iter()
+        .filter(|hit| hit.kind == Some(kind))
+        .filter_map(|hit| {
+            let title_score = title_similarity(title, &hit.title);
+            (hit.score >= DUPLICATE_THRESHOLD || title_score >= SIMILAR_TITLE_THRESHOLD).then(
+                || SearchHit {
+                    score: hit.score.max(title_score),
+                    ..hit
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    sort_similar_titles(&mut hits);
+    Ok(hits)
+}
+
+/// [`similar_titles`] without an index: titles of every entity of `kind`
+/// compared directly.
+fn similar_titles_by_text(
+    repo: &IxchelRepo,
+    kind: EntityKind,
+    title: &str,
+) -> Result<Vec<SearchHit>> {
+    let mut hits = repo
+        .list(Some(kind), ListSort::default())?
+        .into_iter()
+        .filter_map(|entity| {
+            let score = title_similarity(title, &entity.title);
+            (score >= SIMILAR_TITLE_THRESHOLD).then(|| SearchHit {
+                score,
+                id: entity.id,
+                kind: Some(entity.kind),
+                title: entity.title,
+                ..SearchHit::default()
+            })
+        })
+        .collect::<Vec<_>>();
+    sort_similar_titles(&mut hits);
+    Ok(hits)
+}
+
+fn sort_similar_titles(hits: &mut [SearchHit]) {
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+}
+
+/// Context for a question: the top search hits seed a graph expansion (see
+/// [`ix_core::context::build_context`]).
+///
+/// Seed scores are relative to the best hit, so vector, lexical and hybrid
+/// scores all expand on the same `[0, 1]` scale.
+pub fn retrieve_context(
+    repo: &IxchelRepo,
+    query: &str,
+    config: &ContextConfig,
+) -> Result<ContextPack> {
+    let hits = search(repo, &SearchRequest::new(query, config.seeds))?;
+    let top = hits
+        .iter()
+        .map(|hit| hit.score)
+        .fold(f32::EPSILON, f32::max);
+    let seeds = hits
+        .into_iter()
+        .map(|hit| {
+            let score = (hit.score / top).clamp(0.0, 1.0);
+            (hit.id, score)
+        })
+        .collect::<Vec<_>>();
+    build_context(repo, open_index(repo)?.as_ref(), &seeds, config)
+}
+
+/// Context around the entity `id` (see [`ix_core::context::build_context`]).
+pub fn entity_context(repo: &IxchelRepo, id: &str, config: &ContextConfig) -> Result<ContextPack> {
+    build_context(
+        repo,
+        open_index(repo)?.as_ref(),
+        &[(id.to_string(), 1.0)],
+        config,
+    )
+}
+
+/// Entities within `max_hops` of `id` through the relationships `filter`
+/// allows, read from the index.
+pub fn graph(
+    repo: &IxchelRepo,
+    id: &str,
+    filter: &EdgeFilter,
+    max_hops: usize,
+) -> Result<Subgraph> {
+    open_index(repo)?
+        .traverse(id, filter, max_hops)
+        .context("traverse graph")
+}
+
+/// The fewest relationships leading from `from` to `to`, if any.
+pub fn shortest_path(
+    repo: &IxchelRepo,
+    from: &str,
+    to: &str,
+    filter: &EdgeFilter,
+) -> Result<Option<Vec<Relationship>>> {
+    open_index(repo)?
+        .shortest_path(from, to, filter)
+        .context("find shortest path")
+}
+
+/// `ids` and the relationships between them.
+pub fn subgraph(repo: &IxchelRepo, ids: &[String], filter: &EdgeFilter) -> Result<Subgraph> {
+    open_index(repo)?
+        .subgraph(ids, filter)
+        .context("extract subgraph")
+}
+
+/// The configured index backend; every backend is registered here.
+fn open_index(repo: &IxchelRepo) -> Result<Box<dyn IndexBackend>> {
+    let index: Box<dyn IndexBackend> = match backend_name(repo).as_str() {
+        "surrealdb" => Box::new(
+            ix_storage_surrealdb::SurrealDbIndex::open(repo).context("open surrealdb index")?,
+        ),
+        "flat" => Box::new(ix_storage_flat::FlatIndex::open(repo).context("open flat index")?),
         "helixdb" => anyhow::bail!(
             "HelixDB backend is not available in this build. \
-             The crates.io version only supports SurrealDB because helix-db \
-             uses a git dependency. Build from source for HelixDB support: \
+             The crates.io version only supports the SurrealDB and flat \
+             backends because helix-db uses a git dependency. Build from source for HelixDB support: \
              https://github.com/kevinmichaelchen/ixchel"
         ),
         backend => anyhow::bail!("Unsupported storage backend: {backend}"),
+    };
+    Ok(index)
+}
+
+/// `repo` with the TOML file at `path` merged over its configuration, e.g.
+/// to evaluate a different embedding model.
+///
+/// Unless the file sets `[storage] path`, the variant gets its own index
+/// under `data/eval/<file stem>-<path hash>/`, keyed by the file's canonical
+/// path so same-named files in different directories don't share an index,
+/// and syncing it leaves the main index alone.
+pub fn config_variant(repo: &IxchelRepo, path: &Path) -> Result<IxchelRepo> {
+    let mut config = repo.config.with_overlay(path)?;
+    if config.storage.path == repo.config.storage.path {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("resolve {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("variant");
+        let hash = blake3::hash(canonical.as_os_str().as_encoded_bytes()).to_hex();
+        config.storage.path = format!("data/eval/{name}-{}/ixchel", &hash[..8]);
+    }
+    Ok(IxchelRepo {
+        paths: repo.paths.clone(),
+        config,
+    })
+}
+
+/// Run the golden queries through [`search`] and score the top `k` hits.
+///
+/// Expected IDs may be unique prefixes; a query's own `mode` overrides
+/// `mode`.
+pub fn evaluate(repo: &IxchelRepo, golden: &GoldenSet, mode: SearchMode) -> Result<EvalReport> {
+    let mut queries = Vec::with_capacity(golden.queries.len());
+    for golden_query in &golden.queries {
+        let expected = golden_query
+            .expected
+            .iter()
+            .map(|id| repo.resolve_id(id))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("golden query {:?}", golden_query.query))?;
+        let mode = match &golden_query.mode {
+            Some(mode) => mode.parse()?,
+            None => mode,
+        };
+        let request = SearchRequest::new(golden_query.query.clone(), golden.k).with_mode(mode);
+        let returned = search(repo, &request)?
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect::<Vec<_>>();
+        queries.push(QueryEval::score(
+            &golden_query.query,
+            &expected,
+            &returned,
+            golden.k,
+        ));
     }
+    Ok(EvalReport::new(golden.k, queries))
 }
 
 pub fn health_check(repo: &IxchelRepo) -> Result<()> {
-    match backend_name(repo).as_str() {
-        "surrealdb" => {
-            let index =
-                ix_storage_surrealdb::SurrealDbIndex::open(repo).context("open surrealdb index")?;
-            IndexBackend::health_check(&index).context("surrealdb health check")
+    open_index(repo)?
+        .health_check()
+        .context("index health check")
+}
+
+pub fn index_metadata(repo: &IxchelRepo) -> Result<IndexMetadataStatus> {
+    open_index(repo)?.metadata().context("read index metadata")
+}
+
+/// Shrink the embedding cache to `[embedding].cache_max_mb`.
+pub fn prune_embedding_cache(repo: &IxchelRepo) -> Result<PruneStats> {
+    let max_bytes = repo
+        .config
+        .embedding
+        .cache_max_mb
+        .saturating_mul(1024 * 1024);
+    ix_embeddings::EmbeddingCache::new(repo.paths.embedding_cache_dir())
+        .prune(max_bytes)
+        .context("prune embedding cache")
+}
+
+/// The repository's model directory, in offline mode when `[models].offline`
+/// is set.
+#[must_use]
+pub fn model_store(repo: &IxchelRepo) -> ModelStore {
+    ModelStore::from_config(repo.paths.models_dir(), &repo.config.models)
+}
+
+/// Hub repositories of the models the configuration uses: the embedding
+/// model, plus the reranker when reranking is enabled.
+pub fn required_models(repo: &IxchelRepo) -> Result<Vec<String>> {
+    let mut models = Vec::new();
+    models.extend(
+        ix_embeddings::embedding_model_repo(&repo.config.embedding)
+            .context("resolve embedding model")?,
+    );
+    if repo.config.rerank.enabled {
+        models.extend(
+            ix_embeddings::rerank_model_repo(&repo.config.rerank)
+                .context("resolve reranker model")?,
+        );
+    }
+    Ok(models)
+}
+
+/// Download the configured models (or embedding `model` instead) into the
+/// model directory and record their checksums. Pulling ignores offline mode.
+pub fn pull_models(repo: &IxchelRepo, model: Option<&str>) -> Result<Vec<InstalledModel>> {
+    let store = model_store(repo).with_offline(false);
+    let mut embedding = repo.config.embedding.clone();
+    if let Some(model) = model {
+        embedding.model = model.to_string();
+    }
+
+    let mut pulled = Vec::new();
+    if let Some(repo_id) =
+        ix_embeddings::embedding_model_repo(&embedding).context("resolve embedding model")?
+    {
+        ix_embeddings::Embedder::with_model_store(&embedding, &store)
+            .map_err(|e| anyhow::anyhow!("Failed to download {repo_id}: {e}"))?;
+        pulled.push(store.record_checksums(&repo_id)?);
+    }
+
+    let rerank = &repo.config.rerank;
+    if model.is_none()
+        && rerank.enabled
+        && let Some(repo_id) =
+            ix_embeddings::rerank_model_repo(rerank).context("resolve reranker model")?
+    {
+        ix_embeddings::Reranker::with_model_store(rerank, &store)
+            .map_err(|e| anyhow::anyhow!("Failed to download {repo_id}: {e}"))?;
+        pulled.push(store.record_checksums(&repo_id)?);
+    }
+    Ok(pulled)
+}
+
+/// Installed models the configuration no longer uses, removed unless
+/// `dry_run` is set.
+pub fn prune_models(repo: &IxchelRepo, dry_run: bool) -> Result<Vec<InstalledModel>> {
+    let store = model_store(repo);
+    let required = required_models(repo)?;
+    let unused = store
+        .list()?
+        .into_iter()
+        .filter(|model| !required.contains(&model.repo_id))
+        .collect::<Vec<_>>();
+    if !dry_run {
+        for model in &unused {
+            store.remove(&model.repo_id)?;
         }
-        "helixdb" => anyhow::bail!(
-            "HelixDB backend is not available in this build. \
-             The crates.io version only supports SurrealDB because helix-db \
-             uses a git dependency. Build from source for HelixDB support: \
-             https://github.com/kevinmichaelchen/ixchel"
-        ),
-        backend => anyhow::bail!("Unsupported storage backend: {backend}"),
     }
+    Ok(unused)
 }
@@ -0,0 +1,28 @@
+use ix_core::repo::IxchelRepo;
+use tempfile::TempDir;
+
+#[test]
+fn same_named_variants_in_different_directories_get_separate_indexes() {
+    let temp = TempDir::new().expect("tempdir");
+    let repo = IxchelRepo::init_at(temp.path(), false).expect("init ixchel repo");
+    let mut paths = Vec::new();
+    for dir in ["a", "b"] {
+        let dir = temp.path().join(dir);
+        std::fs::create_dir(&dir).expect("create variant dir");
+        let path = dir.join("ixchel.toml");
+        std::fs::write(&path, "[embedding]\nmodel = \"BAAI/bge-base-en-v1.5\"\n")
+            .expect("write variant");
+        paths.push(path);
+    }
+
+    let a = ix_app::config_variant(&repo, &paths[0]).expect("variant a");
+    let b = ix_app::config_variant(&repo, &paths[1]).expect("variant b");
+    assert_eq!(a.config.embedding.model, "BAAI/bge-base-en-v1.5");
+    assert_ne!(a.config.storage.path, repo.config.storage.path);
+    assert_ne!(a.config.storage.path, b.config.storage.path);
+    assert!(a.config.storage.path.starts_with("data/eval/ixchel-"));
+
+    let again = ix_app::config_variant(&repo, &temp.path().join("a/../a/ixchel.toml"))
+        .expect("variant a again");
+    assert_eq!(again.config.storage.path, a.config.storage.path);
+}
@@ -0,0 +1,67 @@
+use ix_core::entity::EntityKind;
+use ix_core::markdown::{parse_markdown, render_markdown};
+use ix_core::repo::{IxchelRepo, ListSort};
+use ix_core::similarity::{DUPLICATE_THRESHOLD, SIMILAR_TITLE_THRESHOLD, title_similarity};
+use tempfile::TempDir;
+
+#[test]
+fn near_duplicate_titles_of_the_same_kind_are_reported() {
+    let temp = TempDir::new().expect("tempdir");
+    let repo = IxchelRepo::init_at(temp.path(), false).expect("init ixchel repo");
+    let refresh = repo
+        .create_entity(EntityKind::Issue, "Auth token refresh fails", None)
+        .expect("create issue");
+    repo.create_entity(EntityKind::Issue, "Login page loops", None)
+        .expect("create unrelated issue");
+    repo.create_entity(EntityKind::Decision, "Auth token refresh fails", None)
+        .expect("create decision");
+
+    let hits = ix_app::similar_titles(&repo, EntityKind::Issue, "auth token-refresh failing")
+        .expect("similar titles");
+    assert_eq!(hits.len(), 1, "{hits:#?}");
+    assert_eq!(hits[0].id, refresh.id);
+    assert_eq!(hits[0].kind, Some(EntityKind::Issue));
+    assert!(hits[0].score >= SIMILAR_TITLE_THRESHOLD);
+
+    let hits = ix_app::similar_titles(&repo, EntityKind::Issue, "Use Postgres for storage")
+        .expect("unrelated title");
+    assert!(hits.is_empty(), "{hits:#?}");
+}
+
+#[test]
+fn similar_titles_use_stored_vectors_once_synced() {
+    let temp = TempDir::new().expect("tempdir");
+    let mut repo = IxchelRepo::init_at(temp.path(), false).expect("init ixchel repo");
+    repo.config.storage.backend = "flat".to_string();
+    repo.config.embedding.provider = "hash".to_string();
+    repo.config.embedding.dimension = Some(64);
+
+    let refresh = repo
+        .create_entity(EntityKind::Issue, "Auth token refresh fails", None)
+        .expect("create issue");
+    repo.create_entity(EntityKind::Decision, "Auth token refresh fails", None)
+        .expect("create decision");
+    for entity in repo.list(None, ListSort::default()).expect("list") {
+        let raw = std::fs::read_to_string(&entity.path).expect("read entity");
+        let mut doc = parse_markdown(&entity.path, &raw).expect("parse entity");
+        doc.body = String::new();
+        std::fs::write(&entity.path, render_markdown(&doc).expect("render")).expect("write");
+    }
+
+    // Same words in another order: only the vectors see them as identical
+    let title = "Refresh fails: auth token";
+    let title_score = title_similarity(title, "Auth token refresh fails");
+    assert!(title_score < 1.0);
+    let hits = ix_app::similar_titles(&repo, EntityKind::Issue, title)
+        .expect("similar titles before sync");
+    assert_eq!(hits.len(), 1, "{hits:#?}");
+    assert!((hits[0].score - title_score).abs() < f32::EPSILON);
+
+    ix_app::sync(&repo).expect("sync");
+    let hits = ix_app::similar_titles(&repo, EntityKind::Issue, title).expect("similar titles");
+    assert_eq!(hits.len(), 1, "{hits:#?}");
+    assert_eq!(hits[0].id, refresh.id);
+    assert_eq!(hits[0].kind, Some(EntityKind::Issue));
+    assert!(hits[0].score > title_score, "{hits:#?}");
+    assert!(hits[0].score >= DUPLICATE_THRESHOLD, "{hits:#?}");
+}
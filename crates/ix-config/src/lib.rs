@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+pub use ix_id::IdStrategy;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 
@@ -64,7 +66,15 @@ pub struct IxchelConfig {
     #[serde(default)]
     pub embedding: EmbeddingConfig,
     #[serde(default)]
+    pub rerank: RerankConfig,
+    #[serde(default)]
+    pub context: ContextConfig,
+    #[serde(default)]
+    pub models: ModelsConfig,
+    #[serde(default)]
     pub storage: StorageConfig,
+    #[serde(default)]
+    pub ids: IdConfig,
 }
 
 pub type SharedConfig = IxchelConfig;
@@ -79,7 +89,7 @@ pub struct GitHubConfig {
 /// Embedding model configuration.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EmbeddingConfig {
-    /// Provider implementation to use (e.g. "fastembed").
+    /// Provider implementation to use (e.g. "fastembed", "candle", "http", "hash").
     #[serde(default = "default_embedding_provider")]
     pub provider: String,
     /// The embedding model to use.
@@ -91,6 +101,30 @@ pub struct EmbeddingConfig {
     /// Optional dimension override for providers that don't advertise dims.
     #[serde(default)]
     pub dimension: Option<usize>,
+    /// Text prepended to search queries; overrides the model's default
+    /// instruction (use "" to disable it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_prefix: Option<String>,
+    /// Text prepended to indexed documents; overrides the model's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_prefix: Option<String>,
+    /// Base URL of an OpenAI-compatible server for the "http" provider
+    /// (e.g. `http://localhost:11434/v1`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Bearer token for the "http" provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Request timeout in seconds for remote providers.
+    #[serde(default = "default_embedding_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Retries after a failed request for remote providers.
+    #[serde(default = "default_embedding_max_retries")]
+    pub max_retries: u32,
+    /// Size limit of the on-disk embedding cache in MiB; `ixchel doctor`
+    /// evicts the oldest entries beyond it.
+    #[serde(default = "default_embedding_cache_max_mb")]
+    pub cache_max_mb: u64,
 }
 
 impl Default for EmbeddingConfig {
@@ -100,6 +134,13 @@ impl Default for EmbeddingConfig {
             model: default_embedding_model(),
             batch_size: default_batch_size(),
             dimension: None,
+            query_prefix: None,
+            document_prefix: None,
+            base_url: None,
+            api_key: None,
+            timeout_secs: default_embedding_timeout_secs(),
+            max_retries: default_embedding_max_retries(),
+            cache_max_mb: default_embedding_cache_max_mb(),
         }
     }
 }
@@ -116,10 +157,177 @@ const fn default_batch_size() -> usize {
     32
 }
 
+const fn default_embedding_timeout_secs() -> u64 {
+    30
+}
+
+const fn default_embedding_max_retries() -> u32 {
+    2
+}
+
+const fn default_embedding_cache_max_mb() -> u64 {
+    512
+}
+
+/// Optional cross-encoder reranking of search candidates.
+///
+/// ```toml
+/// [rerank]
+/// enabled = true
+/// model = "BAAI/bge-reranker-base"
+/// candidates = 20
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RerankConfig {
+    /// Rerank search results; off by default since rerankers are slower.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Reranker implementation (e.g. "fastembed", "hash").
+    #[serde(default = "default_rerank_provider")]
+    pub provider: String,
+    /// The cross-encoder model to use.
+    #[serde(default = "default_rerank_model")]
+    pub model: String,
+    /// Number of vector-search candidates passed to the reranker.
+    #[serde(default = "default_rerank_candidates")]
+    pub candidates: usize,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_rerank_provider(),
+            model: default_rerank_model(),
+            candidates: default_rerank_candidates(),
+        }
+    }
+}
+
+fn default_rerank_provider() -> String {
+    "fastembed".to_string()
+}
+
+fn default_rerank_model() -> String {
+    "BAAI/bge-reranker-base".to_string()
+}
+
+const fn default_rerank_candidates() -> usize {
+    20
+}
+
+/// Graph-expanded context retrieval (`ixchel context`).
+///
+/// ```toml
+/// [context]
+/// hops = 2
+/// budget_tokens = 4000
+///
+/// [context.weights]
+/// implements = 1.0
+/// references = 0.4
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContextConfig {
+    /// Search hits used as starting points for a query.
+    #[serde(default = "default_context_seeds")]
+    pub seeds: usize,
+    /// Relationship hops followed from each starting entity.
+    #[serde(default = "default_context_hops")]
+    pub hops: usize,
+    /// Score multiplier applied per hop, so nearer entities rank higher.
+    #[serde(default = "default_context_hop_decay")]
+    pub hop_decay: f32,
+    /// Approximate token budget of the returned context.
+    #[serde(default = "default_context_budget_tokens")]
+    pub budget_tokens: usize,
+    /// Per-relationship score multipliers, replacing the built-in table when
+    /// set. A weight of 0 stops expansion along that label.
+    #[serde(default = "default_context_weights")]
+    pub weights: BTreeMap<String, f32>,
+    /// Multiplier for relationships not listed in `weights`.
+    #[serde(default = "default_context_weight")]
+    pub default_weight: f32,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            seeds: default_context_seeds(),
+            hops: default_context_hops(),
+            hop_decay: default_context_hop_decay(),
+            budget_tokens: default_context_budget_tokens(),
+            weights: default_context_weights(),
+            default_weight: default_context_weight(),
+        }
+    }
+}
+
+impl ContextConfig {
+    /// Score multiplier for following a `rel` relationship.
+    #[must_use]
+    pub fn weight(&self, rel: &str) -> f32 {
+        self.weights
+            .get(rel)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+const fn default_context_seeds() -> usize {
+    5
+}
+
+const fn default_context_hops() -> usize {
+    2
+}
+
+const fn default_context_hop_decay() -> f32 {
+    0.7
+}
+
+const fn default_context_budget_tokens() -> usize {
+    4000
+}
+
+fn default_context_weights() -> BTreeMap<String, f32> {
+    [
+        ("implements", 1.0),
+        ("depends_on", 0.9),
+        ("blocks", 0.9),
+        ("supersedes", 0.8),
+        ("superseded_by", 0.8),
+        ("supports", 0.7),
+        ("duplicates", 0.7),
+        ("references", 0.5),
+    ]
+    .into_iter()
+    .map(|(rel, weight)| (rel.to_string(), weight))
+    .collect()
+}
+
+const fn default_context_weight() -> f32 {
+    0.6
+}
+
+/// Local model files under `.ixchel/models/`.
+///
+/// ```toml
+/// [models]
+/// offline = true
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ModelsConfig {
+    /// Never download models; fail fast when a configured model isn't
+    /// installed (see `ixchel models pull` / `ixchel models import`).
+    #[serde(default)]
+    pub offline: bool,
+}
+
 /// Storage configuration.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StorageConfig {
-    /// Storage backend to use (e.g. "helixdb", "surrealdb").
+    /// Storage backend to use (e.g. "surrealdb", "flat", "helixdb").
     #[serde(default = "default_storage_backend")]
     pub backend: String,
 
@@ -152,6 +360,72 @@ fn default_storage_path() -> String {
     "data/ixchel".to_string()
 }
 
+/// Entity ID generation settings.
+///
+/// ```toml
+/// [ids]
+/// strategy = "random"
+/// bytes = 3
+///
+/// [ids.kinds.issue]
+/// strategy = "time_sorted"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdConfig {
+    /// Default strategy: "random", "`time_sorted`", or "`content_keyed`".
+    #[serde(default)]
+    pub strategy: IdStrategy,
+    /// Default hash length in bytes (each byte is two hex characters).
+    #[serde(default = "default_id_bytes")]
+    pub bytes: usize,
+    /// Per-kind overrides keyed by kind name (e.g. "decision").
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub kinds: BTreeMap<String, IdKindConfig>,
+}
+
+/// Per-kind override of [`IdConfig`].
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct IdKindConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<IdStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+}
+
+impl Default for IdConfig {
+    fn default() -> Self {
+        Self {
+            strategy: IdStrategy::default(),
+            bytes: default_id_bytes(),
+            kinds: BTreeMap::new(),
+        }
+    }
+}
+
+impl IdConfig {
+    /// Strategy for `kind`, falling back to the default.
+    #[must_use]
+    pub fn strategy_for(&self, kind: &str) -> IdStrategy {
+        self.kinds
+            .get(kind)
+            .and_then(|k| k.strategy)
+            .unwrap_or(self.strategy)
+    }
+
+    /// Hash length in bytes for `kind`, falling back to the default.
+    #[must_use]
+    pub fn bytes_for(&self, kind: &str) -> usize {
+        self.kinds
+            .get(kind)
+            .and_then(|k| k.bytes)
+            .unwrap_or(self.bytes)
+    }
+}
+
+const fn default_id_bytes() -> usize {
+    3
+}
+
 impl IxchelConfig {
     pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
         let raw = toml::to_string_pretty(self).map_err(|source| ConfigError::SerializeError {
@@ -164,6 +438,27 @@ impl IxchelConfig {
         })?;
         Ok(())
     }
+
+    /// This configuration with the TOML file at `path` merged over it, the
+    /// same way project config is merged over global config.
+    pub fn with_overlay(&self, path: &Path) -> Result<Self, ConfigError> {
+        let overlay = load_toml_file(path)?.ok_or_else(|| ConfigError::ReadError {
+            path: path.to_path_buf(),
+            source: std::io::ErrorKind::NotFound.into(),
+        })?;
+        let mut merged =
+            toml::Table::try_from(self).map_err(|source| ConfigError::SerializeError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        merge_tables(&mut merged, overlay);
+        toml::Value::Table(merged)
+            .try_into()
+            .map_err(|source| ConfigError::ParseError {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
 }
 
 /// Load the shared configuration from global and project config files.
@@ -451,4 +746,98 @@ mod tests {
 
         assert_eq!(config, TestConfig::default());
     }
+
+    #[test]
+    fn test_id_config_kind_overrides() {
+        let config: IxchelConfig = toml::from_str(
+            r#"
+            [ids]
+            bytes = 4
+
+            [ids.kinds.issue]
+            strategy = "time_sorted"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.ids.strategy_for("issue"), IdStrategy::TimeSorted);
+        assert_eq!(config.ids.strategy_for("decision"), IdStrategy::Random);
+        assert_eq!(config.ids.bytes_for("issue"), 4);
+    }
+
+    #[test]
+    fn test_id_config_rejects_unknown_strategy() {
+        let err = toml::from_str::<IxchelConfig>("[ids]\nstrategy = \"uuid\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `uuid`"), "{err}");
+
+        let config: IxchelConfig =
+            toml::from_str("[ids.kinds.issue]\nstrategy = \"sortable\"\n").unwrap();
+        assert_eq!(config.ids.strategy_for("issue"), IdStrategy::TimeSorted);
+    }
+
+    #[test]
+    fn test_embedding_http_settings() {
+        let config: IxchelConfig = toml::from_str(
+            r#"
+            [embedding]
+            provider = "http"
+            model = "nomic-embed-text"
+            base_url = "http://localhost:11434/v1"
+            timeout_secs = 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.embedding.base_url.as_deref(),
+            Some("http://localhost:11434/v1")
+        );
+        assert_eq!(config.embedding.timeout_secs, 5);
+        assert_eq!(config.embedding.max_retries, 2);
+        assert_eq!(config.embedding.cache_max_mb, 512);
+        assert!(config.embedding.api_key.is_none());
+    }
+
+    #[test]
+    fn test_context_config_weights() {
+        let defaults = ContextConfig::default();
+        assert!((defaults.weight("implements") - 1.0).abs() < f32::EPSILON);
+        assert!((defaults.weight("unknown_rel") - defaults.default_weight).abs() < f32::EPSILON);
+
+        let config: IxchelConfig = toml::from_str(
+            r"
+            [context]
+            hops = 3
+            default_weight = 0.2
+
+            [context.weights]
+            cites = 0.9
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(config.context.hops, 3);
+        assert_eq!(config.context.budget_tokens, 4000);
+        assert!((config.context.weight("cites") - 0.9).abs() < f32::EPSILON);
+        assert!((config.context.weight("implements") - 0.2).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_config_overlay_merges_over_loaded_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("variant.toml");
+        std::fs::write(&path, "[embedding]\nmodel = \"BAAI/bge-base-en-v1.5\"\n").unwrap();
+
+        let mut base = IxchelConfig::default();
+        base.embedding.batch_size = 8;
+        let config = base.with_overlay(&path).unwrap();
+
+        assert_eq!(config.embedding.model, "BAAI/bge-base-en-v1.5");
+        assert_eq!(config.embedding.batch_size, 8);
+        assert_eq!(config.storage.path, base.storage.path);
+        assert!(matches!(
+            base.with_overlay(&dir.path().join("missing.toml")),
+            Err(ConfigError::ReadError { .. })
+        ));
+    }
 }
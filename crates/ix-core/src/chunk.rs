@@ -0,0 +1,188 @@
+//! Heading-aware splitting of entity bodies into separately embedded passages.
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::EntityKind;
+use crate::markdown::parse_sections;
+
+/// Soft upper bound on the size of a chunk's text, in characters.
+///
+/// Paragraphs are never split unless a single paragraph exceeds the limit.
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 1500;
+
+/// A passage of an entity body with its own embedding.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// 0-based position of the chunk within its entity.
+    pub index: usize,
+    /// Headings enclosing the passage, outermost first.
+    pub heading_path: Vec<String>,
+    /// 1-based, inclusive line range of the passage within the file.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+}
+
+impl Chunk {
+    /// Text fed to the embedding model: entity title and heading path give the
+    /// passage context it would otherwise lose once split from the document.
+    #[must_use]
+    pub fn embedding_text(&self, title: &str, tags: &[String], kind: EntityKind) -> String {
+        let mut out = String::from(title);
+        out.push('\n');
+        if !self.heading_path.is_empty() {
+            out.push_str(&self.heading_path.join(" > "));
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(&self.text);
+        out.push_str("\n\nTags: ");
+        out.push_str(&tags.join(", "));
+        out.push_str("\nType: ");
+        out.push_str(kind.as_str());
+        out.push('\n');
+        out
+    }
+
+    /// Text matched by full-text search: title, headings and passage, so an
+    /// identifier in any of them finds the chunk.
+    #[must_use]
+    pub fn search_text(&self, title: &str) -> String {
+        let mut out = String::from(title);
+        for heading in &self.heading_path {
+            out.push('\n');
+            out.push_str(heading);
+        }
+        out.push('\n');
+        out.push_str(&self.text);
+        out
+    }
+}
+
+/// Split a Markdown body into chunks that never cross a heading.
+///
+/// `line_offset` is the number of file lines before the body (see
+/// [`crate::markdown::body_line_offset`]) so chunk line ranges point into the
+/// file. Bodies without any text yield a single empty chunk, so every entity
+/// stays searchable by title.
+#[must_use]
+pub fn chunk_body(body: &str, line_offset: usize, max_chars: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+
+    for section in parse_sections(body) {
+        if section.level > 0 {
+            while headings
+                .last()
+                .is_some_and(|(level, _)| *level >= section.level)
+            {
+                headings.pop();
+            }
+            headings.push((section.level, section.heading.clone()));
+        }
+
+        // Section text starts on the line after the heading.
+        let first_line = section.start_line + usize::from(section.level > 0);
+        let lines = section.text.lines().collect::<Vec<_>>();
+        let heading_path = headings
+            .iter()
+            .map(|(_, heading)| heading.clone())
+            .collect::<Vec<_>>();
+
+        for (start, end) in pack_lines(&lines, max_chars) {
+            chunks.push(Chunk {
+                index: chunks.len(),
+                heading_path: heading_path.clone(),
+                start_line: line_offset + first_line + start,
+                end_line: line_offset + first_line + end,
+                text: lines[start..=end].join("\n"),
+            });
+        }
+    }
+
+    if chunks.is_empty() {
+        chunks.push(Chunk {
+            index: 0,
+            heading_path: Vec::new(),
+            start_line: line_offset + 1,
+            end_line: line_offset + 1,
+            text: String::new(),
+        });
+    }
+
+    chunks
+}
+
+/// Group `lines` into inclusive index ranges of at most `max_chars`, breaking
+/// between paragraphs where possible. Blank lines at range edges are dropped.
+fn pack_lines(lines: &[&str], max_chars: usize) -> Vec<(usize, usize)> {
+    let mut paragraphs = Vec::new();
+    let mut start = None;
+    for (idx, line) in lines.iter().enumerate() {
+        match (line.trim().is_empty(), start) {
+            (false, None) => start = Some(idx),
+            (true, Some(first)) => {
+                paragraphs.push((first, idx - 1));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        paragraphs.push((first, lines.len() - 1));
+    }
+
+    let chars = |from: usize, to: usize| -> usize {
+        lines[from..=to].iter().map(|line| line.len() + 1).sum()
+    };
+
+    let mut out = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for (first, last) in paragraphs {
+        if let Some((cur_first, _)) = current
+            && chars(cur_first, last) > max_chars
+        {
+            out.extend(current.take());
+        }
+
+        if chars(first, last) <= max_chars {
+            current = Some(current.map_or((first, last), |(cur_first, _)| (cur_first, last)));
+            continue;
+        }
+
+        // A single oversized paragraph: split it line by line.
+        let mut piece = first;
+        for idx in first..=last {
+            if idx > piece && chars(piece, idx) > max_chars {
+                out.push((piece, idx - 1));
+                piece = idx;
+            }
+        }
+        current = Some((piece, last));
+    }
+    out.extend(current);
+    out
+}
+
+/// Document-level embedding: the normalized mean of an entity's chunk embeddings.
+#[must_use]
+pub fn pool_embeddings(embeddings: &[Vec<f32>]) -> Vec<f32> {
+    let Some(first) = embeddings.first() else {
+        return Vec::new();
+    };
+
+    let mut pooled = vec![0.0_f32; first.len()];
+    for embedding in embeddings {
+        for (sum, value) in pooled.iter_mut().zip(embedding) {
+            *sum += value;
+        }
+    }
+
+    let norm = pooled.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut pooled {
+            *value /= norm;
+        }
+    }
+    pooled
+}
@@ -0,0 +1,226 @@
+//! Graph-expanded context for agents.
+//!
+//! Starting from seed entities (search hits or a single entity), typed
+//! relationships are followed in both directions and what is reached is
+//! ranked by seed score and graph distance, within a token budget.
+
+use std::collections::HashMap;
+
+use anyhow::{Context as _, Result};
+pub use ix_config::ContextConfig;
+
+use crate::entity::{EntityKind, kind_from_id};
+use crate::graph::{Direction, EdgeFilter};
+use crate::index::IndexBackend;
+use crate::markdown::{get_string, parse_markdown};
+use crate::repo::IxchelRepo;
+
+/// Rough characters per token, close enough for budgeting English prose and
+/// code across common tokenizers.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Approximate token count of `text`.
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// The relationship an expanded entity was reached through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextStep {
+    /// The entity it was reached from.
+    pub from: String,
+    pub rel: String,
+    /// Whether the relationship is declared on `from` (rather than pointing
+    /// back at it).
+    pub outgoing: bool,
+}
+
+/// An entity reached during expansion.
+#[derive(Debug, Clone)]
+pub struct ContextCandidate {
+    pub id: String,
+    pub score: f32,
+    /// Relationships followed from the nearest seed; 0 for seeds.
+    pub hops: usize,
+    pub via: Option<ContextStep>,
+}
+
+/// Expand `seeds` (entity id and a score in `[0, 1]`) up to `config.hops`
+/// relationships away through `backend`'s indexed relationships, best first.
+///
+/// Each hop multiplies the score by the relationship's weight and by
+/// `config.hop_decay`; an entity reachable several ways keeps its best score.
+/// Seeds keep their own score and stay at hop 0.
+pub fn expand<B: IndexBackend + ?Sized>(
+    backend: &B,
+    seeds: &[(String, f32)],
+    config: &ContextConfig,
+) -> Result<Vec<ContextCandidate>> {
+    let filter = EdgeFilter::new(Direction::Both);
+    let mut best: HashMap<String, ContextCandidate> = HashMap::new();
+    let mut frontier = Vec::new();
+    for (id, score) in seeds {
+        if best.get(id).is_some_and(|seen| seen.score >= *score) {
+            continue;
+        }
+        best.insert(
+            id.clone(),
+            ContextCandidate {
+                id: id.clone(),
+                score: *score,
+                hops: 0,
+                via: None,
+            },
+        );
+        frontier.push(id.clone());
+    }
+
+    for hop in 1..=config.hops {
+        let mut next = Vec::new();
+        for id in frontier {
+            let score = best[&id].score;
+            for neighbor in backend.neighbors(&id, &filter)? {
+                let reached = score * config.weight(&neighbor.rel) * config.hop_decay;
+                // Seeds stay seeds, however well another seed reaches them
+                if reached <= 0.0
+                    || best
+                        .get(&neighbor.entity.id)
+                        .is_some_and(|seen| seen.hops == 0 || seen.score >= reached)
+                {
+                    continue;
+                }
+                next.push(neighbor.entity.id.clone());
+                best.insert(
+                    neighbor.entity.id.clone(),
+                    ContextCandidate {
+                        id: neighbor.entity.id,
+                        score: reached,
+                        hops: hop,
+                        via: Some(ContextStep {
+                            from: id.clone(),
+                            rel: neighbor.rel,
+                            outgoing: neighbor.outgoing,
+                        }),
+                    },
+                );
+            }
+        }
+        next.sort();
+        next.dedup();
+        frontier = next;
+    }
+
+    let mut candidates = best.into_values().collect::<Vec<_>>();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.hops.cmp(&b.hops))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(candidates)
+}
+
+/// One entity of a [`ContextPack`].
+#[derive(Debug, Clone)]
+pub struct ContextItem {
+    pub id: String,
+    pub kind: Option<EntityKind>,
+    pub title: String,
+    pub body: String,
+    pub score: f32,
+    pub hops: usize,
+    pub via: Option<ContextStep>,
+    /// Estimated tokens of the title and body.
+    pub tokens: usize,
+    /// Whether the body was cut short to fit the budget (seeds only).
+    pub truncated: bool,
+}
+
+/// Ranked, deduplicated context that fits a token budget.
+#[derive(Debug, Clone, Default)]
+pub struct ContextPack {
+    pub items: Vec<ContextItem>,
+    pub tokens: usize,
+    pub budget_tokens: usize,
+    /// Ranked entities left out because they didn't fit the budget.
+    pub omitted: Vec<String>,
+}
+
+/// Expand `seeds` through `backend`'s indexed relationships and load the
+/// reached entities from `repo`, in rank order, until `config.budget_tokens`
+/// is spent.
+///
+/// Seeds are always included: one that doesn't fit what is left of the
+/// budget has its body truncated, so only seed titles can take the pack over
+/// budget. Expanded entities that don't fit are skipped rather than ending
+/// the pack, so a long document doesn't crowd out shorter, lower-ranked
+/// ones. Links to missing entities are ignored.
+pub fn build_context<B: IndexBackend + ?Sized>(
+    repo: &IxchelRepo,
+    backend: &B,
+    seeds: &[(String, f32)],
+    config: &ContextConfig,
+) -> Result<ContextPack> {
+    let mut items = Vec::new();
+    for candidate in expand(backend, seeds, config)? {
+        let Some(path) = repo
+            .paths
+            .entity_path(&candidate.id)
+            .filter(|path| path.exists())
+        else {
+            continue;
+        };
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let doc = parse_markdown(&path, &raw)?;
+        let title = get_string(&doc.frontmatter, "title").unwrap_or_default();
+        let tokens = estimate_tokens(&title) + estimate_tokens(&doc.body);
+        items.push(ContextItem {
+            kind: kind_from_id(&candidate.id),
+            id: candidate.id,
+            title,
+            body: doc.body,
+            score: candidate.score,
+            hops: candidate.hops,
+            via: candidate.via,
+            tokens,
+            truncated: false,
+        });
+    }
+
+    // Seeds claim the budget first, whatever their rank.
+    let mut tokens = 0;
+    for item in items.iter_mut().filter(|item| item.hops == 0) {
+        let left = config.budget_tokens.saturating_sub(tokens);
+        if item.tokens > left {
+            let body_tokens = left.saturating_sub(estimate_tokens(&item.title));
+            item.body = item
+                .body
+                .chars()
+                .take(body_tokens * CHARS_PER_TOKEN)
+                .collect();
+            item.tokens = estimate_tokens(&item.title) + estimate_tokens(&item.body);
+            item.truncated = true;
+        }
+        tokens += item.tokens;
+    }
+
+    let mut pack = ContextPack {
+        budget_tokens: config.budget_tokens,
+        tokens,
+        ..ContextPack::default()
+    };
+    for item in items {
+        if item.hops > 0 {
+            if pack.tokens + item.tokens > config.budget_tokens {
+                pack.omitted.push(item.id);
+                continue;
+            }
+            pack.tokens += item.tokens;
+        }
+        pack.items.push(item);
+    }
+
+    Ok(pack)
+}
@@ -0,0 +1,193 @@
+//! Search quality evaluation against a checked-in golden set of queries and
+//! the entities each should find.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Hits considered per query when the golden file doesn't set `k`.
+pub const DEFAULT_EVAL_K: usize = 10;
+
+/// Golden queries, usually `.ixchel/eval.yaml`:
+///
+/// ```yaml
+/// k: 10
+/// thresholds:
+///   recall: 0.8
+///   mrr: 0.6
+/// queries:
+///   - query: connection pooling
+///     expected: [iss-a1b2c3, dec-d4e5f6]
+///   - query: E0425
+///     mode: lexical
+///     expected: [iss-0f9e8d]
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GoldenSet {
+    #[serde(default = "default_eval_k")]
+    pub k: usize,
+    #[serde(default)]
+    pub thresholds: EvalThresholds,
+    pub queries: Vec<GoldenQuery>,
+}
+
+const fn default_eval_k() -> usize {
+    DEFAULT_EVAL_K
+}
+
+/// One golden query and the entities it should find.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GoldenQuery {
+    pub query: String,
+    /// Entity IDs (or unique prefixes) a good search returns for `query`.
+    pub expected: Vec<String>,
+    /// Search mode for this query, overriding the mode being evaluated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+impl GoldenSet {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let golden: Self = serde_yaml::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if golden.k == 0 {
+            anyhow::bail!("{}: k must be at least 1", path.display());
+        }
+        if let Some(query) = golden.queries.iter().find(|q| q.expected.is_empty()) {
+            anyhow::bail!(
+                "{}: query {:?} has no expected entities",
+                path.display(),
+                query.query
+            );
+        }
+        Ok(golden)
+    }
+}
+
+/// Minimum mean metrics; unset metrics aren't checked.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct EvalThresholds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recall: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrr: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ndcg: Option<f32>,
+}
+
+impl EvalThresholds {
+    /// A message for each metric of `report` below its threshold.
+    #[must_use]
+    pub fn failures(&self, report: &EvalReport) -> Vec<String> {
+        let k = report.k;
+        [
+            (format!("recall@{k}"), report.recall, self.recall),
+            ("MRR".to_string(), report.mrr, self.mrr),
+            (format!("nDCG@{k}"), report.ndcg, self.ndcg),
+        ]
+        .into_iter()
+        .filter_map(|(name, value, min)| {
+            let min = min?;
+            (value < min).then(|| format!("{name} {value:.3} is below {min:.3}"))
+        })
+        .collect()
+    }
+}
+
+/// Metrics of one golden query.
+#[derive(Debug, Clone)]
+pub struct QueryEval {
+    pub query: String,
+    pub expected: Vec<String>,
+    /// Top `k` entity IDs returned, best first.
+    pub returned: Vec<String>,
+    /// Share of `expected` found in the top `k`.
+    pub recall: f32,
+    /// 1 / rank of the first expected entity, 0 when none is in the top `k`.
+    pub reciprocal_rank: f32,
+    /// Discounted cumulative gain over the ideal ordering, binary relevance.
+    pub ndcg: f32,
+}
+
+impl QueryEval {
+    /// Score `returned` (best first) against `expected`, looking at the top
+    /// `k` hits.
+    #[must_use]
+    pub fn score(query: &str, expected: &[String], returned: &[String], k: usize) -> Self {
+        let returned = returned.iter().take(k).cloned().collect::<Vec<_>>();
+        let ranks = returned
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| expected.contains(id))
+            .map(|(index, _)| index + 1)
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::cast_precision_loss)]
+        let recall = if expected.is_empty() {
+            0.0
+        } else {
+            ranks.len() as f32 / expected.len() as f32
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let reciprocal_rank = ranks.first().map_or(0.0, |&rank| 1.0 / rank as f32);
+        let dcg = ranks.iter().map(|&rank| discount(rank)).sum::<f32>();
+        let ideal = (1..=expected.len().min(k)).map(discount).sum::<f32>();
+        let ndcg = if ideal > 0.0 { dcg / ideal } else { 0.0 };
+
+        Self {
+            query: query.to_string(),
+            expected: expected.to_vec(),
+            returned,
+            recall,
+            reciprocal_rank,
+            ndcg,
+        }
+    }
+
+    /// Expected entities missing from the top `k`.
+    #[must_use]
+    pub fn missed(&self) -> Vec<&str> {
+        self.expected
+            .iter()
+            .filter(|id| !self.returned.contains(id))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn discount(rank: usize) -> f32 {
+    1.0 / ((rank + 1) as f32).log2()
+}
+
+/// Mean metrics over a golden set.
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+    pub k: usize,
+    pub queries: Vec<QueryEval>,
+    /// Mean recall@k.
+    pub recall: f32,
+    /// Mean reciprocal rank.
+    pub mrr: f32,
+    /// Mean nDCG@k.
+    pub ndcg: f32,
+}
+
+impl EvalReport {
+    #[must_use]
+    pub fn new(k: usize, queries: Vec<QueryEval>) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let count = queries.len().max(1) as f32;
+        let mean = |metric: fn(&QueryEval) -> f32| queries.iter().map(metric).sum::<f32>() / count;
+        Self {
+            k,
+            recall: mean(|q| q.recall),
+            mrr: mean(|q| q.reciprocal_rank),
+            ndcg: mean(|q| q.ndcg),
+            queries,
+        }
+    }
+}
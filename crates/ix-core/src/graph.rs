@@ -0,0 +1,274 @@
+//! Relationship traversal over the graph an index backend stores.
+//!
+//! Backends only answer "who is linked to this entity"; multi-hop
+//! traversal, shortest paths and subgraphs are built on that here.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::index::IndexBackend;
+use crate::repo::Relationship;
+use crate::similarity::EntityRef;
+
+/// Hops [`shortest_path`] searches before giving up.
+pub const MAX_PATH_HOPS: usize = 10;
+
+/// Which way relationships are followed from an entity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Relationships declared on the entity.
+    Outgoing,
+    /// Relationships other entities declare to it.
+    Incoming,
+    #[default]
+    Both,
+}
+
+impl Direction {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Outgoing => "outgoing",
+            Self::Incoming => "incoming",
+            Self::Both => "both",
+        }
+    }
+
+    #[must_use]
+    pub const fn outgoing(self) -> bool {
+        matches!(self, Self::Outgoing | Self::Both)
+    }
+
+    #[must_use]
+    pub const fn incoming(self) -> bool {
+        matches!(self, Self::Incoming | Self::Both)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseDirectionError {
+    #[error("Unknown direction: {0} (expected outgoing, incoming or both)")]
+    UnknownDirection(String),
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "outgoing" | "out" => Ok(Self::Outgoing),
+            "incoming" | "in" => Ok(Self::Incoming),
+            "both" => Ok(Self::Both),
+            _ => Err(ParseDirectionError::UnknownDirection(s.to_string())),
+        }
+    }
+}
+
+/// Which relationships a traversal follows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeFilter {
+    pub direction: Direction,
+    /// Relationship names to follow, case-insensitively; empty follows all.
+    pub labels: Vec<String>,
+}
+
+impl EdgeFilter {
+    #[must_use]
+    pub const fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            labels: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    #[must_use]
+    pub fn allows(&self, label: &str) -> bool {
+        self.labels.is_empty()
+            || self
+                .labels
+                .iter()
+                .any(|allowed| allowed.trim().eq_ignore_ascii_case(label))
+    }
+}
+
+/// An entity linked to another, and the relationship between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbor {
+    pub entity: EntityRef,
+    /// Relationship name, lowercase as written in frontmatter.
+    pub rel: String,
+    /// Whether the relationship is declared on the entity whose neighbor
+    /// this is (rather than pointing back at it).
+    pub outgoing: bool,
+}
+
+impl Neighbor {
+    /// The relationship as declared, seen from the entity `of`.
+    #[must_use]
+    pub fn edge(&self, of: &str) -> Relationship {
+        let (from, to) = if self.outgoing {
+            (of, self.entity.id.as_str())
+        } else {
+            (self.entity.id.as_str(), of)
+        };
+        Relationship {
+            from: from.to_string(),
+            rel: self.rel.clone(),
+            to: to.to_string(),
+        }
+    }
+}
+
+/// An entity of a [`Subgraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub entity: EntityRef,
+    /// Hops from the traversal start; 0 for the start itself.
+    pub depth: usize,
+}
+
+/// Entities and the relationships between them.
+#[derive(Debug, Clone, Default)]
+pub struct Subgraph {
+    /// Nearest first, then by ID.
+    pub nodes: Vec<GraphNode>,
+    /// Sorted and without duplicates.
+    pub edges: Vec<Relationship>,
+}
+
+impl Subgraph {
+    fn finish(mut self) -> Self {
+        self.nodes.sort_by(|a, b| {
+            a.depth
+                .cmp(&b.depth)
+                .then_with(|| a.entity.id.cmp(&b.entity.id))
+        });
+        self.edges
+            .sort_by(|a, b| (&a.from, &a.rel, &a.to).cmp(&(&b.from, &b.rel, &b.to)));
+        self.edges.dedup();
+        self
+    }
+}
+
+/// Entities within `max_hops` of `id` through relationships `filter`
+/// allows, and the relationships followed to reach them.
+pub fn traverse<B: IndexBackend + ?Sized>(
+    backend: &B,
+    id: &str,
+    filter: &EdgeFilter,
+    max_hops: usize,
+) -> Result<Subgraph> {
+    let root = indexed_entity(backend, id)?;
+    let mut seen = HashSet::from([root.id.clone()]);
+    let mut graph = Subgraph {
+        nodes: vec![GraphNode {
+            entity: root,
+            depth: 0,
+        }],
+        edges: Vec::new(),
+    };
+
+    let mut frontier = vec![id.to_string()];
+    for depth in 1..=max_hops {
+        let mut next = Vec::new();
+        for current in &frontier {
+            for neighbor in backend.neighbors(current, filter)? {
+                graph.edges.push(neighbor.edge(current));
+                if seen.insert(neighbor.entity.id.clone()) {
+                    next.push(neighbor.entity.id.clone());
+                    graph.nodes.push(GraphNode {
+                        entity: neighbor.entity,
+                        depth,
+                    });
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    Ok(graph.finish())
+}
+
+/// The fewest relationships `filter` allows leading from `from` to `to`, in
+/// order, or `None` when `to` isn't within [`MAX_PATH_HOPS`].
+pub fn shortest_path<B: IndexBackend + ?Sized>(
+    backend: &B,
+    from: &str,
+    to: &str,
+    filter: &EdgeFilter,
+) -> Result<Option<Vec<Relationship>>> {
+    indexed_entity(backend, from)?;
+    indexed_entity(backend, to)?;
+    if from == to {
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut reached_by: HashMap<String, (String, Relationship)> = HashMap::new();
+    let mut queue = VecDeque::from([(from.to_string(), 0)]);
+    while let Some((current, hops)) = queue.pop_front() {
+        if hops == MAX_PATH_HOPS {
+            continue;
+        }
+        for neighbor in backend.neighbors(&current, filter)? {
+            let id = neighbor.entity.id.clone();
+            if id == from || reached_by.contains_key(&id) {
+                continue;
+            }
+            reached_by.insert(id.clone(), (current.clone(), neighbor.edge(&current)));
+            if id == to {
+                let mut path = Vec::new();
+                let mut step = id;
+                while let Some((previous, edge)) = reached_by.remove(&step) {
+                    path.push(edge);
+                    step = previous;
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            queue.push_back((id, hops + 1));
+        }
+    }
+    Ok(None)
+}
+
+/// The indexed entities among `ids` and the relationships `filter` allows
+/// between them.
+pub fn subgraph<B: IndexBackend + ?Sized>(
+    backend: &B,
+    ids: &[String],
+    filter: &EdgeFilter,
+) -> Result<Subgraph> {
+    let mut graph = Subgraph::default();
+    let wanted = ids.iter().map(String::as_str).collect::<HashSet<_>>();
+    for id in &wanted {
+        let Some(entity) = backend.entity(id)? else {
+            continue;
+        };
+        graph.nodes.push(GraphNode { entity, depth: 0 });
+        graph.edges.extend(
+            backend
+                .neighbors(id, filter)?
+                .into_iter()
+                .filter(|neighbor| wanted.contains(neighbor.entity.id.as_str()))
+                .map(|neighbor| neighbor.edge(id)),
+        );
+    }
+    Ok(graph.finish())
+}
+
+fn indexed_entity<B: IndexBackend + ?Sized>(backend: &B, id: &str) -> Result<EntityRef> {
+    backend
+        .entity(id)?
+        .ok_or_else(|| anyhow::anyhow!("{id} is not indexed; run `ixchel sync` first"))
+}
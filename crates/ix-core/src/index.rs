@@ -1,7 +1,20 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use thiserror::Error;
+
+use crate::chunk::Chunk;
 use crate::entity::EntityKind;
-use crate::repo::IxchelRepo;
+use crate::graph::{self, EdgeFilter, Neighbor, Subgraph};
+use crate::markdown::{get_string, get_string_list};
+use crate::repo::{IxchelRepo, Relationship};
+use crate::similarity::{EntityRef, EntityVector};
+use crate::snippet::{SNIPPET_CHARS, Snippet, snippet};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct SyncStats {
@@ -10,18 +23,642 @@ pub struct SyncStats {
     pub modified: u32,
     pub deleted: u32,
     pub unchanged: u32,
+    /// Embeddings served from the persistent embedding cache.
+    pub cache_hits: u64,
+    /// Embeddings that had to be computed by the provider.
+    pub cache_misses: u64,
 }
 
-#[derive(Debug, Clone)]
+/// One entity matched by a search.
+///
+/// Scores follow one contract across backends, so thresholds carry over when
+/// `storage.backend` changes:
+///
+/// - Vector matches (vector search, [`IndexBackend::related`] and
+///   [`IndexBackend::nearest_entities`]) score the cosine similarity of the
+///   query and matched vectors, clamped to `[0, 1]`, and carry the raw cosine
+///   `distance` (`1 - similarity`, in `[0, 2]`); see
+///   [`similarity_from_distance`].
+/// - Lexical matches score BM25 and hybrid matches reciprocal rank fusion.
+///   Those scores only order hits within one query. Hybrid hits keep the
+///   distance of their vector match.
+#[derive(Debug, Clone, Default)]
 pub struct SearchHit {
     pub score: f32,
+    /// Cosine distance between the query and the matched vector, for hits
+    /// with a vector match.
+    pub distance: Option<f32>,
     pub id: String,
     pub kind: Option<EntityKind>,
     pub title: String,
+    pub status: Option<String>,
+    pub tags: Vec<String>,
+    /// Entity file relative to the repository root.
+    pub path: Option<String>,
+    /// Best-matching passage of the entity, when the backend indexes chunks.
+    pub passage: Option<Chunk>,
+    /// Excerpt of the passage around the query terms.
+    pub snippet: Option<Snippet>,
+    /// Cross-encoder relevance, when search results were reranked.
+    pub rerank_score: Option<f32>,
+}
+
+/// Score of a vector match at cosine `distance` (`1 - cosine similarity`):
+/// the similarity, with anti-correlated vectors scoring 0.
+#[must_use]
+pub fn similarity_from_distance(distance: f32) -> f32 {
+    (1.0 - distance).clamp(0.0, 1.0)
+}
+
+impl SearchHit {
+    /// Score a vector match by its cosine distance, per the score contract.
+    #[must_use]
+    pub fn with_cosine_distance(mut self, distance: f32) -> Self {
+        self.score = similarity_from_distance(distance);
+        self.distance = Some(distance);
+        self
+    }
+
+    /// Copy the entity fields stored with the matched chunk onto the hit.
+    #[must_use]
+    pub fn with_facets(mut self, facets: SearchFacets) -> Self {
+        self.status = Some(facets.status).filter(|status| !status.is_empty());
+        self.tags = facets.tags;
+        self.path = Some(facets.path).filter(|path| !path.is_empty());
+        self
+    }
+
+    /// Set the snippet from the passage, highlighting the terms of `query`.
+    pub fn highlight(&mut self, query: &str) {
+        self.snippet = self
+            .passage
+            .as_ref()
+            .and_then(|passage| snippet(&passage.text, query, SNIPPET_CHARS));
+    }
+
+    /// Text shown to a reranker: the title plus the best passage.
+    #[must_use]
+    pub fn rerank_text(&self) -> String {
+        match &self.passage {
+            Some(passage) if !passage.text.is_empty() => {
+                format!("{}\n\n{}", self.title, passage.text)
+            }
+            _ => self.title.clone(),
+        }
+    }
+}
+
+/// Collapse chunk-level hits to one hit per entity, keeping each entity's
+/// best-scoring passage, and return the top `limit` by score.
+#[must_use]
+pub fn group_chunk_hits(mut hits: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut seen = std::collections::HashSet::new();
+    hits.retain(|hit| seen.insert(hit.id.clone()));
+    hits.truncate(limit);
+    hits
+}
+
+/// How a backend retrieves candidates for a query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SearchMode {
+    /// Nearest chunks by embedding similarity.
+    #[default]
+    Vector,
+    /// BM25 full-text match; finds exact identifiers embeddings blur.
+    Lexical,
+    /// Both, merged with reciprocal rank fusion.
+    Hybrid,
+}
+
+impl SearchMode {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Vector => "vector",
+            Self::Lexical => "lexical",
+            Self::Hybrid => "hybrid",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseSearchModeError {
+    #[error("Unknown search mode: {0} (expected vector, lexical or hybrid)")]
+    UnknownMode(String),
+}
+
+impl FromStr for SearchMode {
+    type Err = ParseSearchModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "vector" => Ok(Self::Vector),
+            "lexical" | "bm25" | "text" => Ok(Self::Lexical),
+            "hybrid" => Ok(Self::Hybrid),
+            _ => Err(ParseSearchModeError::UnknownMode(s.to_string())),
+        }
+    }
+}
+
+/// Rank offset of reciprocal rank fusion; 60 is the value from the original
+/// paper and keeps a single top rank from dominating the fused order.
+pub const RRF_K: f32 = 60.0;
+
+/// Candidates fetched from each retriever per requested hit in hybrid mode.
+pub const HYBRID_OVERSAMPLE: usize = 2;
+
+/// Merge ranked hit lists with reciprocal rank fusion: each hit scores
+/// `sum(1 / (RRF_K + rank))` over the lists it appears in.
+///
+/// The passage comes from the first list that returned the entity, the
+/// distance from the list that measured one.
+#[must_use]
+pub fn reciprocal_rank_fusion(lists: &[Vec<SearchHit>], limit: usize) -> Vec<SearchHit> {
+    let mut fused: Vec<SearchHit> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for list in lists {
+        for (rank, hit) in list.iter().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let contribution = 1.0 / (RRF_K + (rank + 1) as f32);
+            if let Some(&position) = positions.get(&hit.id) {
+                fused[position].score += contribution;
+                fused[position].distance = fused[position].distance.or(hit.distance);
+            } else {
+                positions.insert(hit.id.clone(), fused.len());
+                fused.push(SearchHit {
+                    score: contribution,
+                    ..hit.clone()
+                });
+            }
+        }
+    }
+
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    fused.truncate(limit);
+    fused
+}
+
+/// Run a search in `mode` given a backend's vector and lexical retrievers,
+/// each called with the number of hits it should return.
+pub fn search_with_mode<V, L>(
+    mode: SearchMode,
+    limit: usize,
+    vector: V,
+    lexical: L,
+) -> Result<Vec<SearchHit>>
+where
+    V: FnOnce(usize) -> Result<Vec<SearchHit>>,
+    L: FnOnce(usize) -> Result<Vec<SearchHit>>,
+{
+    match mode {
+        SearchMode::Vector => vector(limit),
+        SearchMode::Lexical => lexical(limit),
+        SearchMode::Hybrid => {
+            let candidates = limit.saturating_mul(HYBRID_OVERSAMPLE);
+            let lists = [vector(candidates)?, lexical(candidates)?];
+            Ok(reciprocal_rank_fusion(&lists, limit))
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SearchRequestError {
+    #[error("Invalid date: {0} (expected YYYY-MM-DD or an RFC 3339 timestamp)")]
+    InvalidDate(String),
+    #[error("Invalid search cursor: {0}")]
+    InvalidCursor(String),
+}
+
+/// Parse a filter bound: a plain date means midnight UTC.
+pub fn parse_date(raw: &str) -> Result<DateTime<Utc>, SearchRequestError> {
+    let raw = raw.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .ok_or_else(|| SearchRequestError::InvalidDate(raw.to_string()))
+}
+
+/// Canonical form of stored timestamps: UTC to the second, so they compare
+/// correctly as strings in databases that don't parse them.
+#[must_use]
+pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it into
+/// place, so a crash mid-write never leaves a truncated sidecar behind.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    let written = std::fs::write(&tmp, contents).and_then(|()| std::fs::rename(&tmp, path));
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(err).with_context(|| format!("Failed to write {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Half-open time window: `after` is inclusive, `before` exclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    #[must_use]
+    pub const fn is_unbounded(&self) -> bool {
+        self.after.is_none() && self.before.is_none()
+    }
+
+    /// Whether a canonical `timestamp` falls in the range. Entities without
+    /// the timestamp only pass an unbounded range.
+    #[must_use]
+    pub fn contains(&self, timestamp: Option<&str>) -> bool {
+        if self.is_unbounded() {
+            return true;
+        }
+        let Some(timestamp) = timestamp else {
+            return false;
+        };
+        self.after
+            .is_none_or(|after| timestamp >= format_timestamp(after).as_str())
+            && self
+                .before
+                .is_none_or(|before| timestamp < format_timestamp(before).as_str())
+    }
+}
+
+/// Entity fields denormalized onto every indexed chunk, so backends can
+/// filter and describe hits without joining back to the entity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchFacets {
+    /// Entity file relative to the repository root; not filterable.
+    #[serde(default)]
+    pub path: String,
+    pub status: String,
+    pub tags: Vec<String>,
+    /// Canonical timestamp, see [`format_timestamp`].
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl SearchFacets {
+    /// Facets of an entity; `created_at` falls back to `date` for entities
+    /// that only record a date.
+    #[must_use]
+    pub fn from_frontmatter(frontmatter: &Mapping) -> Self {
+        let timestamp = |key: &str| {
+            get_string(frontmatter, key)
+                .and_then(|raw| parse_date(&raw).ok())
+                .map(format_timestamp)
+        };
+        Self {
+            path: String::new(),
+            status: get_string(frontmatter, "status").unwrap_or_default(),
+            tags: get_string_list(frontmatter, "tags"),
+            created_at: timestamp("created_at").or_else(|| timestamp("date")),
+            updated_at: timestamp("updated_at"),
+        }
+    }
+}
+
+/// Restrictions on which entities a search may return.
+///
+/// Empty lists and unbounded ranges don't filter; statuses match
+/// case-insensitively, ignoring surrounding whitespace, and every listed tag
+/// must be present.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchFilters {
+    pub kinds: Vec<EntityKind>,
+    pub statuses: Vec<String>,
+    pub tags: Vec<String>,
+    pub created: DateRange,
+    pub updated: DateRange,
+}
+
+impl SearchFilters {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+            && self.statuses.is_empty()
+            && self.tags.is_empty()
+            && self.created.is_unbounded()
+            && self.updated.is_unbounded()
+    }
+
+    #[must_use]
+    pub fn matches(&self, kind: Option<EntityKind>, facets: &SearchFacets) -> bool {
+        (self.kinds.is_empty() || kind.is_some_and(|kind| self.kinds.contains(&kind)))
+            && (self.statuses.is_empty()
+                || self
+                    .statuses
+                    .iter()
+                    .any(|status| status.trim().eq_ignore_ascii_case(facets.status.trim())))
+            && self.tags.iter().all(|tag| facets.tags.contains(tag))
+            && self.created.contains(facets.created_at.as_deref())
+            && self.updated.contains(facets.updated_at.as_deref())
+    }
+}
+
+/// A search query with its filters and the page of results wanted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRequest {
+    pub query: String,
+    pub limit: usize,
+    /// Hits to skip before the page starts.
+    pub offset: usize,
+    pub mode: SearchMode,
+    pub filters: SearchFilters,
+    /// Drop hits scoring below this. Scores are those of the search mode:
+    /// similarity for vector, BM25 for lexical and fused rank for hybrid.
+    pub min_score: Option<f32>,
+}
+
+impl SearchRequest {
+    #[must_use]
+    pub fn new(query: impl Into<String>, limit: usize) -> Self {
+        Self {
+            query: query.into(),
+            limit,
+            offset: 0,
+            mode: SearchMode::default(),
+            filters: SearchFilters::default(),
+            min_score: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_filters(mut self, filters: SearchFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_min_score(mut self, min_score: Option<f32>) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Resume after a page, given the cursor returned with it.
+    pub fn with_cursor(self, cursor: &str) -> Result<Self, SearchRequestError> {
+        let offset = cursor
+            .trim()
+            .parse()
+            .map_err(|_| SearchRequestError::InvalidCursor(cursor.to_string()))?;
+        Ok(self.with_offset(offset))
+    }
+
+    /// Hits a backend must rank to serve this page.
+    #[must_use]
+    pub const fn window(&self) -> usize {
+        self.offset.saturating_add(self.limit)
+    }
+
+    /// Apply the score threshold to the top [`Self::window`] hits, cut out
+    /// the requested page and highlight the query in each hit's snippet.
+    #[must_use]
+    pub fn page(&self, hits: Vec<SearchHit>) -> Vec<SearchHit> {
+        hits.into_iter()
+            .filter(|hit| self.min_score.is_none_or(|min| hit.score >= min))
+            .skip(self.offset)
+            .take(self.limit)
+            .map(|mut hit| {
+                hit.highlight(&self.query);
+                hit
+            })
+            .collect()
+    }
+
+    /// Cursor for the page after one that returned `returned` hits, or `None`
+    /// when that page was the last.
+    #[must_use]
+    pub fn next_cursor(&self, returned: usize) -> Option<String> {
+        (returned > 0 && returned >= self.limit).then(|| (self.offset + returned).to_string())
+    }
+}
+
+/// Attach reranker `scores` (one per hit, in order), reorder by them and keep
+/// the top `limit`. Vector scores are kept so callers can show both.
+pub fn apply_rerank_scores(
+    mut hits: Vec<SearchHit>,
+    scores: &[f32],
+    limit: usize,
+) -> Result<Vec<SearchHit>> {
+    anyhow::ensure!(
+        scores.len() == hits.len(),
+        "Reranker returned {} scores for {} hits",
+        scores.len(),
+        hits.len()
+    );
+    for (hit, score) in hits.iter_mut().zip(scores) {
+        hit.rerank_score = Some(*score);
+    }
+    hits.sort_by(|a, b| {
+        b.rerank_score
+            .unwrap_or(f32::MIN)
+            .total_cmp(&a.rerank_score.unwrap_or(f32::MIN))
+    });
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Version of the index layout. Bump it when tables, node labels or stored
+/// fields change so existing indexes get rebuilt on the next sync.
+pub const INDEX_SCHEMA_VERSION: u32 = 5;
+
+/// Identifies how embedding input is derived from entities; see
+/// [`Chunk::embedding_text`].
+pub const EMBEDDING_TEXT_RECIPE: &str = "chunk-v1";
+
+/// What an index was built with. Vectors from different providers, models or
+/// dimensions can't be compared, so a mismatch means the index must be rebuilt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexMetadata {
+    pub backend: String,
+    pub provider: String,
+    pub model: String,
+    pub dimension: usize,
+    /// Instruction prepended to every indexed document.
+    #[serde(default)]
+    pub document_prefix: String,
+    pub recipe: String,
+    pub schema_version: u32,
+}
+
+impl IndexMetadata {
+    /// Metadata for the current schema version and embedding recipe.
+    #[must_use]
+    pub fn current(
+        backend: &str,
+        provider: &str,
+        model: &str,
+        dimension: usize,
+        document_prefix: &str,
+    ) -> Self {
+        Self {
+            backend: backend.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            dimension,
+            document_prefix: document_prefix.to_string(),
+            recipe: EMBEDDING_TEXT_RECIPE.to_string(),
+            schema_version: INDEX_SCHEMA_VERSION,
+        }
+    }
+
+    /// Metadata lives next to the database directory, e.g. `data/ixchel.meta.json`.
+    #[must_use]
+    pub fn sidecar_path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".meta.json");
+        db_path.with_file_name(name)
+    }
+
+    /// Read the metadata stored for `db_path`, or `None` if the index has
+    /// never been synced with metadata.
+    pub fn load(db_path: &Path) -> Result<Option<Self>> {
+        let path = Self::sidecar_path(db_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let metadata = serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(metadata))
+    }
+
+    pub fn save(&self, db_path: &Path) -> Result<()> {
+        let path = Self::sidecar_path(db_path);
+        let raw = serde_json::to_string_pretty(self)?;
+        write_atomic(&path, format!("{raw}\n"))
+    }
+
+    /// Fields that differ from `expected`, as `field: stored -> expected`.
+    #[must_use]
+    pub fn differences(&self, expected: &Self) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut compare = |field: &str, stored: String, wanted: String| {
+            if stored != wanted {
+                out.push(format!("{field}: {stored} -> {wanted}"));
+            }
+        };
+        compare("backend", self.backend.clone(), expected.backend.clone());
+        compare("provider", self.provider.clone(), expected.provider.clone());
+        compare("model", self.model.clone(), expected.model.clone());
+        compare(
+            "dimension",
+            self.dimension.to_string(),
+            expected.dimension.to_string(),
+        );
+        compare(
+            "document_prefix",
+            format!("{:?}", self.document_prefix),
+            format!("{:?}", expected.document_prefix),
+        );
+        compare("recipe", self.recipe.clone(), expected.recipe.clone());
+        compare(
+            "schema_version",
+            self.schema_version.to_string(),
+            expected.schema_version.to_string(),
+        );
+        out
+    }
+}
+
+/// Stored index metadata compared with what the current configuration would
+/// produce.
+#[derive(Debug, Clone)]
+pub struct IndexMetadataStatus {
+    pub expected: IndexMetadata,
+    /// `None` before the first sync (or for indexes built before metadata).
+    pub stored: Option<IndexMetadata>,
+}
+
+impl IndexMetadataStatus {
+    #[must_use]
+    pub fn differences(&self) -> Vec<String> {
+        self.stored
+            .as_ref()
+            .map(|stored| stored.differences(&self.expected))
+            .unwrap_or_default()
+    }
+
+    /// Whether a sync must re-embed everything instead of updating in place.
+    #[must_use]
+    pub fn needs_rebuild(&self) -> bool {
+        self.stored.as_ref() != Some(&self.expected)
+    }
+
+    /// Fail if the stored index can't be queried with the current embedder.
+    pub fn ensure_compatible(&self) -> Result<()> {
+        let differences = self.differences();
+        if differences.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "Index was built with a different embedding setup ({}). \
+             Run `ixchel sync` to rebuild it.",
+            differences.join(", ")
+        )
+    }
 }
 
 pub trait IndexBackend: Send + Sync {
     fn sync(&mut self, repo: &IxchelRepo) -> Result<SyncStats>;
-    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>>;
+    fn search(&self, request: &SearchRequest) -> Result<Vec<SearchHit>>;
+    /// Entities nearest to the stored vector of `id`, excluding `id` itself,
+    /// scored by cosine similarity.
+    fn related(&self, id: &str, limit: usize) -> Result<Vec<SearchHit>>;
+    /// Entities nearest to `text` embedded like the only chunk of a new
+    /// entity, scored by cosine similarity.
+    fn nearest_entities(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>>;
+    /// Every indexed entity with its pooled vector.
+    fn entity_vectors(&self) -> Result<Vec<EntityVector>>;
+    /// The indexed entity `id`, or `None` when it isn't indexed.
+    fn entity(&self, id: &str) -> Result<Option<EntityRef>>;
+    /// Entities linked to `id` by relationships `filter` allows, sorted by
+    /// relationship then ID. Links to unindexed entities are left out.
+    fn neighbors(&self, id: &str, filter: &EdgeFilter) -> Result<Vec<Neighbor>>;
     fn health_check(&self) -> Result<()>;
+    fn metadata(&self) -> Result<IndexMetadataStatus>;
+
+    /// See [`graph::traverse`].
+    fn traverse(&self, id: &str, filter: &EdgeFilter, max_hops: usize) -> Result<Subgraph> {
+        graph::traverse(self, id, filter, max_hops)
+    }
+
+    /// See [`graph::shortest_path`].
+    fn shortest_path(
+        &self,
+        from: &str,
+        to: &str,
+        filter: &EdgeFilter,
+    ) -> Result<Option<Vec<Relationship>>> {
+        graph::shortest_path(self, from, to, filter)
+    }
+
+    /// See [`graph::subgraph`].
+    fn subgraph(&self, ids: &[String], filter: &EdgeFilter) -> Result<Subgraph> {
+        graph::subgraph(self, ids, filter)
+    }
 }
@@ -0,0 +1,278 @@
+//! Built-in BM25 full-text index over entity chunks, for backends without
+//! full-text search of their own.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::Chunk;
+use crate::entity::EntityKind;
+use crate::index::{SearchFacets, SearchFilters, SearchHit, group_chunk_hits, write_atomic};
+
+/// BM25 term-frequency saturation.
+const BM25_K1: f32 = 1.2;
+/// BM25 document-length normalization.
+const BM25_B: f32 = 0.75;
+
+/// Characters that join identifier parts, e.g. `ix-core`, `snake_case`,
+/// `std::fs` or `v1.2`.
+pub(crate) const IDENT_JOINERS: &[char] = &['-', '_', '.', ':'];
+
+/// Split text into lowercase search terms.
+///
+/// Identifiers are kept whole and also split into their parts, so `ix-core`
+/// matches both an exact `ix-core` query and a query for `core`.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let words = text.split(|c: char| !(c.is_alphanumeric() || IDENT_JOINERS.contains(&c)));
+    for word in words {
+        let word = word.trim_matches(IDENT_JOINERS).to_lowercase();
+        if word.is_empty() {
+            continue;
+        }
+        let parts = word
+            .split(IDENT_JOINERS)
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        terms.push(word);
+        if parts.len() > 1 {
+            terms.extend(parts);
+        }
+    }
+    terms
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LexicalDoc {
+    entity_id: String,
+    kind: String,
+    title: String,
+    #[serde(default)]
+    facets: SearchFacets,
+    chunk: Chunk,
+}
+
+impl LexicalDoc {
+    fn terms(&self) -> Vec<String> {
+        tokenize(&self.chunk.search_text(&self.title))
+    }
+}
+
+/// Inverted index of chunk text scored with BM25.
+///
+/// Only the chunks are persisted; postings are rebuilt when the index is
+/// loaded.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LexicalIndex {
+    docs: Vec<LexicalDoc>,
+    #[serde(skip)]
+    postings: HashMap<String, Vec<(usize, u32)>>,
+    #[serde(skip)]
+    lengths: Vec<u32>,
+}
+
+impl LexicalIndex {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, entity_id: &str, kind: EntityKind, title: &str, chunk: &Chunk) {
+        self.add_with_facets(entity_id, kind, title, &SearchFacets::default(), chunk);
+    }
+
+    /// Add a chunk along with the entity fields [`Self::search_filtered`]
+    /// filters on.
+    pub fn add_with_facets(
+        &mut self,
+        entity_id: &str,
+        kind: EntityKind,
+        title: &str,
+        facets: &SearchFacets,
+        chunk: &Chunk,
+    ) {
+        self.docs.push(LexicalDoc {
+            entity_id: entity_id.to_string(),
+            kind: kind.as_str().to_string(),
+            title: title.to_string(),
+            facets: facets.clone(),
+            chunk: chunk.clone(),
+        });
+        self.index_doc(self.docs.len() - 1);
+    }
+
+    /// Drop every chunk of `entity_id`.
+    pub fn remove_entity(&mut self, entity_id: &str) {
+        self.remove_entities([entity_id]);
+    }
+
+    /// Drop every chunk of the given entities, rebuilding the postings once.
+    pub fn remove_entities<'a>(&mut self, entity_ids: impl IntoIterator<Item = &'a str>) {
+        let entity_ids = entity_ids.into_iter().collect::<HashSet<_>>();
+        if entity_ids.is_empty() {
+            return;
+        }
+        self.docs
+            .retain(|doc| !entity_ids.contains(doc.entity_id.as_str()));
+        self.reindex();
+    }
+
+    /// Move every chunk of `other` to the end of this index.
+    pub fn append(&mut self, other: Self) {
+        for doc in other.docs {
+            self.docs.push(doc);
+            self.index_doc(self.docs.len() - 1);
+        }
+    }
+
+    /// Every entity with at least one chunk in the index, sorted.
+    #[must_use]
+    pub fn entity_ids(&self) -> Vec<String> {
+        let mut ids = self
+            .docs
+            .iter()
+            .map(|doc| doc.entity_id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Best-matching entities for `query`, one hit per entity carrying its
+    /// best chunk, scored with BM25.
+    #[must_use]
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        self.search_filtered(query, limit, &SearchFilters::default())
+    }
+
+    /// [`Self::search`] over the chunks whose entity passes `filters`.
+    #[must_use]
+    pub fn search_filtered(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Vec<SearchHit> {
+        if self.docs.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let doc_count = self.docs.len() as f32;
+        #[allow(clippy::cast_precision_loss)]
+        let avg_len = self.lengths.iter().map(|&n| n as f32).sum::<f32>() / doc_count;
+
+        let allowed = self
+            .docs
+            .iter()
+            .map(|doc| filters.matches(doc.kind.parse::<EntityKind>().ok(), &doc.facets))
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for term in tokenize(query) {
+            if !seen.insert(term.clone()) {
+                continue;
+            }
+            let Some(postings) = self.postings.get(&term) else {
+                continue;
+            };
+            #[allow(clippy::cast_precision_loss)]
+            let df = postings.len() as f32;
+            let idf = ((doc_count - df + 0.5) / (df + 0.5)).ln_1p();
+            for &(doc, tf) in postings {
+                if !allowed[doc] {
+                    continue;
+                }
+                #[allow(clippy::cast_precision_loss)]
+                let (tf, len) = (tf as f32, self.lengths[doc] as f32);
+                let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len.max(1.0));
+                *scores.entry(doc).or_default() += idf * tf * (BM25_K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        // Ties keep chunk order, so repeated searches page consistently
+        let mut scores = scores.into_iter().collect::<Vec<_>>();
+        scores.sort_unstable_by_key(|&(doc, _)| doc);
+        let hits = scores
+            .into_iter()
+            .map(|(doc, score)| {
+                let doc = &self.docs[doc];
+                SearchHit {
+                    score,
+                    id: doc.entity_id.clone(),
+                    kind: doc.kind.parse::<EntityKind>().ok(),
+                    title: doc.title.clone(),
+                    passage: Some(doc.chunk.clone()),
+                    ..SearchHit::default()
+                }
+                .with_facets(doc.facets.clone())
+            })
+            .collect();
+        group_chunk_hits(hits, limit)
+    }
+
+    /// The index lives next to the database, e.g. `data/ixchel.lexical.json`.
+    #[must_use]
+    pub fn sidecar_path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lexical.json");
+        db_path.with_file_name(name)
+    }
+
+    /// Read the index stored for `db_path`, or `None` before the first sync.
+    pub fn load(db_path: &Path) -> Result<Option<Self>> {
+        let path = Self::sidecar_path(db_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut index: Self = serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        index.reindex();
+        Ok(Some(index))
+    }
+
+    pub fn save(&self, db_path: &Path) -> Result<()> {
+        let path = Self::sidecar_path(db_path);
+        let raw = serde_json::to_string(self)?;
+        write_atomic(&path, raw)
+    }
+
+    fn reindex(&mut self) {
+        self.postings.clear();
+        self.lengths.clear();
+        for doc in 0..self.docs.len() {
+            self.index_doc(doc);
+        }
+    }
+
+    fn index_doc(&mut self, doc: usize) {
+        let terms = self.docs[doc].terms();
+        self.lengths
+            .push(u32::try_from(terms.len()).unwrap_or(u32::MAX));
+
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for term in terms {
+            *counts.entry(term).or_default() += 1;
+        }
+        for (term, tf) in counts {
+            self.postings.entry(term).or_default().push((doc, tf));
+        }
+    }
+}
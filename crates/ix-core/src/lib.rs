@@ -1,7 +1,16 @@
+pub mod chunk;
+pub mod context;
 pub mod entity;
+pub mod eval;
+pub mod graph;
 pub mod index;
+pub mod lexical;
+pub mod manifest;
 pub mod markdown;
 pub mod paths;
 pub mod repo;
+pub mod similarity;
+pub mod snippet;
+pub mod topics;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,57 +1,89 @@
 //! Sync manifest for tracking entity state between syncs.
 //!
-//! The manifest stores content hashes and file paths for each entity,
-//! enabling incremental sync that skips unchanged files.
+//! The manifest stores content hashes and file paths for each entity, plus
+//! whatever the backend wrote for it, so a sync can skip unchanged files and
+//! remove exactly what a changed or deleted file left behind.
 
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::index::write_atomic;
 
 /// Entry in the sync manifest tracking an entity's state.
+///
+/// `T` records what the backend wrote for the entity, e.g. node IDs; `()`
+/// for backends that can find an entity's records by its ID.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ManifestEntry {
+pub struct ManifestEntry<T = ()> {
     /// Content hash (blake3) of the file at last sync.
     pub content_hash: String,
     /// Relative file path from repo root.
     pub file_path: String,
     /// Unix timestamp (seconds) of last sync.
     pub last_synced: u64,
+    /// What the backend wrote for the entity.
+    pub written: T,
 }
 
 /// Manifest tracking all synced entities.
 ///
 /// Used to determine which files have changed since the last sync,
 /// enabling incremental updates instead of full rebuilds.
-#[derive(Debug, Default)]
-pub struct SyncManifest {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncManifest<T = ()> {
     /// Map from `entity_id` to manifest entry.
-    entries: HashMap<String, ManifestEntry>,
+    entries: HashMap<String, ManifestEntry<T>>,
+}
+
+impl<T> Default for SyncManifest<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// Result of comparing a file against the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Entity is new, needs to be inserted.
+    Insert,
+    /// Entity has changed, needs to be updated.
+    Update,
+    /// Entity is unchanged, skip processing.
+    Skip,
 }
 
-impl SyncManifest {
+impl<T> SyncManifest<T> {
     /// Create an empty manifest.
+    #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
     /// Create a manifest from existing entries.
-    pub const fn from_entries(entries: HashMap<String, ManifestEntry>) -> Self {
+    #[must_use]
+    pub const fn from_entries(entries: HashMap<String, ManifestEntry<T>>) -> Self {
         Self { entries }
     }
 
     /// Get an entry by entity ID.
-    #[allow(dead_code)]
-    pub fn get(&self, entity_id: &str) -> Option<&ManifestEntry> {
+    #[must_use]
+    pub fn get(&self, entity_id: &str) -> Option<&ManifestEntry<T>> {
         self.entries.get(entity_id)
     }
 
     /// Insert or update an entry.
-    pub fn insert(&mut self, entity_id: String, entry: ManifestEntry) {
+    pub fn insert(&mut self, entity_id: String, entry: ManifestEntry<T>) {
         self.entries.insert(entity_id, entry);
     }
 
     /// Remove an entry by entity ID.
-    #[allow(dead_code)]
-    pub fn remove(&mut self, entity_id: &str) -> Option<ManifestEntry> {
+    pub fn remove(&mut self, entity_id: &str) -> Option<ManifestEntry<T>> {
         self.entries.remove(entity_id)
     }
 
@@ -60,40 +92,20 @@ impl SyncManifest {
         self.entries.keys()
     }
 
-    /// Check if the manifest contains an entity.
-    #[allow(dead_code)]
-    pub fn contains(&self, entity_id: &str) -> bool {
-        self.entries.contains_key(entity_id)
-    }
-
-    /// Get the number of entries.
-    #[allow(dead_code)]
+    #[must_use]
     pub fn len(&self) -> usize {
         self.entries.len()
     }
 
-    /// Check if the manifest is empty.
-    #[allow(dead_code)]
+    #[must_use]
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
-}
-
-/// Result of comparing a file against the manifest.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SyncAction {
-    /// Entity is new, needs to be inserted.
-    Insert,
-    /// Entity has changed, needs to be updated.
-    Update,
-    /// Entity is unchanged, skip processing.
-    Skip,
-}
 
-impl SyncManifest {
     /// Determine what action to take for an entity based on its current hash and path.
     ///
     /// Returns `Update` if either the content hash or file path has changed.
+    #[must_use]
     pub fn action_for(
         &self,
         entity_id: &str,
@@ -110,72 +122,45 @@ impl SyncManifest {
             None => SyncAction::Insert,
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TEST_PATH: &str = ".ixchel/decisions/dec-123.md";
-
-    #[test]
-    fn test_manifest_action_for_new() {
-        let manifest = SyncManifest::new();
-        assert_eq!(
-            manifest.action_for("dec-123", "abc123", TEST_PATH),
-            SyncAction::Insert
-        );
+    /// The manifest lives next to the database, e.g. `data/ixchel.manifest.json`.
+    #[must_use]
+    pub fn sidecar_path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".manifest.json");
+        db_path.with_file_name(name)
     }
+}
 
-    #[test]
-    fn test_manifest_action_for_unchanged() {
-        let mut manifest = SyncManifest::new();
-        manifest.insert(
-            "dec-123".to_string(),
-            ManifestEntry {
-                content_hash: "abc123".to_string(),
-                file_path: TEST_PATH.to_string(),
-                last_synced: 1000,
-            },
-        );
-        assert_eq!(
-            manifest.action_for("dec-123", "abc123", TEST_PATH),
-            SyncAction::Skip
-        );
+impl<T: Serialize + DeserializeOwned> SyncManifest<T> {
+    /// Read the manifest stored for `db_path`, or `None` before the first
+    /// incremental sync.
+    pub fn load(db_path: &Path) -> Result<Option<Self>> {
+        let path = Self::sidecar_path(db_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let manifest = serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(manifest))
     }
 
-    #[test]
-    fn test_manifest_action_for_content_changed() {
-        let mut manifest = SyncManifest::new();
-        manifest.insert(
-            "dec-123".to_string(),
-            ManifestEntry {
-                content_hash: "abc123".to_string(),
-                file_path: TEST_PATH.to_string(),
-                last_synced: 1000,
-            },
-        );
-        assert_eq!(
-            manifest.action_for("dec-123", "different_hash", TEST_PATH),
-            SyncAction::Update
-        );
+    /// Delete the manifest stored for `db_path`, so the next sync treats the
+    /// database as unknown and rebuilds it.
+    pub fn discard(db_path: &Path) -> Result<()> {
+        let path = Self::sidecar_path(db_path);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
     }
 
-    #[test]
-    fn test_manifest_action_for_path_changed() {
-        let mut manifest = SyncManifest::new();
-        manifest.insert(
-            "dec-123".to_string(),
-            ManifestEntry {
-                content_hash: "abc123".to_string(),
-                file_path: TEST_PATH.to_string(),
-                last_synced: 1000,
-            },
-        );
-        // Same content hash but different path should trigger update
-        assert_eq!(
-            manifest.action_for("dec-123", "abc123", ".ixchel/decisions/renamed.md"),
-            SyncAction::Update
-        );
+    pub fn save(&self, db_path: &Path) -> Result<()> {
+        let path = Self::sidecar_path(db_path);
+        let raw = serde_json::to_string(self)?;
+        write_atomic(&path, raw)
     }
 }
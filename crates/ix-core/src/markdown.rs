@@ -81,6 +81,20 @@ pub fn parse_markdown(path: &Path, contents: &str) -> Result<MarkdownDocument, M
     Ok(MarkdownDocument { frontmatter, body })
 }
 
+/// Number of file lines before the body returned by [`parse_markdown`].
+///
+/// Adding this to a 1-based body line number gives the line number in the file.
+#[must_use]
+pub fn body_line_offset(contents: &str) -> usize {
+    let mut lines = contents.lines();
+    if lines.next() != Some("---") {
+        return 0;
+    }
+    lines
+        .position(|line| line == "---")
+        .map_or(0, |closing| closing + 2)
+}
+
 pub fn render_markdown(doc: &MarkdownDocument) -> Result<String, MarkdownError> {
     let mut out = String::new();
     out.push_str("---\n");
@@ -138,3 +152,181 @@ pub fn set_string_list(frontmatter: &mut Mapping, key: &str, values: Vec<String>
     let seq = values.into_iter().map(Value::String).collect();
     frontmatter.insert(Value::String(key.to_string()), Value::Sequence(seq));
 }
+
+/// A heading-delimited region of a Markdown body.
+///
+/// Text before the first heading is returned as a section with `level == 0`
+/// and an empty heading. Line numbers are 1-based and refer to the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub level: usize,
+    pub heading: String,
+    pub text: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// A `- [ ]` / `- [x]` task list item found in a Markdown body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    /// 1-based position among all checklist items in the body.
+    pub index: usize,
+    pub checked: bool,
+    pub text: String,
+    /// Heading of the section containing the item, if any.
+    pub section: Option<String>,
+    /// 1-based line number within the body.
+    pub line: usize,
+}
+
+#[must_use]
+pub fn parse_sections(body: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut current = Section {
+        level: 0,
+        heading: String::new(),
+        text: String::new(),
+        start_line: 1,
+        end_line: 0,
+    };
+    let mut in_fence = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        let line_no = idx + 1;
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+
+        let heading = if in_fence { None } else { parse_heading(line) };
+        if let Some((level, heading)) = heading {
+            if current.level > 0 || !current.text.trim().is_empty() {
+                sections.push(finish_section(current));
+            }
+            current = Section {
+                level,
+                heading,
+                text: String::new(),
+                start_line: line_no,
+                end_line: line_no,
+            };
+            continue;
+        }
+
+        current.text.push_str(line);
+        current.text.push('\n');
+        current.end_line = line_no;
+    }
+
+    if current.level > 0 || !current.text.trim().is_empty() {
+        sections.push(finish_section(current));
+    }
+
+    sections
+}
+
+#[must_use]
+pub fn parse_checklist(body: &str) -> Vec<ChecklistItem> {
+    let mut items = Vec::new();
+    let mut section: Option<String> = None;
+    let mut in_fence = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some((_, heading)) = parse_heading(line) {
+            section = Some(heading);
+            continue;
+        }
+
+        if let Some((checked, text)) = parse_checkbox(line) {
+            items.push(ChecklistItem {
+                index: items.len() + 1,
+                checked,
+                text: text.to_string(),
+                section: section.clone(),
+                line: idx + 1,
+            });
+        }
+    }
+
+    items
+}
+
+/// Set the checked state of the `index`-th (1-based) checklist item.
+///
+/// Returns `None` when the body has no item at that position.
+#[must_use]
+pub fn set_checklist_item(body: &str, index: usize, checked: bool) -> Option<String> {
+    let item = parse_checklist(body)
+        .into_iter()
+        .find(|item| item.index == index)?;
+
+    let mut lines = body.split('\n').map(str::to_string).collect::<Vec<_>>();
+    let line = lines.get_mut(item.line - 1)?;
+    let open = line.find('[')?;
+    let mark = if checked { "x" } else { " " };
+    line.replace_range(open + 1..open + 2, mark);
+
+    Some(lines.join("\n"))
+}
+
+fn finish_section(mut section: Section) -> Section {
+    let trimmed = section.text.trim_end().len();
+    section.text.truncate(trimmed);
+    section
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+
+    // Drop an optional closing `###` sequence, but keep `C#`-style suffixes.
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with(' ') {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+
+    Some((level, text.to_string()))
+}
+
+fn parse_checkbox(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim_start();
+    let rest = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))?;
+
+    let checked = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    let text = &rest[3..];
+    if !text.is_empty() && !text.starts_with(' ') {
+        return None;
+    }
+
+    Some((checked, text.trim()))
+}
@@ -37,6 +37,24 @@ impl IxchelPaths {
         self.ixchel_dir().join("data")
     }
 
+    /// Golden search queries for `ixchel eval` (checked in).
+    #[must_use]
+    pub fn eval_path(&self) -> PathBuf {
+        self.ixchel_dir().join("eval.yaml")
+    }
+
+    /// Embedding vectors shared by every index backend.
+    #[must_use]
+    pub fn embedding_cache_dir(&self) -> PathBuf {
+        self.data_dir().join("embeddings")
+    }
+
+    /// Downloaded and imported embedding/reranker models (gitignored).
+    #[must_use]
+    pub fn models_dir(&self) -> PathBuf {
+        self.ixchel_dir().join("models")
+    }
+
     #[must_use]
     pub fn kind_dir(&self, kind: EntityKind) -> PathBuf {
         self.ixchel_dir().join(kind.directory_name())
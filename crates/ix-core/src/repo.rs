@@ -1,18 +1,19 @@
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, SecondsFormat, Utc};
-use ix_config::{ConfigLoader, IxchelConfig};
+use ix_config::{ConfigLoader, EmbeddingConfig, IxchelConfig};
 use serde_yaml::{Mapping, Value};
 use thiserror::Error;
 
 use crate::entity::{EntityKind, kind_from_id, looks_like_entity_id};
+use crate::index::IndexMetadata;
 use crate::markdown::{
-    MarkdownDocument, MarkdownError, get_string, get_string_list, parse_markdown, render_markdown,
-    set_string, set_string_list,
+    ChecklistItem, MarkdownDocument, MarkdownError, get_string, get_string_list, parse_checklist,
+    parse_markdown, render_markdown, set_checklist_item, set_string, set_string_list,
 };
 use crate::paths::{IxchelPaths, find_git_root};
 
@@ -22,6 +23,16 @@ pub struct EntitySummary {
     pub kind: EntityKind,
     pub title: String,
     pub path: PathBuf,
+    /// Checklist completion of the entity's body.
+    pub tasks: TaskProgress,
+}
+
+/// A typed frontmatter link, e.g. `iss-1 implements dec-2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    pub from: String,
+    pub rel: String,
+    pub to: String,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -52,6 +63,92 @@ impl FromStr for ListSort {
     }
 }
 
+/// Checklist completion for a single entity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl TaskProgress {
+    #[must_use]
+    pub fn from_items(items: &[ChecklistItem]) -> Self {
+        Self {
+            done: items.iter().filter(|item| item.checked).count(),
+            total: items.len(),
+        }
+    }
+
+    /// Completion percentage rounded down, or `None` when there are no tasks.
+    #[must_use]
+    pub const fn percent(self) -> Option<usize> {
+        (self.done * 100).checked_div(self.total)
+    }
+}
+
+/// An issue as seen by the dependency graph.
+#[derive(Debug, Clone)]
+pub struct IssueRef {
+    pub id: String,
+    pub title: String,
+    pub status: Option<String>,
+    pub path: PathBuf,
+}
+
+impl IssueRef {
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        !self.status.as_deref().is_some_and(is_closed_status)
+    }
+}
+
+/// An open issue together with the open issues it is waiting on.
+#[derive(Debug, Clone)]
+pub struct BlockedIssue {
+    pub issue: IssueRef,
+    pub blockers: Vec<IssueRef>,
+}
+
+/// Open issues partitioned by whether they have open blockers.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyReport {
+    pub ready: Vec<IssueRef>,
+    pub blocked: Vec<BlockedIssue>,
+}
+
+/// Statuses that mark an issue as finished for dependency purposes.
+const CLOSED_STATUSES: &[&str] = &[
+    "closed",
+    "done",
+    "resolved",
+    "cancelled",
+    "canceled",
+    "wontfix",
+    "duplicate",
+];
+
+#[must_use]
+pub fn is_closed_status(status: &str) -> bool {
+    let normalized = status.trim().to_ascii_lowercase();
+    CLOSED_STATUSES.contains(&normalized.as_str())
+}
+
+/// A checklist item together with the entity it belongs to.
+#[derive(Debug, Clone)]
+pub struct EntityTask {
+    pub id: String,
+    pub title: String,
+    pub item: ChecklistItem,
+}
+
+/// An entity whose ID was lengthened by [`IxchelRepo::widen_ids`].
+#[derive(Debug, Clone)]
+pub struct IdRename {
+    pub old: String,
+    pub new: String,
+    pub path: PathBuf,
+}
+
 #[derive(Debug)]
 pub struct CheckReport {
     pub errors: Vec<CheckError>,
@@ -81,7 +178,9 @@ pub struct IxchelRepo {
     pub config: IxchelConfig,
 }
 
-const METADATA_KEYS: &[&str] = &[
+/// Frontmatter keys that describe the entity itself; every other key whose
+/// values look like entity IDs declares a relationship.
+pub const METADATA_KEYS: &[&str] = &[
     "id",
     "type",
     "title",
@@ -93,6 +192,9 @@ const METADATA_KEYS: &[&str] = &[
     "tags",
 ];
 
+/// How many IDs `create_entity` tries before giving up on collisions.
+const MAX_ID_ATTEMPTS: usize = 8;
+
 const KNOWN_ID_PREFIXES_HINT: &str = "dec, iss, bd, idea, rpt, src, cite, agt, ses";
 
 impl IxchelRepo {
@@ -163,12 +265,7 @@ impl IxchelRepo {
         let created_by = default_actor();
         let now = Utc::now();
 
-        let id = ix_id::id_random(kind.id_prefix());
-        let path = self.paths.kind_dir(kind).join(format!("{id}.md"));
-
-        if path.exists() {
-            anyhow::bail!("Entity already exists: {}", path.display());
-        }
+        let (id, path) = self.allocate_id(kind, title)?;
 
         let mut frontmatter = Mapping::new();
         frontmatter.insert(Value::String("id".to_string()), Value::String(id.clone()));
@@ -208,6 +305,7 @@ impl IxchelRepo {
         );
 
         let body = default_template(kind);
+        let tasks = TaskProgress::from_items(&parse_checklist(&body));
         let doc = MarkdownDocument { frontmatter, body };
         let markdown = render_markdown(&doc)?;
 
@@ -219,9 +317,224 @@ impl IxchelRepo {
             kind,
             title: title.to_string(),
             path,
+            tasks,
         })
     }
 
+    /// Pick a fresh ID for a new entity using the configured strategy, retrying
+    /// on collision with an existing file.
+    fn allocate_id(&self, kind: EntityKind, title: &str) -> Result<(String, PathBuf)> {
+        let name = kind.as_str();
+        let strategy = self.config.ids.strategy_for(name);
+        let bytes = self.config.ids.bytes_for(name);
+        check_id_bytes(bytes)?;
+
+        let key = slugify(title);
+        for attempt in 0..MAX_ID_ATTEMPTS {
+            // Content-keyed IDs are deterministic, so salt the key on retry.
+            let salted = if attempt == 0 {
+                key.clone()
+            } else {
+                format!("{key}:{attempt}")
+            };
+            let id = strategy.generate(kind.id_prefix(), &salted, bytes);
+            let path = self.paths.kind_dir(kind).join(format!("{id}.md"));
+            if !path.exists() {
+                return Ok((id, path));
+            }
+            tracing::debug!(%id, attempt, "id collision, retrying");
+        }
+
+        anyhow::bail!(
+            "Could not allocate a unique {name} id after {MAX_ID_ATTEMPTS} attempts; \
+             run `ixchel ids widen` or raise ids.bytes"
+        )
+    }
+
+    /// Lengthen every entity ID shorter than `bytes` and rewrite references to it.
+    ///
+    /// Renamed files keep their old ID as a prefix, so short references typed
+    /// before the migration still resolve. Frontmatter values and body mentions
+    /// are rewritten in place; `updated_at` is left alone.
+    pub fn widen_ids(&self, bytes: usize) -> Result<Vec<IdRename>> {
+        check_id_bytes(bytes)?;
+
+        let entities = self.list(None, ListSort::default())?;
+        let mut renames = BTreeMap::new();
+        for entity in &entities {
+            if !looks_like_entity_id(&entity.id) {
+                continue;
+            }
+            let wide = ix_id::widen_id(&entity.id, bytes)?;
+            if wide != entity.id {
+                renames.insert(entity.id.clone(), wide);
+            }
+        }
+        if renames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        for entity in &entities {
+            if let Some(new_id) = renames.get(&entity.id) {
+                let target = entity.path.with_file_name(format!("{new_id}.md"));
+                if target.exists() {
+                    anyhow::bail!(
+                        "Cannot widen {} to {new_id}: {} already exists",
+                        entity.id,
+                        target.display()
+                    );
+                }
+            }
+        }
+
+        // Render every rewrite before touching the tree, so a bad file aborts
+        // the migration with nothing changed
+        let mut rewrites = Vec::new();
+        for entity in entities {
+            let raw = std::fs::read_to_string(&entity.path)
+                .with_context(|| format!("Failed to read {}", entity.path.display()))?;
+            let mut doc = parse_markdown(&entity.path, &raw)?;
+
+            let mut changed = false;
+            for (_, value) in &mut doc.frontmatter {
+                changed |= rename_ids_in_value(value, &renames);
+            }
+            if let Some(body) = rename_ids_in_text(&doc.body, &renames) {
+                doc.body = body;
+                changed = true;
+            }
+
+            let new_id = renames.get(&entity.id);
+            if !changed && new_id.is_none() {
+                continue;
+            }
+
+            let path = new_id.map_or_else(
+                || entity.path.clone(),
+                |new_id| entity.path.with_file_name(format!("{new_id}.md")),
+            );
+            rewrites.push(IdRewrite {
+                rename: new_id.map(|new_id| IdRename {
+                    old: entity.id,
+                    new: new_id.clone(),
+                    path: path.clone(),
+                }),
+                markdown: render_markdown(&doc)?,
+                original: raw,
+                old_path: entity.path,
+                path,
+            });
+        }
+
+        apply_id_rewrites(&rewrites)?;
+
+        let mut out = rewrites
+            .into_iter()
+            .filter_map(|rewrite| rewrite.rename)
+            .collect::<Vec<_>>();
+        out.sort_by(|a, b| a.old.cmp(&b.old));
+        Ok(out)
+    }
+
+    /// The index database, `.ixchel/<storage.path>`.
+    #[must_use]
+    pub fn index_path(&self) -> PathBuf {
+        self.paths
+            .ixchel_dir()
+            .join(PathBuf::from(&self.config.storage.path))
+    }
+
+    /// The embedding config to open the index with.
+    ///
+    /// When the config leaves the dimension to the provider, the dimension
+    /// the index was synced with by the same provider and model is filled in,
+    /// so opening the index for e.g. `status` doesn't probe an embedding
+    /// server for it.
+    #[must_use]
+    pub fn index_embedding_config(&self) -> EmbeddingConfig {
+        let mut config = self.config.embedding.clone();
+        if config.dimension.is_none()
+            && let Ok(Some(stored)) = IndexMetadata::load(&self.index_path())
+            && stored.provider == config.provider.trim().to_ascii_lowercase()
+            && stored.model == config.model
+        {
+            config.dimension = Some(stored.dimension);
+        }
+        config
+    }
+
+    /// Resolve user input to a full entity ID, the way git resolves short hashes.
+    ///
+    /// Accepts a full ID, any unique ID prefix (`dec-a1`), a bare hash prefix
+    /// (`a1b2`), or a title slug (`use-postgres`). Ambiguous input is an error
+    /// that lists every candidate.
+    ///
+    /// The task and supersession methods resolve the IDs they are given with
+    /// it, so callers can pass user input straight through.
+    pub fn resolve_id(&self, input: &str) -> Result<String> {
+        let needle = input.trim().to_ascii_lowercase();
+        if needle.is_empty() {
+            anyhow::bail!("Entity id must not be empty");
+        }
+        if self
+            .paths
+            .entity_path(&needle)
+            .is_some_and(|path| path.exists())
+        {
+            return Ok(needle);
+        }
+
+        let entities = self.list(None, ListSort::default())?;
+        let mut matches = entities
+            .iter()
+            .filter(|entity| {
+                entity.id.starts_with(&needle)
+                    || entity
+                        .id
+                        .split_once('-')
+                        .is_some_and(|(_, hash)| hash.starts_with(&needle))
+            })
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            let slug = slugify(&needle);
+            if !slug.is_empty() {
+                let slugs = entities
+                    .iter()
+                    .map(|entity| (entity, slugify(&entity.title)))
+                    .collect::<Vec<_>>();
+                matches = slugs
+                    .iter()
+                    .filter(|(_, title)| *title == slug)
+                    .map(|(entity, _)| *entity)
+                    .collect();
+                if matches.is_empty() {
+                    matches = slugs
+                        .iter()
+                        .filter(|(_, title)| title.starts_with(&slug))
+                        .map(|(entity, _)| *entity)
+                        .collect();
+                }
+            }
+        }
+
+        match matches.as_slice() {
+            [] => anyhow::bail!("No entity matches {input}"),
+            [entity] => Ok(entity.id.clone()),
+            candidates => {
+                let listing = candidates
+                    .iter()
+                    .map(|entity| format!("  {}  {}", entity.id, entity.title))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                anyhow::bail!(
+                    "Ambiguous id {input}: {} candidates\n{listing}",
+                    candidates.len()
+                )
+            }
+        }
+    }
+
     pub fn read_raw(&self, id: &str) -> Result<String> {
         let path = self
             .paths
@@ -298,6 +611,7 @@ impl IxchelRepo {
                     kind,
                     title,
                     path,
+                    tasks: TaskProgress::from_items(&parse_checklist(&doc.body)),
                 };
 
                 let sort_ts = match sort {
@@ -321,6 +635,26 @@ impl IxchelRepo {
         Ok(out.into_iter().map(|entry| entry.summary).collect())
     }
 
+    /// Every relationship declared in entity frontmatter, ordered by source
+    /// entity. Targets that don't exist are included; callers skip them.
+    pub fn relationships(&self) -> Result<Vec<Relationship>> {
+        let mut out = Vec::new();
+        for summary in self.list(None, ListSort::default())? {
+            let raw = std::fs::read_to_string(&summary.path)
+                .with_context(|| format!("Failed to read {}", summary.path.display()))?;
+            let doc = parse_markdown(&summary.path, &raw)?;
+            for (rel, targets) in extract_relationships(&doc.frontmatter) {
+                out.extend(targets.into_iter().map(|to| Relationship {
+                    from: summary.id.clone(),
+                    rel: rel.clone(),
+                    to,
+                }));
+            }
+        }
+        out.sort_by(|a, b| a.from.cmp(&b.from));
+        Ok(out)
+    }
+
     pub fn collect_tags(&self, kind: Option<EntityKind>) -> Result<HashMap<String, Vec<String>>> {
         let mut out: HashMap<String, Vec<String>> = HashMap::new();
 
@@ -511,6 +845,229 @@ impl IxchelRepo {
         Ok(true)
     }
 
+    /// List checklist items across entities of `kind`.
+    ///
+    /// Only unchecked items are returned unless `include_done` is set.
+    pub fn list_tasks(&self, kind: EntityKind, include_done: bool) -> Result<Vec<EntityTask>> {
+        let mut out = Vec::new();
+
+        for item in self.list(Some(kind), ListSort::default())? {
+            let raw = std::fs::read_to_string(&item.path)
+                .with_context(|| format!("Failed to read {}", item.path.display()))?;
+            let doc = parse_markdown(&item.path, &raw)?;
+
+            for task in parse_checklist(&doc.body) {
+                if task.checked && !include_done {
+                    continue;
+                }
+                out.push(EntityTask {
+                    id: item.id.clone(),
+                    title: item.title.clone(),
+                    item: task,
+                });
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Checklist completion of an entity.
+    pub fn task_progress(&self, id: &str) -> Result<TaskProgress> {
+        let id = &self.resolve_id(id)?;
+        let path = self
+            .paths
+            .entity_path(id)
+            .with_context(|| format!("Unknown entity id prefix: {id}"))?;
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let doc = parse_markdown(&path, &raw)?;
+        Ok(TaskProgress::from_items(&parse_checklist(&doc.body)))
+    }
+
+    /// Check or uncheck the `index`-th (1-based) checklist item of an entity.
+    ///
+    /// Returns `false` when the item already had the requested state.
+    pub fn set_task(&self, id: &str, index: usize, checked: bool) -> Result<bool> {
+        let id = &self.resolve_id(id)?;
+        let path = self
+            .paths
+            .entity_path(id)
+            .with_context(|| format!("Unknown entity id prefix: {id}"))?;
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut doc = parse_markdown(&path, &raw)?;
+
+        let items = parse_checklist(&doc.body);
+        let Some(item) = items.iter().find(|item| item.index == index) else {
+            anyhow::bail!("Task {index} not found in {id} ({} tasks)", items.len());
+        };
+        if item.checked == checked {
+            return Ok(false);
+        }
+
+        doc.body = set_checklist_item(&doc.body, index, checked)
+            .with_context(|| format!("Failed to update task {index} in {id}"))?;
+        let now = Utc::now();
+        set_string(
+            &mut doc.frontmatter,
+            "updated_at",
+            now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+
+        let out = render_markdown(&doc)?;
+        std::fs::write(&path, out)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(true)
+    }
+
+    /// Mark `old_id` as superseded by `new_id`.
+    ///
+    /// Records `supersedes` on the new decision and `superseded_by` on the old one,
+    /// and flips the old decision's status to `superseded`.
+    pub fn supersede(&self, old_id: &str, new_id: &str) -> Result<()> {
+        let old_id = &self.resolve_id(old_id)?;
+        let new_id = &self.resolve_id(new_id)?;
+        for id in [old_id, new_id] {
+            if kind_from_id(id) != Some(EntityKind::Decision) {
+                anyhow::bail!("Only decisions can be superseded: {id} is not a decision");
+            }
+        }
+        if old_id == new_id {
+            anyhow::bail!("A decision cannot supersede itself: {old_id}");
+        }
+
+        let (old_path, mut old_doc) = self.load_document(old_id)?;
+        let (new_path, mut new_doc) = self.load_document(new_id)?;
+
+        if self
+            .supersession_chain(new_id)?
+            .iter()
+            .any(|id| id == old_id)
+        {
+            anyhow::bail!("{new_id} is already superseded by {old_id}; refusing to create a cycle");
+        }
+
+        let mut successors = get_string_list(&old_doc.frontmatter, "superseded_by");
+        if !successors.iter().any(|id| id == new_id) {
+            successors.push(new_id.clone());
+        }
+        set_string_list(&mut old_doc.frontmatter, "superseded_by", successors);
+        set_string(&mut old_doc.frontmatter, "status", "superseded");
+
+        let mut predecessors = get_string_list(&new_doc.frontmatter, "supersedes");
+        if !predecessors.iter().any(|id| id == old_id) {
+            predecessors.push(old_id.clone());
+        }
+        set_string_list(&mut new_doc.frontmatter, "supersedes", predecessors);
+
+        write_document(&old_path, &mut old_doc)?;
+        write_document(&new_path, &mut new_doc)?;
+        Ok(())
+    }
+
+    /// Follow supersession links from `id`, returning every decision visited
+    /// (starting with `id` itself). The last element is the current decision.
+    ///
+    /// A decision is superseded by those it lists in `superseded_by` and
+    /// those listing it in `supersedes`, as `check` sees them; with several
+    /// successors the first is followed.
+    pub fn supersession_chain(&self, id: &str) -> Result<Vec<String>> {
+        let id = self.resolve_id(id)?;
+        self.load_document(&id)?;
+        let nodes = load_decision_nodes(&self.paths)?;
+        let mut chain = vec![id.clone()];
+        let mut current = id;
+
+        loop {
+            let Some(next) = nodes
+                .get(&current)
+                .and_then(|node| node.successors.first())
+                .cloned()
+            else {
+                return Ok(chain);
+            };
+
+            if chain.contains(&next) {
+                anyhow::bail!(
+                    "Supersession cycle detected: {} -> {next}",
+                    chain.join(" -> ")
+                );
+            }
+            chain.push(next.clone());
+            current = next;
+        }
+    }
+
+    /// The decision at the head of `id`'s supersession chain.
+    pub fn current_decision(&self, id: &str) -> Result<String> {
+        let chain = self.supersession_chain(id)?;
+        Ok(chain.last().cloned().unwrap_or_else(|| id.to_string()))
+    }
+
+    /// The current decision superseding `id`, or `None` when `id` isn't a
+    /// superseded decision or its chain is broken by a cycle (which `check`
+    /// reports).
+    #[must_use]
+    pub fn superseding_decision(&self, id: &str) -> Option<String> {
+        let id = &self.resolve_id(id).ok()?;
+        if kind_from_id(id) != Some(EntityKind::Decision) {
+            return None;
+        }
+        let chain = self.supersession_chain(id).ok()?;
+        (chain.len() > 1).then(|| chain[chain.len() - 1].clone())
+    }
+
+    /// Compute which open issues are ready to work on and which are blocked.
+    ///
+    /// An issue waits on every issue it lists in `depends_on` and every issue
+    /// that lists it in `blocks`. Closed blockers and dangling links are ignored.
+    pub fn issue_dependencies(&self) -> Result<DependencyReport> {
+        let nodes = load_issue_nodes(&self.paths)?;
+        let mut report = DependencyReport::default();
+
+        for summary in self.list(Some(EntityKind::Issue), ListSort::default())? {
+            let Some(node) = nodes.get(&summary.id) else {
+                continue;
+            };
+            if !node.issue.is_open() {
+                continue;
+            }
+
+            let blockers = node
+                .waits_on
+                .iter()
+                .filter_map(|id| nodes.get(id))
+                .filter(|blocker| blocker.issue.is_open())
+                .map(|blocker| blocker.issue.clone())
+                .collect::<Vec<_>>();
+
+            if blockers.is_empty() {
+                report.ready.push(node.issue.clone());
+            } else {
+                report.blocked.push(BlockedIssue {
+                    issue: node.issue.clone(),
+                    blockers,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn load_document(&self, id: &str) -> Result<(PathBuf, MarkdownDocument)> {
+        let path = self
+            .paths
+            .entity_path(id)
+            .with_context(|| format!("Unknown entity id prefix: {id}"))?;
+        if !path.exists() {
+            anyhow::bail!("Entity does not exist: {id} ({})", path.display());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let doc = parse_markdown(&path, &raw)?;
+        Ok((path, doc))
+    }
+
     pub fn check(&self) -> Result<CheckReport> {
         let report = self.check_with_suggestions()?;
         Ok(CheckReport {
@@ -564,6 +1121,9 @@ impl IxchelRepo {
             }
         }
 
+        check_supersession(&self.paths, &mut errors)?;
+        check_dependency_cycles(&self.paths, &mut errors)?;
+
         Ok(CheckReportDetailed { errors })
     }
 }
@@ -991,6 +1551,255 @@ fn check_relationships(
     }
 }
 
+struct IssueNode {
+    issue: IssueRef,
+    waits_on: Vec<String>,
+}
+
+fn load_issue_nodes(paths: &IxchelPaths) -> Result<BTreeMap<String, IssueNode>> {
+    let mut nodes: BTreeMap<String, IssueNode> = BTreeMap::new();
+    let dir = paths.kind_dir(EntityKind::Issue);
+    if !dir.exists() {
+        return Ok(nodes);
+    }
+
+    let mut inverse: Vec<(String, String)> = Vec::new();
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("md") {
+            continue;
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        // Parse failures are reported by `check`; they simply don't participate here.
+        let Ok(doc) = parse_markdown(&path, &raw) else {
+            continue;
+        };
+        let Some(id) = get_string(&doc.frontmatter, "id") else {
+            continue;
+        };
+
+        for blocked in dependency_targets(&doc.frontmatter, "blocks") {
+            inverse.push((blocked, id.clone()));
+        }
+        nodes.insert(
+            id.clone(),
+            IssueNode {
+                issue: IssueRef {
+                    id,
+                    title: get_string(&doc.frontmatter, "title").unwrap_or_default(),
+                    status: get_string(&doc.frontmatter, "status"),
+                    path,
+                },
+                waits_on: dependency_targets(&doc.frontmatter, "depends_on"),
+            },
+        );
+    }
+
+    for (waiter, blocker) in inverse {
+        if let Some(node) = nodes.get_mut(&waiter)
+            && !node.waits_on.contains(&blocker)
+        {
+            node.waits_on.push(blocker);
+        }
+    }
+
+    Ok(nodes)
+}
+
+/// Read dependency ids from `key`, accepting both plain ids and the legacy
+/// `{ id: ..., type: blocks }` mapping form.
+fn dependency_targets(frontmatter: &Mapping, key: &str) -> Vec<String> {
+    let Some(value) = frontmatter.get(Value::String(key.to_string())) else {
+        return Vec::new();
+    };
+
+    let items = match value {
+        Value::Sequence(seq) => seq.iter().collect::<Vec<_>>(),
+        other => vec![other],
+    };
+
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            Value::String(s) => Some(s.trim().to_string()),
+            Value::Mapping(map) => get_string(map, "id").map(|s| s.trim().to_string()),
+            _ => None,
+        })
+        .filter(|id| looks_like_entity_id(id))
+        .collect()
+}
+
+fn check_dependency_cycles(paths: &IxchelPaths, errors: &mut Vec<CheckIssue>) -> Result<()> {
+    let nodes = load_issue_nodes(paths)?;
+    let edges = nodes
+        .iter()
+        .map(|(id, node)| (id.clone(), node.waits_on.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    for cycle in find_cycles(&edges) {
+        let Some(node) = nodes.get(&cycle[0]) else {
+            continue;
+        };
+        push_issue(
+            errors,
+            &node.issue.path,
+            format!("dependency cycle: {}", cycle.join(" -> ")),
+            Some(
+                "Remove one `depends_on`/`blocks` link so the issues can be completed in order."
+                    .to_string(),
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+struct DecisionNode {
+    path: PathBuf,
+    status: Option<String>,
+    successors: Vec<String>,
+}
+
+/// Every decision with its successors: those it lists in `superseded_by`,
+/// then those listing it in `supersedes`.
+fn load_decision_nodes(paths: &IxchelPaths) -> Result<BTreeMap<String, DecisionNode>> {
+    let mut nodes: BTreeMap<String, DecisionNode> = BTreeMap::new();
+    let dir = paths.kind_dir(EntityKind::Decision);
+    if !dir.exists() {
+        return Ok(nodes);
+    }
+
+    let mut inverse: Vec<(String, String)> = Vec::new();
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("md") {
+            continue;
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        // Parse failures are reported by `check`; they simply don't participate here.
+        let Ok(doc) = parse_markdown(&path, &raw) else {
+            continue;
+        };
+        let Some(id) = get_string(&doc.frontmatter, "id") else {
+            continue;
+        };
+
+        for old_id in get_string_list(&doc.frontmatter, "supersedes") {
+            inverse.push((old_id, id.clone()));
+        }
+        nodes.insert(
+            id,
+            DecisionNode {
+                path,
+                status: get_string(&doc.frontmatter, "status"),
+                successors: get_string_list(&doc.frontmatter, "superseded_by"),
+            },
+        );
+    }
+
+    inverse.sort();
+    for (old_id, new_id) in inverse {
+        if let Some(node) = nodes.get_mut(&old_id)
+            && !node.successors.contains(&new_id)
+        {
+            node.successors.push(new_id);
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn check_supersession(paths: &IxchelPaths, errors: &mut Vec<CheckIssue>) -> Result<()> {
+    let nodes = load_decision_nodes(paths)?;
+    for (id, node) in &nodes {
+        if node.successors.is_empty() {
+            continue;
+        }
+        if node
+            .status
+            .as_deref()
+            .is_some_and(|status| status.trim().eq_ignore_ascii_case("accepted"))
+        {
+            push_issue(
+                errors,
+                &node.path,
+                format!(
+                    "accepted decision {id} is superseded by {}",
+                    node.successors.join(", ")
+                ),
+                Some(format!(
+                    "Set `status: superseded` or run `ixchel supersede {id} {}`.",
+                    node.successors[0]
+                )),
+            );
+        }
+    }
+
+    let edges = nodes
+        .iter()
+        .map(|(id, node)| (id.clone(), node.successors.clone()))
+        .collect::<BTreeMap<_, _>>();
+    for cycle in find_cycles(&edges) {
+        let Some(node) = nodes.get(&cycle[0]) else {
+            continue;
+        };
+        push_issue(
+            errors,
+            &node.path,
+            format!("supersession cycle: {}", cycle.join(" -> ")),
+            Some(
+                "Remove one `supersedes`/`superseded_by` link so the chain has a single current decision."
+                    .to_string(),
+            ),
+        );
+    }
+
+    Ok(())
+}
+
+/// Find cycles in a directed graph given as an adjacency map.
+///
+/// Each cycle is returned once, as a path that starts and ends on the same id.
+fn find_cycles(edges: &BTreeMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    fn visit(
+        edges: &BTreeMap<String, Vec<String>>,
+        id: &str,
+        stack: &mut Vec<String>,
+        done: &mut BTreeSet<String>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        if let Some(pos) = stack.iter().position(|item| item == id) {
+            let mut cycle = stack[pos..].to_vec();
+            cycle.push(id.to_string());
+            cycles.push(cycle);
+            return;
+        }
+        if done.contains(id) {
+            return;
+        }
+
+        stack.push(id.to_string());
+        for next in edges.get(id).into_iter().flatten() {
+            visit(edges, next, stack, done, cycles);
+        }
+        stack.pop();
+        done.insert(id.to_string());
+    }
+
+    let mut cycles = Vec::new();
+    let mut done = BTreeSet::new();
+    for id in edges.keys() {
+        visit(edges, id, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    cycles
+}
+
 fn push_issue(
     errors: &mut Vec<CheckIssue>,
     path: &Path,
@@ -1004,6 +1813,18 @@ fn push_issue(
     });
 }
 
+fn write_document(path: &Path, doc: &mut MarkdownDocument) -> Result<()> {
+    let now = Utc::now();
+    set_string(
+        &mut doc.frontmatter,
+        "updated_at",
+        now.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+
+    let out = render_markdown(doc)?;
+    std::fs::write(path, out).with_context(|| format!("Failed to write {}", path.display()))
+}
+
 fn parse_timestamp(frontmatter: &Mapping, key: &str) -> Option<DateTime<Utc>> {
     let raw = get_string(frontmatter, key)?;
     let parsed = DateTime::parse_from_rfc3339(&raw).ok()?;
@@ -1081,11 +1902,60 @@ fn ensure_project_gitignore(repo_root: &Path) -> Result<()> {
     Ok(())
 }
 
-fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<String>)> {
+/// Markdown files of every entity kind, sorted by path.
+pub fn iter_entity_paths(repo: &IxchelRepo) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+
+    for kind in [
+        EntityKind::Decision,
+        EntityKind::Issue,
+        EntityKind::Idea,
+        EntityKind::Report,
+        EntityKind::Source,
+        EntityKind::Citation,
+        EntityKind::Agent,
+        EntityKind::Session,
+    ] {
+        let dir = repo.paths.kind_dir(kind);
+        if !dir.exists() {
+            continue;
+        }
+
+        for entry in
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("md") {
+                continue;
+            }
+            out.push(path);
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
+/// `file_path` relative to `repo_root`, with `/` separators.
+#[must_use]
+pub fn normalize_path(repo_root: &Path, file_path: &Path) -> String {
+    file_path
+        .strip_prefix(repo_root)
+        .unwrap_or(file_path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Relationships declared in `frontmatter`, as `(rel, targets)` in key
+/// order. Keys are trimmed and only the first of duplicate keys counts.
+#[must_use]
+pub fn extract_relationships(frontmatter: &Mapping) -> Vec<(String, Vec<String>)> {
     let mut rels = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
 
     for (key, value) in frontmatter {
-        let serde_yaml::Value::String(key) = key else {
+        let Value::String(key) = key else {
             continue;
         };
 
@@ -1094,14 +1964,14 @@ fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<
         }
 
         let targets = match value {
-            serde_yaml::Value::Sequence(seq) => seq
+            Value::Sequence(seq) => seq
                 .iter()
                 .filter_map(|v| match v {
-                    serde_yaml::Value::String(s) => Some(s.clone()),
+                    Value::String(s) => Some(s.clone()),
                     _ => None,
                 })
                 .collect::<Vec<_>>(),
-            serde_yaml::Value::String(s) => vec![s.clone()],
+            Value::String(s) => vec![s.clone()],
             _ => Vec::new(),
         };
 
@@ -1114,12 +1984,163 @@ fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<
             continue;
         }
 
-        rels.push((key.clone(), targets));
+        let key = key.trim().to_string();
+        if key.is_empty() || !seen.insert(key.clone()) {
+            continue;
+        }
+
+        rels.push((key, targets));
     }
 
     rels
 }
 
+/// One file rewritten by [`IxchelRepo::widen_ids`].
+struct IdRewrite {
+    old_path: PathBuf,
+    path: PathBuf,
+    original: String,
+    markdown: String,
+    rename: Option<IdRename>,
+}
+
+impl IdRewrite {
+    fn staged_path(&self) -> PathBuf {
+        self.path.with_extension("md.widen")
+    }
+
+    /// Move the staged file into place, removing the old file on rename.
+    fn commit(&self) -> std::io::Result<()> {
+        std::fs::rename(self.staged_path(), &self.path)?;
+        if self.old_path != self.path {
+            std::fs::remove_file(&self.old_path)?;
+        }
+        Ok(())
+    }
+
+    /// Undo a (possibly partial) [`IdRewrite::commit`].
+    fn restore(&self) -> std::io::Result<()> {
+        std::fs::write(&self.old_path, &self.original)?;
+        if self.old_path != self.path && self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write every rewrite to a staging file, then move them all into place. On
+/// failure the files already moved are restored and staging files removed.
+fn apply_id_rewrites(rewrites: &[IdRewrite]) -> Result<()> {
+    let discard_staged = |rewrites: &[IdRewrite]| {
+        for rewrite in rewrites {
+            let _ = std::fs::remove_file(rewrite.staged_path());
+        }
+    };
+
+    for (idx, rewrite) in rewrites.iter().enumerate() {
+        let staged = rewrite.staged_path();
+        if let Err(err) = std::fs::write(&staged, &rewrite.markdown) {
+            discard_staged(&rewrites[..=idx]);
+            return Err(err).with_context(|| format!("Failed to write {}", staged.display()));
+        }
+    }
+
+    for (idx, rewrite) in rewrites.iter().enumerate() {
+        if let Err(err) = rewrite.commit() {
+            let mut restore_failures = Vec::new();
+            for done in &rewrites[..=idx] {
+                if let Err(restore_err) = done.restore() {
+                    restore_failures.push(format!("{}: {restore_err}", done.old_path.display()));
+                }
+            }
+            discard_staged(&rewrites[idx..]);
+
+            let mut err = anyhow::Error::new(err).context(format!(
+                "Failed to move {} into place; rolled back",
+                rewrite.path.display()
+            ));
+            if !restore_failures.is_empty() {
+                err = err.context(format!(
+                    "Rollback could not restore: {}",
+                    restore_failures.join(", ")
+                ));
+            }
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+fn check_id_bytes(bytes: usize) -> Result<()> {
+    if !(ix_id::MIN_ID_BYTES..=ix_id::MAX_ID_BYTES).contains(&bytes) {
+        anyhow::bail!(
+            "ID length must be between {} and {} bytes, got {bytes}",
+            ix_id::MIN_ID_BYTES,
+            ix_id::MAX_ID_BYTES
+        );
+    }
+    Ok(())
+}
+
+/// Replace every string in `value` that is exactly an old ID. Returns whether
+/// anything changed.
+fn rename_ids_in_value(value: &mut Value, renames: &BTreeMap<String, String>) -> bool {
+    match value {
+        Value::String(text) => renames.get(text.as_str()).is_some_and(|new_id| {
+            text.clone_from(new_id);
+            true
+        }),
+        Value::Sequence(items) => items.iter_mut().fold(false, |changed, item| {
+            rename_ids_in_value(item, renames) | changed
+        }),
+        Value::Mapping(map) => map.iter_mut().fold(false, |changed, (_, item)| {
+            rename_ids_in_value(item, renames) | changed
+        }),
+        _ => false,
+    }
+}
+
+/// Replace whole-word mentions of old IDs in `text`, or `None` if there are none.
+fn rename_ids_in_text(text: &str, renames: &BTreeMap<String, String>) -> Option<String> {
+    let is_word = |ch: char| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_';
+
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+    while let Some(start) = rest.find(is_word) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|ch: char| !is_word(ch)).unwrap_or(rest.len());
+        let word = &rest[..end];
+        match renames.get(word) {
+            Some(new_id) => {
+                out.push_str(new_id);
+                changed = true;
+            }
+            None => out.push_str(word),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+
+    changed.then_some(out)
+}
+
+/// Lowercase `text` and collapse every run of non-alphanumerics into one `-`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let trimmed = slug.trim_end_matches('-').len();
+    slug.truncate(trimmed);
+    slug
+}
+
 fn normalize_tag(tag: &str) -> Option<String> {
     let trimmed = tag.trim();
     if trimmed.is_empty() {
@@ -0,0 +1,264 @@
+//! Entity-level vector similarity: "more like this", duplicate detection
+//! and link suggestions over the pooled vectors backends store per entity.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::entity::EntityKind;
+use crate::repo::Relationship;
+
+/// Cosine similarity above which two entities are reported as likely
+/// duplicates.
+pub const DUPLICATE_THRESHOLD: f32 = 0.92;
+
+/// Cosine similarity of two vectors; 0 when either is zero or their
+/// dimensions differ.
+#[must_use]
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// An indexed entity with its pooled embedding.
+#[derive(Debug, Clone)]
+pub struct EntityVector {
+    pub id: String,
+    pub kind: Option<EntityKind>,
+    pub title: String,
+    pub vector: Vec<f32>,
+}
+
+/// One side of a [`DuplicatePair`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityRef {
+    pub id: String,
+    pub kind: Option<EntityKind>,
+    pub title: String,
+}
+
+impl From<&EntityVector> for EntityRef {
+    fn from(entity: &EntityVector) -> Self {
+        Self {
+            id: entity.id.clone(),
+            kind: entity.kind,
+            title: entity.title.clone(),
+        }
+    }
+}
+
+/// Two entities whose vectors are nearly identical.
+#[derive(Debug, Clone)]
+pub struct DuplicatePair {
+    pub first: EntityRef,
+    pub second: EntityRef,
+    pub similarity: f32,
+}
+
+/// Pairs of entities at or above `threshold` cosine similarity, most similar
+/// first.
+///
+/// Unless `cross_kind` is set only entities of the same kind are paired,
+/// since e.g. an issue often shares the title of the decision it implements.
+#[must_use]
+pub fn find_duplicates(
+    entities: &[EntityVector],
+    threshold: f32,
+    cross_kind: bool,
+) -> Vec<DuplicatePair> {
+    let mut pairs = Vec::new();
+    for (i, first) in entities.iter().enumerate() {
+        for second in &entities[i + 1..] {
+            if !cross_kind && first.kind != second.kind {
+                continue;
+            }
+            let similarity = cosine_similarity(&first.vector, &second.vector);
+            if similarity < threshold {
+                continue;
+            }
+            let (first, second) = if first.id <= second.id {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            pairs.push(DuplicatePair {
+                first: first.into(),
+                second: second.into(),
+                similarity,
+            });
+        }
+    }
+    pairs.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.first.id.cmp(&b.first.id))
+            .then_with(|| a.second.id.cmp(&b.second.id))
+    });
+    pairs
+}
+
+/// [`title_similarity`] above which a new entity's title is reported as a
+/// likely duplicate of an existing one.
+pub const SIMILAR_TITLE_THRESHOLD: f32 = 0.85;
+
+/// Dice coefficient of the character bigrams of two titles.
+///
+/// Titles are compared case-insensitively with punctuation and runs of
+/// whitespace collapsed to single spaces: 1 for titles differing only in
+/// those, high for small edits such as "fails" and "failing".
+#[must_use]
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    fn normalize(title: &str) -> Vec<char> {
+        let lowered = title
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>();
+        lowered
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .collect()
+    }
+
+    let (a, b) = (normalize(a), normalize(b));
+    if a == b {
+        return if a.is_empty() { 0.0 } else { 1.0 };
+    }
+    let mut bigrams = HashMap::new();
+    for pair in a.windows(2) {
+        *bigrams.entry((pair[0], pair[1])).or_insert(0_usize) += 1;
+    }
+    let mut shared = 0_usize;
+    for pair in b.windows(2) {
+        if let Some(count) = bigrams.get_mut(&(pair[0], pair[1]))
+            && *count > 0
+        {
+            *count -= 1;
+            shared += 1;
+        }
+    }
+    let total = a.len().saturating_sub(1) + b.len().saturating_sub(1);
+    if total == 0 {
+        return 0.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let similarity = (2 * shared) as f32 / total as f32;
+    similarity
+}
+
+/// Cosine similarity a pair needs before a link between them is suggested.
+pub const LINK_SUGGESTION_THRESHOLD: f32 = 0.5;
+
+/// Relationships suggested between kinds, as `(from, rel, to)`.
+///
+/// Each unordered kind pair appears once, so a pair is always proposed with
+/// the same relationship whichever side is asked about. Same-kind pairs are
+/// left to duplicate detection.
+pub const LINK_RULES: &[(EntityKind, &str, EntityKind)] = &[
+    (EntityKind::Issue, "implements", EntityKind::Decision),
+    (EntityKind::Decision, "cites", EntityKind::Source),
+    (EntityKind::Report, "cites", EntityKind::Source),
+    (EntityKind::Report, "summarizes", EntityKind::Issue),
+    (EntityKind::Report, "summarizes", EntityKind::Decision),
+    (EntityKind::Idea, "evolves_into", EntityKind::Decision),
+    (EntityKind::Idea, "evolves_into", EntityKind::Issue),
+    (EntityKind::Idea, "inspired_by", EntityKind::Source),
+    (EntityKind::Citation, "from_source", EntityKind::Source),
+    (EntityKind::Citation, "supports", EntityKind::Decision),
+    (EntityKind::Citation, "used_in", EntityKind::Report),
+];
+
+/// The [`LINK_RULES`] relationship between kinds `a` and `b`, and whether
+/// `a` is the side it's declared on.
+#[must_use]
+pub fn link_rule(a: EntityKind, b: EntityKind) -> Option<(&'static str, bool)> {
+    LINK_RULES.iter().find_map(|&(from, rel, to)| {
+        if (from, to) == (a, b) {
+            Some((rel, true))
+        } else if (from, to) == (b, a) {
+            Some((rel, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// A relationship proposed between two similar entities.
+#[derive(Debug, Clone)]
+pub struct LinkSuggestion {
+    pub from: String,
+    pub rel: String,
+    pub to: String,
+    /// The entity on the other end from the one suggestions were asked for.
+    pub other: EntityRef,
+    pub similarity: f32,
+}
+
+/// Links between `target` and the entities at or above `threshold` cosine
+/// similarity whose kinds have a [`LINK_RULES`] entry, most similar first.
+///
+/// Pairs already linked in either direction, by any relationship, are
+/// skipped.
+#[must_use]
+pub fn suggest_links(
+    target: &EntityVector,
+    entities: &[EntityVector],
+    existing: &[Relationship],
+    threshold: f32,
+) -> Vec<LinkSuggestion> {
+    let Some(kind) = target.kind else {
+        return Vec::new();
+    };
+    let linked = |other: &str| {
+        existing.iter().any(|link| {
+            (link.from == target.id && link.to == other)
+                || (link.from == other && link.to == target.id)
+        })
+    };
+
+    let mut suggestions = entities
+        .iter()
+        .filter(|entity| entity.id != target.id && !linked(&entity.id))
+        .filter_map(|entity| {
+            let (rel, outgoing) = link_rule(kind, entity.kind?)?;
+            let similarity = cosine_similarity(&target.vector, &entity.vector);
+            if similarity < threshold {
+                return None;
+            }
+            let (from, to) = if outgoing {
+                (&target.id, &entity.id)
+            } else {
+                (&entity.id, &target.id)
+            };
+            Some(LinkSuggestion {
+                from: from.clone(),
+                rel: rel.to_string(),
+                to: to.clone(),
+                other: entity.into(),
+                similarity,
+            })
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.other.id.cmp(&b.other.id))
+    });
+    suggestions
+}
@@ -0,0 +1,165 @@
+//! Query-focused excerpts of search passages with the matched terms marked.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lexical::{IDENT_JOINERS, tokenize};
+
+/// Characters of passage text shown in a search snippet.
+pub const SNIPPET_CHARS: usize = 200;
+
+const ELLIPSIS: &str = "…";
+
+/// A one-line excerpt with the byte ranges that matched the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Excerpt with line breaks flattened and `…` where it was cut.
+    pub text: String,
+    /// Byte ranges of `text` matching a query term, in order.
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl Snippet {
+    /// `text` with every highlight wrapped in `open` and `close`.
+    #[must_use]
+    pub fn render(&self, open: &str, close: &str) -> String {
+        let mut out = String::with_capacity(self.text.len());
+        let mut last = 0;
+        for range in &self.highlights {
+            out.push_str(&self.text[last..range.start]);
+            out.push_str(open);
+            out.push_str(&self.text[range.clone()]);
+            out.push_str(close);
+            last = range.end;
+        }
+        out.push_str(&self.text[last..]);
+        out
+    }
+}
+
+/// The `max_chars` window of `text` covering the most distinct query terms,
+/// or its start when nothing matches. `None` for blank text.
+#[must_use]
+pub fn snippet(text: &str, query: &str, max_chars: usize) -> Option<Snippet> {
+    let words = words(text);
+    let first = words.first()?;
+
+    let terms = tokenize(query).into_iter().collect::<HashSet<_>>();
+    let matches = words
+        .iter()
+        .filter(|word| {
+            tokenize(&text[(*word).clone()])
+                .iter()
+                .any(|term| terms.contains(term))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Leave some context before the first match of each candidate window
+    let lead = max_chars / 4;
+    let mut start = first.start;
+    let mut best = (0, 0);
+    for candidate in &matches {
+        let back = text[..candidate.start]
+            .char_indices()
+            .rev()
+            .nth(lead.saturating_sub(1))
+            .map_or(0, |(i, _)| i);
+        let window_start = words
+            .iter()
+            .find(|word| word.start >= back)
+            .map_or(candidate.start, |word| word.start);
+        let window_end = advance(text, window_start, max_chars);
+
+        let inside = matches
+            .iter()
+            .filter(|m| m.start >= window_start && m.end <= window_end);
+        let distinct = inside
+            .clone()
+            .map(|m| text[m.clone()].to_lowercase())
+            .collect::<HashSet<_>>()
+            .len();
+        let score = (distinct, inside.count());
+        if score > best {
+            best = score;
+            start = window_start;
+        }
+    }
+
+    // Keep punctuation around the text when the window reaches its ends
+    let (text_start, text_end) = (text.len() - text.trim_start().len(), text.trim_end().len());
+    if start <= first.start {
+        start = text_start;
+    }
+    let limit = advance(text, start, max_chars);
+    let end = if limit < text_end {
+        words
+            .iter()
+            .filter(|word| word.start >= start && word.end <= limit)
+            .map(|word| word.end)
+            .next_back()
+            .unwrap_or(limit)
+    } else {
+        text_end
+    };
+
+    let prefix = if start > text_start { ELLIPSIS } else { "" };
+    let mut excerpt = String::with_capacity(end - start + 2 * ELLIPSIS.len());
+    excerpt.push_str(prefix);
+    excerpt.extend(text[start..end].chars().map(|c| match c {
+        '\n' | '\r' | '\t' => ' ',
+        c => c,
+    }));
+    if end < text_end {
+        excerpt.push_str(ELLIPSIS);
+    }
+
+    let shift = prefix.len();
+    let highlights = matches
+        .into_iter()
+        .filter(|m| m.start >= start && m.end <= end)
+        .map(|m| m.start - start + shift..m.end - start + shift)
+        .collect();
+
+    Some(Snippet {
+        text: excerpt,
+        highlights,
+    })
+}
+
+/// Byte ranges of the words in `text`, split like [`tokenize`].
+fn words(text: &str) -> Vec<Range<usize>> {
+    let is_word = |c: char| c.is_alphanumeric() || IDENT_JOINERS.contains(&c);
+    let mut out = Vec::new();
+    let mut current: Option<usize> = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (current, is_word(c)) {
+            (None, true) => current = Some(i),
+            (Some(begin), false) => {
+                let word = &text[begin..i];
+                let trimmed = word.trim_start_matches(IDENT_JOINERS);
+                let begin = begin + word.len() - trimmed.len();
+                let end = begin + trimmed.trim_end_matches(IDENT_JOINERS).len();
+                if begin < end {
+                    out.push(begin..end);
+                }
+                current = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Byte offset `chars` characters after `start`, capped at the end of `text`.
+fn advance(text: &str, start: usize, chars: usize) -> usize {
+    text[start..]
+        .char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(i, _)| start + i)
+}
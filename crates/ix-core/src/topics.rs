@@ -0,0 +1,286 @@
+//! Tag suggestions and topic clusters over the pooled entity vectors.
+//!
+//! Suggestions come from the tags of an entity's nearest tagged neighbors;
+//! topics are spherical k-means clusters labelled by their members' tags.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::lexical::tokenize;
+use crate::similarity::{EntityRef, EntityVector, cosine_similarity};
+
+/// Nearest tagged entities whose tags are considered.
+pub const SUGGEST_NEIGHBORS: usize = 10;
+
+/// Share of the neighbors' similarity a tag needs to be suggested.
+pub const MIN_TAG_SUPPORT: f32 = 0.3;
+
+/// k-means passes before giving up on convergence.
+const MAX_KMEANS_ITERATIONS: usize = 50;
+
+/// Tags or title terms used in a topic label.
+const LABEL_TERMS: usize = 3;
+
+/// Title words too common to label a topic.
+const STOPWORDS: &[&str] = &[
+    "about", "add", "and", "are", "for", "from", "how", "into", "not", "the", "this", "use",
+    "when", "with",
+];
+
+/// A tag proposed for an entity.
+#[derive(Debug, Clone)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// Similarity-weighted share of the nearest tagged neighbors carrying
+    /// the tag, in `[0, 1]`.
+    pub support: f32,
+    /// Neighbors carrying the tag, most similar first.
+    pub sources: Vec<String>,
+}
+
+/// Tags for `target` from its [`SUGGEST_NEIGHBORS`] most similar tagged
+/// entities, best supported first.
+///
+/// `entity_tags` maps entity IDs to their tags; tags `target` already has
+/// are never suggested.
+#[must_use]
+pub fn suggest_tags(
+    target: &EntityVector,
+    entities: &[EntityVector],
+    entity_tags: &HashMap<String, Vec<String>>,
+) -> Vec<TagSuggestion> {
+    let mut neighbors = entities
+        .iter()
+        .filter(|entity| entity.id != target.id)
+        .filter_map(|entity| {
+            let tags = entity_tags
+                .get(&entity.id)
+                .filter(|tags| !tags.is_empty())?;
+            let similarity = cosine_similarity(&target.vector, &entity.vector);
+            (similarity > 0.0).then_some((entity.id.as_str(), tags, similarity))
+        })
+        .collect::<Vec<_>>();
+    neighbors.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+    neighbors.truncate(SUGGEST_NEIGHBORS);
+
+    let total = neighbors
+        .iter()
+        .map(|(_, _, similarity)| similarity)
+        .sum::<f32>();
+    let existing = entity_tags.get(&target.id).cloned().unwrap_or_default();
+    let mut by_tag: HashMap<&str, TagSuggestion> = HashMap::new();
+    for (id, tags, similarity) in &neighbors {
+        for tag in tags.iter().filter(|tag| !existing.contains(tag)) {
+            let suggestion = by_tag.entry(tag).or_insert_with(|| TagSuggestion {
+                tag: tag.clone(),
+                support: 0.0,
+                sources: Vec::new(),
+            });
+            suggestion.support += similarity / total;
+            suggestion.sources.push((*id).to_string());
+        }
+    }
+
+    let mut suggestions = by_tag
+        .into_values()
+        .filter(|suggestion| suggestion.support >= MIN_TAG_SUPPORT)
+        .collect::<Vec<_>>();
+    suggestions.sort_by(|a, b| {
+        b.support
+            .partial_cmp(&a.support)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+    suggestions
+}
+
+/// A cluster of entities about the same thing.
+#[derive(Debug, Clone)]
+pub struct Topic {
+    /// The most common tags of the members, or their most common title terms
+    /// when they're untagged.
+    pub label: String,
+    /// Tags of the members and how many carry each, most common first.
+    pub tags: Vec<(String, usize)>,
+    /// Members, closest to the cluster centre first.
+    pub members: Vec<EntityRef>,
+}
+
+/// A topic count that grows slowly with the number of entities.
+#[must_use]
+pub fn default_topic_count(entities: usize) -> usize {
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    let count = (entities as f32 / 2.0).sqrt().round() as usize;
+    count.clamp(1, entities.max(1))
+}
+
+/// Cluster `entities` into at most `count` topics with spherical k-means,
+/// largest first.
+///
+/// Centres are seeded farthest-first from the lowest entity ID, so the same
+/// vectors always give the same topics.
+#[must_use]
+pub fn cluster_topics(
+    entities: &[EntityVector],
+    count: usize,
+    entity_tags: &HashMap<String, Vec<String>>,
+) -> Vec<Topic> {
+    if entities.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let mut order = (0..entities.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| entities[a].id.cmp(&entities[b].id));
+    let entities = order.iter().map(|&i| &entities[i]).collect::<Vec<_>>();
+
+    let mut centroids = seed_centroids(&entities, count);
+    let mut assignments = vec![usize::MAX; entities.len()];
+    for _ in 0..MAX_KMEANS_ITERATIONS {
+        let next = entities
+            .iter()
+            .map(|entity| nearest_centroid(&entity.vector, &centroids).0)
+            .collect::<Vec<_>>();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let members = entities
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assigned)| **assigned == cluster)
+                .map(|(entity, _)| entity.vector.as_slice())
+                .collect::<Vec<_>>();
+            if !members.is_empty() {
+                *centroid = mean_direction(&members);
+            }
+        }
+    }
+
+    let mut topics = centroids
+        .iter()
+        .enumerate()
+        .filter_map(|(cluster, centroid)| {
+            let mut members = entities
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assigned)| **assigned == cluster)
+                .map(|(entity, _)| (*entity, cosine_similarity(&entity.vector, centroid)))
+                .collect::<Vec<_>>();
+            if members.is_empty() {
+                return None;
+            }
+            members.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+            let members = members
+                .into_iter()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            Some(label_topic(&members, entity_tags))
+        })
+        .collect::<Vec<_>>();
+    topics.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    topics
+}
+
+fn seed_centroids(entities: &[&EntityVector], count: usize) -> Vec<Vec<f32>> {
+    let mut centroids = vec![entities[0].vector.clone()];
+    while centroids.len() < count.min(entities.len()) {
+        let farthest = entities
+            .iter()
+            .map(|entity| nearest_centroid(&entity.vector, &centroids).1)
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+        match farthest {
+            // Every remaining entity duplicates a centre
+            Some((_, similarity)) if similarity >= 1.0 - f32::EPSILON => break,
+            Some((index, _)) => centroids.push(entities[index].vector.clone()),
+            None => break,
+        }
+    }
+    centroids
+}
+
+/// Index of the centroid most similar to `vector`, and that similarity.
+fn nearest_centroid(vector: &[f32], centroids: &[Vec<f32>]) -> (usize, f32) {
+    centroids
+        .iter()
+        .map(|centroid| cosine_similarity(vector, centroid))
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (index, similarity)| {
+            if similarity > best.1 {
+                (index, similarity)
+            } else {
+                best
+            }
+        })
+}
+
+/// Mean of the unit-length `vectors`.
+fn mean_direction(vectors: &[&[f32]]) -> Vec<f32> {
+    let mut sum = vec![0.0; vectors[0].len()];
+    for vector in vectors {
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 || vector.len() != sum.len() {
+            continue;
+        }
+        for (total, value) in sum.iter_mut().zip(*vector) {
+            *total += value / norm;
+        }
+    }
+    sum
+}
+
+fn label_topic(members: &[&EntityVector], entity_tags: &HashMap<String, Vec<String>>) -> Topic {
+    let tags = most_common(
+        members
+            .iter()
+            .filter_map(|entity| entity_tags.get(&entity.id))
+            .flatten()
+            .cloned(),
+    );
+    let label_terms = if tags.is_empty() {
+        most_common(members.iter().flat_map(|entity| {
+            let mut terms = tokenize(&entity.title);
+            terms.retain(|term| term.chars().count() >= 3 && !STOPWORDS.contains(&term.as_str()));
+            terms.sort();
+            terms.dedup();
+            terms
+        }))
+    } else {
+        tags.clone()
+    };
+    let label = label_terms
+        .iter()
+        .take(LABEL_TERMS)
+        .map(|(term, _)| term.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Topic {
+        label,
+        tags,
+        members: members
+            .iter()
+            .map(|entity| EntityRef::from(*entity))
+            .collect(),
+    }
+}
+
+/// Distinct `values` with their counts, most common first.
+fn most_common(values: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
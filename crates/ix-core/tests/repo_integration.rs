@@ -283,3 +283,1206 @@ fn list_sorts_by_timestamps_and_puts_missing_last() {
         ]
     );
 }
+
+#[test]
+fn parse_sections_and_checklist_skip_code_fences() {
+    let body = "intro\n\n## Problem\n\nBroken.\n\n## Plan\n\n- [ ] first\n- [x] second\n\n```\n- [ ] not a task\n# not a heading\n```\n";
+
+    let sections = ix_core::markdown::parse_sections(body);
+    let headings = sections
+        .iter()
+        .map(|s| (s.level, s.heading.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(headings, vec![(0, ""), (2, "Problem"), (2, "Plan")]);
+    assert_eq!(sections[1].text.trim(), "Broken.");
+    assert_eq!(sections[2].start_line, 7);
+
+    let items = ix_core::markdown::parse_checklist(body);
+    assert_eq!(items.len(), 2, "{items:#?}");
+    assert_eq!(items[0].text, "first");
+    assert!(!items[0].checked);
+    assert_eq!(items[1].index, 2);
+    assert!(items[1].checked);
+    assert_eq!(items[1].section.as_deref(), Some("Plan"));
+}
+
+#[test]
+fn set_task_updates_checklist_and_progress() {
+    let (_temp, repo) = init_temp_git_repo();
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Issue A", Some("open"))
+        .expect("create issue");
+
+    let path = repo.paths.entity_path(&issue.id).expect("issue path");
+    let raw = std::fs::read_to_string(&path).expect("read issue");
+    let mut doc = parse_markdown(&path, &raw).expect("parse markdown");
+    doc.body = "## Plan\n\n- [ ] write code\n- [ ] write tests\n".to_string();
+    std::fs::write(&path, render_markdown(&doc).expect("render")).expect("write issue");
+
+    let progress = repo.task_progress(&issue.id).expect("progress");
+    assert_eq!((progress.done, progress.total), (0, 2));
+    assert_eq!(progress.percent(), Some(0));
+
+    assert!(repo.set_task(&issue.id, 2, true).expect("complete task"));
+    assert!(
+        !repo
+            .set_task(&issue.id, 2, true)
+            .expect("complete task again")
+    );
+    assert!(repo.set_task(&issue.id, 3, true).is_err());
+
+    let progress = repo.task_progress(&issue.id).expect("progress");
+    assert_eq!(progress.percent(), Some(50));
+    let listed = repo
+        .list(Some(EntityKind::Issue), ListSort::default())
+        .expect("list issues");
+    assert_eq!(listed[0].tasks, progress);
+
+    let open = repo
+        .list_tasks(EntityKind::Issue, false)
+        .expect("list open tasks");
+    assert_eq!(open.len(), 1, "{open:#?}");
+    assert_eq!(open[0].item.text, "write code");
+
+    let all = repo
+        .list_tasks(EntityKind::Issue, true)
+        .expect("list all tasks");
+    assert_eq!(all.len(), 2);
+}
+
+#[test]
+fn supersede_links_both_decisions_and_follows_chain() {
+    let (_temp, repo) = init_temp_git_repo();
+    let first = repo
+        .create_entity(EntityKind::Decision, "Use SQLite", Some("accepted"))
+        .expect("create first decision");
+    let second = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", Some("accepted"))
+        .expect("create second decision");
+    let third = repo
+        .create_entity(EntityKind::Decision, "Use Postgres 16", Some("accepted"))
+        .expect("create third decision");
+
+    repo.supersede(&first.id, &second.id)
+        .expect("supersede first");
+    repo.supersede(&second.id, &third.id)
+        .expect("supersede second");
+
+    let path = repo.paths.entity_path(&first.id).expect("first path");
+    let raw = std::fs::read_to_string(&path).expect("read first");
+    let doc = parse_markdown(&path, &raw).expect("parse markdown");
+    assert_eq!(
+        ix_core::markdown::get_string(&doc.frontmatter, "status").as_deref(),
+        Some("superseded")
+    );
+    assert_eq!(
+        ix_core::markdown::get_string_list(&doc.frontmatter, "superseded_by"),
+        vec![second.id]
+    );
+
+    assert_eq!(
+        repo.current_decision(&first.id).expect("current decision"),
+        third.id
+    );
+    assert!(repo.supersede(&third.id, &first.id).is_err());
+
+    let report = repo.check().expect("check");
+    assert!(report.errors.is_empty(), "{:#?}", report.errors);
+    assert_eq!(repo.superseding_decision(&third.id), None);
+
+    // A `supersedes` link alone also moves the chain on, as `check` sees it
+    let fourth = repo
+        .create_entity(EntityKind::Decision, "Use Postgres 17", Some("accepted"))
+        .expect("create fourth decision");
+    repo.link(&fourth.id, "supersedes", &third.id)
+        .expect("link fourth supersedes third");
+    assert_eq!(
+        repo.current_decision(&first.id).expect("current decision"),
+        fourth.id
+    );
+    assert_eq!(repo.superseding_decision(&first.id), Some(fourth.id));
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Migrate", None)
+        .expect("create issue");
+    assert_eq!(repo.superseding_decision(&issue.id), None);
+}
+
+#[test]
+fn task_and_supersession_methods_resolve_short_ids() {
+    let (_temp, repo) = init_temp_git_repo();
+    let sqlite = repo
+        .create_entity(EntityKind::Decision, "Use SQLite", Some("accepted"))
+        .expect("create first decision");
+    let postgres = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", Some("accepted"))
+        .expect("create second decision");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Migrate schema", None)
+        .expect("create issue");
+
+    let postgres_prefix = &postgres.id[..postgres.id.len() - 1];
+    repo.supersede("use-sqlite", postgres_prefix)
+        .expect("supersede by slug and prefix");
+    assert_eq!(
+        repo.current_decision("use-sqlite")
+            .expect("current decision"),
+        postgres.id
+    );
+    assert_eq!(
+        repo.supersession_chain(&sqlite.id[..sqlite.id.len() - 1])
+            .expect("chain"),
+        vec![sqlite.id.clone(), postgres.id.clone()]
+    );
+    assert_eq!(repo.superseding_decision("use-sqlite"), Some(postgres.id));
+
+    assert!(
+        repo.set_task("migrate-schema", 1, true)
+            .expect("complete task")
+    );
+    let progress = repo.task_progress("migrate-schema").expect("progress");
+    assert_eq!(progress, repo.task_progress(&issue.id).expect("progress"));
+    assert_eq!((progress.done, progress.total), (1, 1));
+}
+
+#[test]
+fn check_reports_supersession_cycles_and_accepted_superseded() {
+    let (_temp, repo) = init_temp_git_repo();
+    let first = repo
+        .create_entity(EntityKind::Decision, "Decision A", Some("accepted"))
+        .expect("create first decision");
+    let second = repo
+        .create_entity(EntityKind::Decision, "Decision B", Some("superseded"))
+        .expect("create second decision");
+
+    repo.link(&first.id, "superseded_by", &second.id)
+        .expect("link first superseded_by second");
+    repo.link(&second.id, "superseded_by", &first.id)
+        .expect("link second superseded_by first");
+
+    let report = repo.check().expect("check");
+    let messages = report
+        .errors
+        .into_iter()
+        .map(|e| e.message)
+        .collect::<Vec<_>>();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("supersession cycle:")),
+        "{messages:#?}"
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.contains(&format!("accepted decision {} is superseded", first.id))),
+        "{messages:#?}"
+    );
+    assert!(repo.current_decision(&first.id).is_err());
+    assert_eq!(repo.superseding_decision(&first.id), None);
+}
+
+#[test]
+fn issue_dependencies_split_ready_and_blocked() {
+    let (_temp, repo) = init_temp_git_repo();
+    let schema = repo
+        .create_entity(EntityKind::Issue, "Design schema", Some("open"))
+        .expect("create schema issue");
+    let api = repo
+        .create_entity(EntityKind::Issue, "Build API", Some("open"))
+        .expect("create api issue");
+    let docs = repo
+        .create_entity(EntityKind::Issue, "Write docs", Some("open"))
+        .expect("create docs issue");
+    let setup = repo
+        .create_entity(EntityKind::Issue, "Set up repo", Some("closed"))
+        .expect("create setup issue");
+
+    repo.link(&api.id, "depends_on", &schema.id)
+        .expect("api depends on schema");
+    repo.link(&api.id, "blocks", &docs.id)
+        .expect("api blocks docs");
+    repo.link(&schema.id, "depends_on", &setup.id)
+        .expect("schema depends on closed setup");
+
+    let report = repo.issue_dependencies().expect("dependencies");
+    let ready = report
+        .ready
+        .iter()
+        .map(|issue| issue.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ready, vec![schema.id.as_str()]);
+
+    let blocked = report
+        .blocked
+        .iter()
+        .map(|b| {
+            (
+                b.issue.id.as_str(),
+                b.blockers
+                    .iter()
+                    .map(|issue| issue.id.as_str())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        blocked.contains(&(api.id.as_str(), vec![schema.id.as_str()])),
+        "{blocked:#?}"
+    );
+    assert!(
+        blocked.contains(&(docs.id.as_str(), vec![api.id.as_str()])),
+        "{blocked:#?}"
+    );
+
+    let report = repo.check().expect("check");
+    assert!(report.errors.is_empty(), "{:#?}", report.errors);
+
+    repo.link(&schema.id, "depends_on", &docs.id)
+        .expect("schema depends on docs");
+    let report = repo.check().expect("check");
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|e| e.message.starts_with("dependency cycle:")),
+        "{:#?}",
+        report.errors
+    );
+}
+
+#[test]
+fn resolve_id_accepts_prefixes_and_title_slugs() {
+    let (_temp, repo) = init_temp_git_repo();
+    let postgres = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create postgres decision");
+    let redis = repo
+        .create_entity(EntityKind::Decision, "Use Redis for caching", None)
+        .expect("create redis decision");
+
+    assert_eq!(repo.resolve_id(&postgres.id).expect("full id"), postgres.id);
+    assert_eq!(
+        repo.resolve_id(&postgres.id.to_uppercase())
+            .expect("uppercase id"),
+        postgres.id
+    );
+
+    let hash = postgres.id.strip_prefix("dec-").expect("decision prefix");
+    assert_eq!(repo.resolve_id(hash).expect("hash"), postgres.id);
+
+    assert_eq!(repo.resolve_id("use-postgres").expect("slug"), postgres.id);
+    assert_eq!(repo.resolve_id("Use Redis").expect("slug prefix"), redis.id);
+
+    let err = repo.resolve_id("dec-").expect_err("ambiguous prefix");
+    let message = err.to_string();
+    assert!(message.contains("Ambiguous"), "{message}");
+    assert!(message.contains(&postgres.id), "{message}");
+    assert!(message.contains(&redis.id), "{message}");
+
+    let err = repo.resolve_id("no-such-thing").expect_err("no match");
+    assert!(err.to_string().contains("No entity matches"), "{err}");
+}
+
+#[test]
+fn create_entity_uses_configured_id_strategy_and_retries_collisions() {
+    let (temp, repo) = init_temp_git_repo();
+    std::fs::write(
+        repo.paths.config_path(),
+        "[ids]\nstrategy = \"content_keyed\"\nbytes = 4\n",
+    )
+    .expect("write config");
+    let repo = IxchelRepo::open_from(temp.path()).expect("reopen repo");
+
+    let first = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create first decision");
+    assert_eq!(
+        first.id,
+        ix_id::id_from_key_with_length("dec", "use-postgres", 4)
+    );
+
+    let second = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create colliding decision");
+    assert_ne!(first.id, second.id);
+    assert_eq!(second.id.len(), "dec-".len() + 8);
+}
+
+#[test]
+fn widen_ids_renames_files_and_rewrites_references() {
+    let (_temp, repo) = init_temp_git_repo();
+    let first = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create first decision");
+    let second = repo
+        .create_entity(EntityKind::Issue, "Migrate schema", None)
+        .expect("create issue");
+    repo.link(&second.id, "implements", &first.id)
+        .expect("link issue to decision");
+
+    let raw = std::fs::read_to_string(&second.path).expect("read issue");
+    let mut doc = parse_markdown(&second.path, &raw).expect("parse issue");
+    doc.body = format!("Follows {}.\n", first.id);
+    std::fs::write(&second.path, render_markdown(&doc).expect("render")).expect("write issue");
+
+    let renames = repo.widen_ids(4).expect("widen ids");
+    assert_eq!(renames.len(), 2);
+    let wide_first = ix_id::widen_id(&first.id, 4).expect("widen first");
+    let wide_second = ix_id::widen_id(&second.id, 4).expect("widen second");
+
+    assert!(!first.path.exists());
+    assert!(!second.path.exists());
+    let issue_path = repo.paths.entity_path(&wide_second).expect("issue path");
+    let raw = std::fs::read_to_string(&issue_path).expect("read widened issue");
+    let doc = parse_markdown(&issue_path, &raw).expect("parse widened issue");
+    assert_eq!(
+        ix_core::markdown::get_string(&doc.frontmatter, "id").as_deref(),
+        Some(wide_second.as_str())
+    );
+    assert_eq!(
+        ix_core::markdown::get_string_list(&doc.frontmatter, "implements"),
+        vec![wide_first.clone()]
+    );
+    assert!(doc.body.contains(&format!("Follows {wide_first}.")));
+
+    assert_eq!(
+        repo.resolve_id(&first.id).expect("old id resolves"),
+        wide_first
+    );
+    assert!(repo.widen_ids(4).expect("widen again").is_empty());
+
+    let report = repo.check().expect("check");
+    assert!(report.errors.is_empty(), "{:#?}", report.errors);
+}
+
+#[test]
+fn widen_ids_leaves_tree_unchanged_when_a_write_fails() {
+    let (_temp, repo) = init_temp_git_repo();
+    let first = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create first decision");
+    let second = repo
+        .create_entity(EntityKind::Issue, "Migrate schema", None)
+        .expect("create issue");
+    repo.link(&second.id, "implements", &first.id)
+        .expect("link issue to decision");
+    let before = [&first.path, &second.path].map(|path| std::fs::read_to_string(path).unwrap());
+
+    // A directory in the way of one staged file makes its write fail
+    let wide_second = ix_id::widen_id(&second.id, 4).expect("widen second");
+    let blocker = second
+        .path
+        .with_file_name(format!("{wide_second}.md.widen"));
+    std::fs::create_dir(&blocker).expect("create blocker");
+
+    assert!(repo.widen_ids(4).is_err());
+    let after = [&first.path, &second.path].map(|path| std::fs::read_to_string(path).unwrap());
+    assert_eq!(before, after);
+
+    let stray = std::fs::read_dir(first.path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".widen"))
+        .collect::<Vec<_>>();
+    assert!(stray.is_empty(), "{stray:?}");
+
+    std::fs::remove_dir(&blocker).expect("remove blocker");
+    assert_eq!(repo.widen_ids(4).expect("widen ids").len(), 2);
+}
+
+#[test]
+fn chunk_body_tracks_heading_paths_and_file_lines() {
+    use ix_core::chunk::chunk_body;
+    use ix_core::markdown::body_line_offset;
+
+    let raw = "---\nid: rpt-a1b2c3\n---\n\nIntro text.\n\n# Design\n\n## Storage\n\nFirst paragraph.\n\nSecond paragraph.\n\n## Caching\n\n```\n# not a heading\n```\n";
+    let doc = parse_markdown(std::path::Path::new("rpt-a1b2c3.md"), raw).expect("parse");
+    let offset = body_line_offset(raw);
+    assert_eq!(offset, 3);
+
+    let chunks = chunk_body(&doc.body, offset, 1500);
+    let summary = chunks
+        .iter()
+        .map(|c| (c.heading_path.join(" > "), c.start_line, c.end_line))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (String::new(), 5, 5),
+            ("Design > Storage".to_string(), 11, 13),
+            ("Design > Caching".to_string(), 17, 19),
+        ]
+    );
+    let lines = raw.lines().collect::<Vec<_>>();
+    assert_eq!(lines[10], "First paragraph.");
+    assert_eq!(chunks[2].text, "```\n# not a heading\n```");
+
+    let small = chunk_body(&doc.body, offset, 20);
+    let storage = small
+        .iter()
+        .filter(|c| c.heading_path.last().is_some_and(|h| h == "Storage"))
+        .map(|c| c.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(storage, vec!["First paragraph.", "Second paragraph."]);
+
+    let empty = chunk_body("", 3, 1500);
+    assert_eq!(empty.len(), 1);
+    assert!(empty[0].text.is_empty());
+}
+
+#[test]
+fn index_embedding_config_reuses_the_stored_dimension() {
+    use ix_core::index::IndexMetadata;
+
+    let (_temp, mut repo) = init_temp_git_repo();
+    repo.config.embedding.provider = "http".to_string();
+    repo.config.embedding.model = "nomic-embed-text".to_string();
+    repo.config.embedding.dimension = None;
+    assert_eq!(repo.index_embedding_config().dimension, None);
+
+    std::fs::create_dir_all(repo.index_path().parent().expect("parent")).expect("data dir");
+    IndexMetadata::current("flat", "http", "nomic-embed-text", 768, "")
+        .save(&repo.index_path())
+        .expect("save metadata");
+    assert_eq!(repo.index_embedding_config().dimension, Some(768));
+
+    // Another model, or an explicit dimension, is never overridden
+    repo.config.embedding.model = "mxbai-embed-large".to_string();
+    assert_eq!(repo.index_embedding_config().dimension, None);
+    repo.config.embedding.dimension = Some(1024);
+    assert_eq!(repo.index_embedding_config().dimension, Some(1024));
+}
+
+#[test]
+fn index_metadata_round_trips_and_reports_mismatches() {
+    use ix_core::index::{IndexMetadata, IndexMetadataStatus};
+
+    let temp = TempDir::new().expect("create tempdir");
+    let db_path = temp.path().join("data").join("ixchel");
+    std::fs::create_dir_all(&db_path).expect("create db dir");
+    assert_eq!(
+        IndexMetadata::sidecar_path(&db_path),
+        temp.path().join("data").join("ixchel.meta.json")
+    );
+    assert!(IndexMetadata::load(&db_path).expect("load").is_none());
+
+    let built = IndexMetadata::current("surrealdb", "fastembed", "BAAI/bge-small-en-v1.5", 384, "");
+    built.save(&db_path).expect("save metadata");
+    let stored = IndexMetadata::load(&db_path)
+        .expect("load")
+        .expect("stored");
+    assert_eq!(stored, built);
+
+    let same = IndexMetadataStatus {
+        expected: built.clone(),
+        stored: Some(stored.clone()),
+    };
+    assert!(!same.needs_rebuild());
+    same.ensure_compatible().expect("compatible");
+
+    let changed = IndexMetadataStatus {
+        expected: IndexMetadata::current("surrealdb", "hash", "hash-v1", 64, ""),
+        stored: Some(stored),
+    };
+    assert!(changed.needs_rebuild());
+    assert_eq!(
+        changed.differences(),
+        vec![
+            "provider: fastembed -> hash".to_string(),
+            "model: BAAI/bge-small-en-v1.5 -> hash-v1".to_string(),
+            "dimension: 384 -> 64".to_string(),
+        ]
+    );
+    let err = changed
+        .ensure_compatible()
+        .expect_err("mismatch")
+        .to_string();
+    assert!(err.contains("ixchel sync"), "{err}");
+
+    let unsynced = IndexMetadataStatus {
+        expected: built,
+        stored: None,
+    };
+    assert!(unsynced.needs_rebuild());
+    unsynced
+        .ensure_compatible()
+        .expect("nothing to compare yet");
+}
+
+#[test]
+fn rerank_scores_reorder_and_truncate_hits() {
+    use ix_core::chunk::Chunk;
+    use ix_core::index::{SearchHit, apply_rerank_scores};
+
+    let hit = |id: &str, score: f32, passage: Option<&str>| SearchHit {
+        score,
+        id: id.to_string(),
+        kind: Some(EntityKind::Decision),
+        title: format!("Title {id}"),
+        passage: passage.map(|text| Chunk {
+            index: 0,
+            heading_path: Vec::new(),
+            start_line: 1,
+            end_line: 1,
+            text: text.to_string(),
+        }),
+        ..SearchHit::default()
+    };
+
+    let hits = vec![
+        hit("rpt-1", 0.9, Some("loosely related")),
+        hit("dec-1", 0.8, Some("the decision")),
+        hit("dec-2", 0.7, None),
+    ];
+    assert_eq!(hits[0].rerank_text(), "Title rpt-1\n\nloosely related");
+    assert_eq!(hits[2].rerank_text(), "Title dec-2");
+
+    let reranked = apply_rerank_scores(hits.clone(), &[0.1, 0.95, 0.4], 2).expect("rerank");
+    let ids = reranked.iter().map(|h| h.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["dec-1", "dec-2"]);
+    assert_eq!(reranked[0].rerank_score, Some(0.95));
+    assert!((reranked[0].score - 0.8).abs() < f32::EPSILON);
+
+    assert!(apply_rerank_scores(hits, &[0.1], 3).is_err());
+}
+
+#[test]
+fn lexical_index_scores_exact_terms_and_round_trips() {
+    use ix_core::chunk::chunk_body;
+    use ix_core::lexical::{LexicalIndex, tokenize};
+
+    assert_eq!(
+        tokenize("Fix E0425 in ix-core."),
+        vec!["fix", "e0425", "in", "ix-core", "ix", "core"]
+    );
+
+    let mut index = LexicalIndex::new();
+    let add = |index: &mut LexicalIndex, id: &str, title: &str, body: &str| {
+        for chunk in chunk_body(body, 0, 1500) {
+            index.add(id, EntityKind::Issue, title, &chunk);
+        }
+    };
+    add(
+        &mut index,
+        "iss-1",
+        "Build fails",
+        "## Compiler\n\nrustc reports E0425\n\n## Notes\n\nnightly only\n",
+    );
+    add(
+        &mut index,
+        "iss-2",
+        "Slow build",
+        "the nightly build is slow\n",
+    );
+    add(&mut index, "iss-3", "Docs", "nothing relevant\n");
+
+    let hits = index.search("E0425", 10);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, "iss-1");
+    let passage = hits[0].passage.as_ref().expect("passage");
+    assert_eq!(passage.heading_path, vec!["Compiler".to_string()]);
+
+    // Rarer terms weigh more: "slow" only appears in iss-2
+    let hits = index.search("nightly slow", 10);
+    let ids = hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["iss-2", "iss-1"]);
+
+    let temp = TempDir::new().expect("tempdir");
+    let db_path = temp.path().join("ixchel");
+    assert!(LexicalIndex::load(&db_path).expect("load").is_none());
+    index.save(&db_path).expect("save");
+    index.save(&db_path).expect("save over");
+    let files = std::fs::read_dir(temp.path())
+        .expect("read dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect::<Vec<_>>();
+    // Written through a temporary file renamed into place
+    assert_eq!(files, vec![std::ffi::OsString::from("ixchel.lexical.json")]);
+    let mut loaded = LexicalIndex::load(&db_path).expect("load").expect("stored");
+    assert_eq!(loaded.len(), index.len());
+    assert_eq!(loaded.search("E0425", 10)[0].id, "iss-1");
+
+    loaded.remove_entity("iss-1");
+    assert!(loaded.search("E0425", 10).is_empty());
+    assert_eq!(loaded.search("slow", 10)[0].id, "iss-2");
+
+    let mut added = LexicalIndex::new();
+    add(&mut added, "iss-2", "Fast build", "the build is fast now\n");
+    add(&mut added, "iss-4", "Nightly", "nightly is fine\n");
+    loaded.remove_entities(["iss-2", "iss-3"]);
+    loaded.append(added);
+    assert_eq!(loaded.entity_ids(), vec!["iss-2", "iss-4"]);
+    assert!(loaded.search("slow", 10).is_empty());
+    assert_eq!(loaded.search("fast", 10)[0].id, "iss-2");
+    assert_eq!(loaded.search("nightly", 10)[0].id, "iss-4");
+}
+
+#[test]
+fn sync_manifest_tracks_changes_and_round_trips_backend_state() {
+    use ix_core::manifest::{ManifestEntry, SyncAction, SyncManifest};
+
+    const PATH: &str = ".ixchel/decisions/dec-123.md";
+    let entry = |content_hash: &str| ManifestEntry {
+        content_hash: content_hash.to_string(),
+        file_path: PATH.to_string(),
+        last_synced: 1000,
+        written: vec![u128::MAX, 2],
+    };
+
+    let mut manifest = SyncManifest::new();
+    assert_eq!(
+        manifest.action_for("dec-123", "abc123", PATH),
+        SyncAction::Insert
+    );
+    manifest.insert("dec-123".to_string(), entry("abc123"));
+    assert_eq!(
+        manifest.action_for("dec-123", "abc123", PATH),
+        SyncAction::Skip
+    );
+    assert_eq!(
+        manifest.action_for("dec-123", "different_hash", PATH),
+        SyncAction::Update
+    );
+    // Same content hash but different path should trigger update
+    assert_eq!(
+        manifest.action_for("dec-123", "abc123", ".ixchel/decisions/renamed.md"),
+        SyncAction::Update
+    );
+
+    let temp = TempDir::new().expect("tempdir");
+    let db_path = temp.path().join("ixchel");
+    assert!(
+        SyncManifest::<Vec<u128>>::load(&db_path)
+            .expect("load")
+            .is_none()
+    );
+    manifest.save(&db_path).expect("save");
+    assert!(temp.path().join("ixchel.manifest.json").exists());
+    let loaded = SyncManifest::<Vec<u128>>::load(&db_path)
+        .expect("load")
+        .expect("manifest");
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(
+        loaded.get("dec-123").expect("entry").written,
+        vec![u128::MAX, 2]
+    );
+
+    SyncManifest::<Vec<u128>>::discard(&db_path).expect("discard");
+    assert!(!temp.path().join("ixchel.manifest.json").exists());
+    SyncManifest::<Vec<u128>>::discard(&db_path).expect("discard again");
+}
+
+#[test]
+fn reciprocal_rank_fusion_rewards_agreement() {
+    use ix_core::index::{SearchHit, SearchMode, reciprocal_rank_fusion, search_with_mode};
+
+    let hit = |id: &str, score: f32| SearchHit {
+        score,
+        id: id.to_string(),
+        kind: None,
+        title: id.to_string(),
+        passage: None,
+        ..SearchHit::default()
+    };
+
+    let vector = vec![hit("a", 0.9), hit("b", 0.8), hit("c", 0.7)];
+    let lexical = vec![hit("b", 12.0), hit("c", 9.0), hit("d", 3.0)];
+    let fused = reciprocal_rank_fusion(&[vector.clone(), lexical.clone()], 3);
+    let ids = fused.iter().map(|h| h.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["b", "c", "a"]);
+    assert!((fused[0].score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-6);
+
+    assert_eq!(
+        "Hybrid".parse::<SearchMode>().expect("parse"),
+        SearchMode::Hybrid
+    );
+    assert_eq!(
+        "bm25".parse::<SearchMode>().expect("parse"),
+        SearchMode::Lexical
+    );
+    assert!("fuzzy".parse::<SearchMode>().is_err());
+
+    let hybrid = search_with_mode(
+        SearchMode::Hybrid,
+        2,
+        |n| {
+            assert_eq!(n, 4);
+            Ok(vector.clone())
+        },
+        |_| Ok(lexical.clone()),
+    )
+    .expect("hybrid");
+    assert_eq!(hybrid.len(), 2);
+
+    let lexical_only = search_with_mode(
+        SearchMode::Lexical,
+        5,
+        |_| panic!("vector retriever should not run"),
+        |_| Ok(lexical.clone()),
+    )
+    .expect("lexical");
+    assert_eq!(lexical_only[0].id, "b");
+}
+
+#[test]
+fn search_filters_match_entity_facets() {
+    use ix_core::index::{DateRange, SearchFacets, SearchFilters, parse_date};
+
+    let (_temp, repo) = init_temp_git_repo();
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Auth fails", Some("open"))
+        .expect("create issue");
+    repo.add_tags(&issue.id, &["auth".to_string()])
+        .expect("add tags");
+    set_entity_timestamps(
+        &repo,
+        &issue.id,
+        Some("2026-01-04T14:06:33.403860+00:00"),
+        Some("2026-02-01T09:00:00+01:00"),
+    );
+
+    let raw = std::fs::read_to_string(&issue.path).expect("read issue");
+    let doc = parse_markdown(&issue.path, &raw).expect("parse issue");
+    let facets = SearchFacets::from_frontmatter(&doc.frontmatter);
+    assert_eq!(facets.status, "open");
+    assert_eq!(facets.tags, vec!["auth".to_string()]);
+    assert_eq!(facets.created_at.as_deref(), Some("2026-01-04T14:06:33Z"));
+    assert_eq!(facets.updated_at.as_deref(), Some("2026-02-01T08:00:00Z"));
+
+    let kind = Some(EntityKind::Issue);
+    assert!(SearchFilters::default().matches(kind, &facets));
+
+    let filters = SearchFilters {
+        kinds: vec![EntityKind::Issue, EntityKind::Decision],
+        statuses: vec!["Open".to_string()],
+        tags: vec!["auth".to_string()],
+        ..SearchFilters::default()
+    };
+    assert!(filters.matches(kind, &facets));
+    assert!(!filters.matches(Some(EntityKind::Source), &facets));
+    assert!(!filters.matches(None, &facets));
+
+    let filters = SearchFilters {
+        tags: vec!["auth".to_string(), "security".to_string()],
+        ..SearchFilters::default()
+    };
+    assert!(!filters.matches(kind, &facets), "every tag must be present");
+
+    let january = DateRange {
+        after: Some(parse_date("2026-01-01").expect("date")),
+        before: Some(parse_date("2026-02-01").expect("date")),
+    };
+    let created_in_january = SearchFilters {
+        created: january,
+        ..SearchFilters::default()
+    };
+    assert!(created_in_january.matches(kind, &facets));
+    let updated_in_january = SearchFilters {
+        updated: january,
+        ..SearchFilters::default()
+    };
+    assert!(
+        !updated_in_january.matches(kind, &facets),
+        "before is exclusive"
+    );
+    assert!(!created_in_january.matches(kind, &SearchFacets::default()));
+    assert!(parse_date("last week").is_err());
+}
+
+#[test]
+fn search_request_pages_with_offsets_and_cursors() {
+    use ix_core::index::{SearchHit, SearchRequest};
+
+    let hits = [1.0, 0.9, 0.8, 0.7, 0.6]
+        .into_iter()
+        .enumerate()
+        .map(|(n, score)| SearchHit {
+            score,
+            id: format!("iss-{n}"),
+            kind: None,
+            title: String::new(),
+            passage: None,
+            ..SearchHit::default()
+        })
+        .collect::<Vec<_>>();
+    let ids = |hits: &[SearchHit]| hits.iter().map(|h| h.id.clone()).collect::<Vec<_>>();
+
+    let first = SearchRequest::new("q", 2);
+    assert_eq!(first.window(), 2);
+    let page = first.page(hits.clone());
+    assert_eq!(ids(&page), vec!["iss-0", "iss-1"]);
+    let cursor = first.next_cursor(page.len()).expect("more pages");
+
+    let second = SearchRequest::new("q", 2)
+        .with_cursor(&cursor)
+        .expect("cursor");
+    assert_eq!(second.offset, 2);
+    assert_eq!(second.window(), 4);
+    assert_eq!(ids(&second.page(hits.clone())), vec!["iss-2", "iss-3"]);
+
+    let last = SearchRequest::new("q", 2).with_offset(4);
+    let page = last.page(hits.clone());
+    assert_eq!(ids(&page), vec!["iss-4"]);
+    assert!(last.next_cursor(page.len()).is_none());
+
+    let confident = SearchRequest::new("q", 10).with_min_score(Some(0.75));
+    assert_eq!(ids(&confident.page(hits)), vec!["iss-0", "iss-1", "iss-2"]);
+
+    assert!(SearchRequest::new("q", 2).with_cursor("next").is_err());
+}
+
+#[test]
+fn lexical_index_applies_search_filters() {
+    use ix_core::chunk::chunk_body;
+    use ix_core::index::{SearchFacets, SearchFilters};
+    use ix_core::lexical::LexicalIndex;
+
+    let facets = |status: &str| SearchFacets {
+        status: status.to_string(),
+        ..SearchFacets::default()
+    };
+    let mut index = LexicalIndex::new();
+    for chunk in chunk_body("auth tokens expire\n", 0, 1500) {
+        index.add_with_facets("iss-1", EntityKind::Issue, "Auth", &facets("open"), &chunk);
+        index.add_with_facets(
+            "iss-2",
+            EntityKind::Issue,
+            "Auth",
+            &facets("closed"),
+            &chunk,
+        );
+        index.add_with_facets("src-1", EntityKind::Source, "Auth", &facets(""), &chunk);
+    }
+    assert_eq!(index.search("auth", 10).len(), 3);
+
+    let filters = SearchFilters {
+        kinds: vec![EntityKind::Issue],
+        statuses: vec!["open".to_string()],
+        ..SearchFilters::default()
+    };
+    let hits = index.search_filtered("auth", 10, &filters);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, "iss-1");
+}
+
+#[test]
+fn lexical_ties_keep_insertion_order() {
+    use ix_core::chunk::chunk_body;
+    use ix_core::lexical::LexicalIndex;
+
+    let mut index = LexicalIndex::new();
+    let ids = (0..20).map(|n| format!("iss-{n:02}")).collect::<Vec<_>>();
+    for id in &ids {
+        for chunk in chunk_body("auth tokens expire\n", 0, 1500) {
+            index.add(id, EntityKind::Issue, "Auth", &chunk);
+        }
+    }
+
+    for _ in 0..3 {
+        let hits = index.search("auth", 20);
+        let found = hits.iter().map(|h| h.id.clone()).collect::<Vec<_>>();
+        assert_eq!(found, ids);
+    }
+    let page = index.search("auth", 5);
+    assert_eq!(
+        page.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(),
+        ids[..5]
+    );
+}
+
+#[test]
+fn snippets_highlight_query_terms_in_the_best_window() {
+    use ix_core::snippet::snippet;
+
+    let short = snippet("Refresh the ix-core\nauth token.", "AUTH core", 200).expect("snippet");
+    assert_eq!(short.text, "Refresh the ix-core auth token.");
+    assert_eq!(
+        short.render("**", "**"),
+        "Refresh the **ix-core** **auth** token."
+    );
+
+    let filler = "lorem ipsum ".repeat(20);
+    let text = format!("{filler}token expiry breaks auth refresh. {filler}");
+    let long = snippet(&text, "auth refresh", 60).expect("snippet");
+    assert!(
+        long.text.starts_with('…') && long.text.ends_with('…'),
+        "{}",
+        long.text
+    );
+    assert!(long.text.chars().count() <= 62, "{}", long.text);
+    assert!(
+        long.render("[", "]").contains("[auth] [refresh]"),
+        "{}",
+        long.text
+    );
+
+    let unmatched = snippet(&text, "kubernetes", 30).expect("snippet");
+    assert!(unmatched.text.starts_with("lorem ipsum"));
+    assert!(unmatched.highlights.is_empty());
+
+    assert!(snippet(" \n ", "auth", 200).is_none());
+}
+
+#[test]
+fn duplicate_detection_pairs_similar_entities_of_one_kind() {
+    use ix_core::similarity::{EntityVector, cosine_similarity, find_duplicates};
+
+    assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+    assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+    assert!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]).abs() < 1e-6);
+    assert!(cosine_similarity(&[1.0], &[1.0, 0.0]).abs() < 1e-6);
+
+    let entity = |id: &str, kind, vector: Vec<f32>| EntityVector {
+        id: id.to_string(),
+        kind: Some(kind),
+        title: id.to_string(),
+        vector,
+    };
+    let entities = vec![
+        entity("iss-b", EntityKind::Issue, vec![1.0, 0.1, 0.0]),
+        entity("iss-a", EntityKind::Issue, vec![1.0, 0.0, 0.0]),
+        entity("iss-c", EntityKind::Issue, vec![1.0, 0.3, 0.0]),
+        entity("dec-a", EntityKind::Decision, vec![1.0, 0.0, 0.0]),
+        entity("src-a", EntityKind::Source, vec![0.0, 0.0, 1.0]),
+    ];
+
+    let pairs = find_duplicates(&entities, 0.98, false);
+    let ids = pairs
+        .iter()
+        .map(|pair| (pair.first.id.as_str(), pair.second.id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![("iss-a", "iss-b"), ("iss-b", "iss-c")]);
+    assert!(pairs[0].similarity > pairs[1].similarity);
+
+    let pairs = find_duplicates(&entities, 0.98, true);
+    let ids = pairs
+        .iter()
+        .map(|pair| (pair.first.id.as_str(), pair.second.id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(ids[0], ("dec-a", "iss-a"));
+    assert!(ids.contains(&("dec-a", "iss-b")));
+    assert!(ids.iter().all(|(a, b)| *a != "src-a" && *b != "src-a"));
+}
+
+#[test]
+fn tags_are_suggested_from_neighbors_and_topics_cluster_vectors() {
+    use std::collections::HashMap;
+
+    use ix_core::similarity::EntityVector;
+    use ix_core::topics::{cluster_topics, default_topic_count, suggest_tags};
+
+    let entity = |id: &str, title: &str, vector: Vec<f32>| EntityVector {
+        id: id.to_string(),
+        kind: Some(EntityKind::Issue),
+        title: title.to_string(),
+        vector,
+    };
+    let entities = vec![
+        entity("iss-a1", "Pool database connections", vec![1.0, 0.1, 0.0]),
+        entity("iss-a2", "Retry database timeouts", vec![0.9, 0.2, 0.0]),
+        entity("iss-a3", "Untagged database issue", vec![1.0, 0.15, 0.0]),
+        entity("iss-b1", "Dark mode toggle", vec![0.0, 0.1, 1.0]),
+        entity("iss-b2", "Dark mode contrast", vec![0.1, 0.0, 1.0]),
+    ];
+    let tags = HashMap::from([
+        (
+            "iss-a1".to_string(),
+            vec!["database".to_string(), "perf".to_string()],
+        ),
+        ("iss-a2".to_string(), vec!["database".to_string()]),
+        ("iss-b1".to_string(), vec!["ui".to_string()]),
+    ]);
+
+    let suggestions = suggest_tags(&entities[2], &entities, &tags);
+    let names = suggestions
+        .iter()
+        .map(|s| s.tag.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["database", "perf"]);
+    assert!(suggestions[0].support > 0.9);
+    assert_eq!(suggestions[0].sources.len(), 2);
+    assert!(
+        suggestions
+            .iter()
+            .all(|s| !s.sources.contains(&"iss-b1".to_string()))
+    );
+
+    let suggestions = suggest_tags(&entities[0], &entities, &tags);
+    assert!(suggestions.iter().all(|s| s.tag != "database"));
+
+    assert_eq!(default_topic_count(0), 1);
+    assert_eq!(default_topic_count(8), 2);
+    let topics = cluster_topics(&entities, 2, &tags);
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0].label, "database, perf");
+    let members = |index: usize| {
+        let mut ids = topics[index]
+            .members
+            .iter()
+            .map(|entity| entity.id.as_str())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    };
+    assert_eq!(members(0), vec!["iss-a1", "iss-a2", "iss-a3"]);
+    assert_eq!(members(1), vec!["iss-b1", "iss-b2"]);
+    assert_eq!(topics[1].label, "ui");
+
+    let untagged = HashMap::new();
+    let topics = cluster_topics(&entities, 2, &untagged);
+    assert_eq!(topics[1].label, "dark, mode, contrast");
+}
+
+#[test]
+fn links_are_suggested_between_similar_entities_of_linkable_kinds() {
+    use ix_core::repo::Relationship;
+    use ix_core::similarity::{EntityVector, link_rule, suggest_links};
+
+    assert_eq!(
+        link_rule(EntityKind::Issue, EntityKind::Decision),
+        Some(("implements", true))
+    );
+    assert_eq!(
+        link_rule(EntityKind::Decision, EntityKind::Issue),
+        Some(("implements", false))
+    );
+    assert_eq!(link_rule(EntityKind::Issue, EntityKind::Issue), None);
+
+    let entity = |id: &str, kind, vector: Vec<f32>| EntityVector {
+        id: id.to_string(),
+        kind: Some(kind),
+        title: id.to_string(),
+        vector,
+    };
+    let entities = vec![
+        entity("dec-a", EntityKind::Decision, vec![1.0, 0.0, 0.0]),
+        entity("iss-a", EntityKind::Issue, vec![1.0, 0.1, 0.0]),
+        entity("iss-b", EntityKind::Issue, vec![1.0, 0.4, 0.0]),
+        entity("iss-c", EntityKind::Issue, vec![0.0, 0.0, 1.0]),
+        entity("src-a", EntityKind::Source, vec![1.0, 0.2, 0.0]),
+        entity("dec-b", EntityKind::Decision, vec![1.0, 0.0, 0.0]),
+        entity("src-b", EntityKind::Source, vec![1.0, 0.0, 0.0]),
+    ];
+    let existing = vec![Relationship {
+        from: "dec-a".to_string(),
+        rel: "cites".to_string(),
+        to: "src-b".to_string(),
+    }];
+
+    let suggestions = suggest_links(&entities[0], &entities, &existing, 0.5);
+    let links = suggestions
+        .iter()
+        .map(|s| (s.from.as_str(), s.rel.as_str(), s.to.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        vec![
+            ("iss-a", "implements", "dec-a"),
+            ("dec-a", "cites", "src-a"),
+            ("iss-b", "implements", "dec-a"),
+        ]
+    );
+    assert_eq!(suggestions[0].other.id, "iss-a");
+    assert!(suggestions[0].similarity > suggestions[2].similarity);
+
+    let suggestions = suggest_links(&entities[0], &entities, &existing, 0.99);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].to, "dec-a");
+}
+
+#[test]
+fn title_similarity_tolerates_case_punctuation_and_small_edits() {
+    use ix_core::similarity::{SIMILAR_TITLE_THRESHOLD, title_similarity};
+
+    assert!((title_similarity("Use Postgres!", "use   postgres") - 1.0).abs() < f32::EPSILON);
+    assert!(
+        title_similarity("Auth token refresh fails", "Auth token refresh failing")
+            >= SIMILAR_TITLE_THRESHOLD
+    );
+    assert!(title_similarity("Use Postgres", "Use PostgreSQL") >= SIMILAR_TITLE_THRESHOLD);
+    assert!(
+        title_similarity("Add tests for parser", "Add tests for lexer") < SIMILAR_TITLE_THRESHOLD
+    );
+    assert!(title_similarity("Auth fails", "Login loops") < 0.5);
+    assert!(title_similarity("", "") < f32::EPSILON);
+}
+
+#[test]
+fn eval_metrics_score_ranked_hits_against_golden_queries() {
+    use ix_core::eval::{EvalReport, GoldenSet, QueryEval};
+
+    let ids = |ids: &[&str]| ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    let perfect = QueryEval::score(
+        "a",
+        &ids(&["iss-1", "iss-2"]),
+        &ids(&["iss-1", "iss-2"]),
+        10,
+    );
+    assert!((perfect.recall - 1.0).abs() < 1e-6);
+    assert!((perfect.reciprocal_rank - 1.0).abs() < 1e-6);
+    assert!((perfect.ndcg - 1.0).abs() < 1e-6);
+
+    let late = QueryEval::score(
+        "b",
+        &ids(&["iss-1", "iss-2"]),
+        &ids(&["iss-9", "iss-1", "iss-8"]),
+        10,
+    );
+    assert!((late.recall - 0.5).abs() < 1e-6);
+    assert!((late.reciprocal_rank - 0.5).abs() < 1e-6);
+    let expected_ndcg = (1.0 / 3f32.log2()) / (1.0 + 1.0 / 3f32.log2());
+    assert!((late.ndcg - expected_ndcg).abs() < 1e-6, "{}", late.ndcg);
+    assert_eq!(late.missed(), vec!["iss-2"]);
+
+    let cut = QueryEval::score("c", &ids(&["iss-1"]), &ids(&["iss-9", "iss-1"]), 1);
+    assert_eq!(cut.returned, ids(&["iss-9"]));
+    assert!(cut.recall.abs() < 1e-6 && cut.reciprocal_rank.abs() < 1e-6);
+
+    let report = EvalReport::new(10, vec![perfect, late]);
+    assert!((report.recall - 0.75).abs() < 1e-6);
+    assert!((report.mrr - 0.75).abs() < 1e-6);
+
+    let temp = TempDir::new().expect("tempdir");
+    let path = temp.path().join("eval.yaml");
+    std::fs::write(
+        &path,
+        "thresholds:\n  recall: 0.8\nqueries:\n  - query: pooling\n    expected: [iss-1]\n",
+    )
+    .expect("write golden");
+    let golden = GoldenSet::load(&path).expect("load golden");
+    assert_eq!(golden.k, 10);
+    assert_eq!(golden.queries[0].expected, ids(&["iss-1"]));
+    let failures = golden.thresholds.failures(&report);
+    assert_eq!(failures, vec!["recall@10 0.750 is below 0.800".to_string()]);
+
+    std::fs::write(&path, "queries:\n  - query: pooling\n    expected: []\n")
+        .expect("write golden");
+    assert!(GoldenSet::load(&path).is_err());
+}
+
+#[test]
+fn vector_scores_map_cosine_distance_into_unit_range() {
+    use ix_core::index::{SearchHit, reciprocal_rank_fusion, similarity_from_distance};
+
+    assert!((similarity_from_distance(0.0) - 1.0).abs() < 1e-6);
+    assert!((similarity_from_distance(0.25) - 0.75).abs() < 1e-6);
+    assert!(similarity_from_distance(1.0).abs() < 1e-6);
+    assert!(similarity_from_distance(1.6).abs() < 1e-6);
+    assert!((similarity_from_distance(-1e-7) - 1.0).abs() < 1e-6);
+
+    let hit = |id: &str| SearchHit {
+        id: id.to_string(),
+        ..SearchHit::default()
+    };
+    let close = hit("a").with_cosine_distance(0.1);
+    assert!((close.score - 0.9).abs() < 1e-6);
+    assert_eq!(close.distance, Some(0.1));
+
+    let lexical = vec![SearchHit {
+        score: 7.5,
+        ..hit("a")
+    }];
+    let fused = reciprocal_rank_fusion(&[lexical, vec![close]], 5);
+    assert_eq!(fused[0].distance, Some(0.1));
+}
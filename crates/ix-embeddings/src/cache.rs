@@ -0,0 +1,267 @@
+//! Persistent on-disk cache of embedding vectors.
+//!
+//! Vectors are keyed by a blake3 hash of the provider, model name, dimension,
+//! and input text, so index rebuilds and backend switches can reuse earlier
+//! work. Each vector is stored as little-endian `f32` bytes in its own file,
+//! sharded by the first two hex characters of the key.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Leftover temporary files older than this are from interrupted writes.
+const STALE_TEMP_AGE: Duration = Duration::from_hours(1);
+
+/// Hit/miss counters for an [`EmbeddingCache`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Counts accumulated since an earlier snapshot.
+    #[must_use]
+    pub const fn since(self, earlier: Self) -> Self {
+        Self {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
+}
+
+/// Outcome of [`EmbeddingCache::prune`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PruneStats {
+    /// Entries left in the cache.
+    pub entries: u64,
+    /// Bytes used by the entries left in the cache.
+    pub bytes: u64,
+    /// Entries and stale temporary files removed.
+    pub removed: u64,
+    /// Bytes freed.
+    pub removed_bytes: u64,
+}
+
+#[derive(Debug)]
+pub struct EmbeddingCache {
+    dir: PathBuf,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EmbeddingCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key for `text` embedded by `provider`'s `model` at `dimension`.
+    ///
+    /// The provider is part of the key since e.g. an HTTP server may serve a
+    /// different model under the same name.
+    #[must_use]
+    pub fn key(provider: &str, model: &str, dimension: usize, text: &str) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(provider.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(model.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(dimension as u64).to_le_bytes());
+        hasher.update(&[0]);
+        hasher.update(text.as_bytes());
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Look up a vector, counting the hit or miss. Unreadable or truncated
+    /// entries are treated as misses.
+    #[must_use]
+    pub fn get(
+        &self,
+        provider: &str,
+        model: &str,
+        dimension: usize,
+        text: &str,
+    ) -> Option<Vec<f32>> {
+        let vector = std::fs::read(self.path_for(&Self::key(provider, model, dimension, text)))
+            .ok()
+            .filter(|bytes| bytes.len() == dimension * 4)
+            .map(|bytes| {
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect::<Vec<_>>()
+            });
+
+        let counter = if vector.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        vector
+    }
+
+    /// Store a vector. Writes go through a temporary file and a rename so
+    /// concurrent readers (e.g. the daemon) never see a partial entry.
+    pub fn put(
+        &self,
+        provider: &str,
+        model: &str,
+        dimension: usize,
+        text: &str,
+        vector: &[f32],
+    ) -> std::io::Result<()> {
+        let path = self.path_for(&Self::key(provider, model, dimension, text));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let bytes = vector
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Shrink the cache to at most `max_bytes`, evicting the least recently
+    /// written entries first, and remove temporary files left behind by
+    /// interrupted writes. A missing cache directory is an empty cache.
+    pub fn prune(&self, max_bytes: u64) -> std::io::Result<PruneStats> {
+        let mut stats = PruneStats::default();
+        let shards = match std::fs::read_dir(&self.dir) {
+            Ok(shards) => shards,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(stats),
+            Err(err) => return Err(err),
+        };
+
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        for shard in shards {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(&shard)? {
+                let path = file?.path();
+                let metadata = std::fs::metadata(&path)?;
+                let modified = metadata.modified()?;
+                if path.extension().is_some_and(|ext| ext == "f32") {
+                    entries.push((modified, metadata.len(), path));
+                } else if now.duration_since(modified).unwrap_or_default() > STALE_TEMP_AGE {
+                    std::fs::remove_file(&path)?;
+                    stats.removed += 1;
+                    stats.removed_bytes += metadata.len();
+                }
+            }
+        }
+
+        entries.sort();
+        stats.entries = entries.len() as u64;
+        stats.bytes = entries.iter().map(|(_, len, _)| len).sum();
+        for (_, len, path) in entries {
+            if stats.bytes <= max_bytes {
+                break;
+            }
+            std::fs::remove_file(&path)?;
+            stats.entries -= 1;
+            stats.bytes -= len;
+            stats.removed += 1;
+            stats.removed_bytes += len;
+        }
+        Ok(stats)
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(format!("{key}.f32"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip_counts_hits_and_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = EmbeddingCache::new(dir.path());
+
+        assert!(cache.get("http", "model", 3, "hello").is_none());
+        cache
+            .put("http", "model", 3, "hello", &[0.5, -1.0, 2.0])
+            .unwrap();
+        assert_eq!(
+            cache.get("http", "model", 3, "hello"),
+            Some(vec![0.5, -1.0, 2.0])
+        );
+        assert!(cache.get("http", "other-model", 3, "hello").is_none());
+        assert!(cache.get("fastembed", "model", 3, "hello").is_none());
+
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+    }
+
+    #[test]
+    fn test_prune_evicts_oldest_entries_until_under_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = EmbeddingCache::new(dir.path());
+        assert_eq!(cache.prune(0).unwrap(), PruneStats::default());
+
+        for text in ["a", "b", "c"] {
+            cache.put("hash", "model", 2, text, &[1.0, 2.0]).unwrap();
+            let path = cache.path_for(&EmbeddingCache::key("hash", "model", 2, text));
+            let age = match text {
+                "a" => 30,
+                "b" => 20,
+                _ => 10,
+            };
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(age))
+                .unwrap();
+        }
+
+        let stats = cache.prune(16).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                entries: 2,
+                bytes: 16,
+                removed: 1,
+                removed_bytes: 8,
+            }
+        );
+        assert!(cache.get("hash", "model", 2, "a").is_none());
+        assert!(cache.get("hash", "model", 2, "b").is_some());
+        assert!(cache.get("hash", "model", 2, "c").is_some());
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_provider_model_dimension_and_text() {
+        let base = EmbeddingCache::key("p", "m", 384, "text");
+        assert_eq!(base, EmbeddingCache::key("p", "m", 384, "text"));
+        assert_ne!(base, EmbeddingCache::key("p2", "m", 384, "text"));
+        assert_ne!(base, EmbeddingCache::key("p", "m2", 384, "text"));
+        assert_ne!(base, EmbeddingCache::key("p", "m", 768, "text"));
+        assert_ne!(base, EmbeddingCache::key("p", "m", 384, "text2"));
+    }
+}
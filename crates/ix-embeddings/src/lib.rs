@@ -3,14 +3,31 @@
 //! Supports multiple backends via feature flags:
 //! - `fastembed` (default): ONNX-based, CPU-only
 //! - `candle`: Hugging Face Candle, supports Metal/CUDA
+//! - `http` (default): any server speaking the `OpenAI` `/v1/embeddings` protocol
+//!
+//! The `hash` provider is always available: it needs no model download and
+//! produces identical vectors on every machine.
+
+mod cache;
+mod models;
+mod rerank;
 
 use ix_config::{EmbeddingConfig, load_shared_config};
+use std::path::PathBuf;
+#[cfg(any(feature = "fastembed", feature = "candle"))]
 use std::sync::Mutex;
 use thiserror::Error;
 
+pub use cache::{CacheStats, EmbeddingCache, PruneStats};
+pub use models::{CHECKSUM_FILE, InstalledModel, ModelStore, ModelVerification};
+pub use rerank::{RerankProvider, Reranker, rerank_model_repo};
+
 #[cfg(feature = "fastembed")]
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 
+#[cfg(feature = "http")]
+use std::time::Duration;
+
 #[cfg(feature = "candle")]
 use candle_core::{Device, Tensor};
 #[cfg(feature = "candle")]
@@ -18,7 +35,10 @@ use candle_nn::VarBuilder;
 #[cfg(feature = "candle")]
 use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
 #[cfg(feature = "candle")]
-use hf_hub::{Repo, RepoType, api::sync::Api};
+use hf_hub::{
+    Repo, RepoType,
+    api::sync::{Api, ApiBuilder},
+};
 #[cfg(feature = "candle")]
 use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams, TruncationStrategy};
 
@@ -53,6 +73,20 @@ pub enum EmbeddingError {
 
     #[error("Provider not available: {provider} (enable the '{feature}' feature)")]
     ProviderNotCompiled { provider: String, feature: String },
+
+    #[error(
+        "Model {model} is not installed in {} (missing {files}) and offline mode is on; \
+         run `ixchel models pull` or `ixchel models import <dir>`",
+        dir.display()
+    )]
+    ModelMissing {
+        model: String,
+        dir: PathBuf,
+        files: String,
+    },
+
+    #[error("Model store error: {0}")]
+    ModelStoreError(String),
 }
 
 pub type Result<T> = std::result::Result<T, EmbeddingError>;
@@ -66,10 +100,83 @@ pub trait EmbeddingProvider: Send + Sync {
     fn batch_size(&self) -> usize {
         1
     }
+
+    /// Instruction prepended to search queries.
+    #[allow(clippy::unnecessary_literal_bound)] // implementors borrow from self
+    fn query_prefix(&self) -> &str {
+        ""
+    }
+
+    /// Instruction prepended to indexed documents.
+    #[allow(clippy::unnecessary_literal_bound)] // implementors borrow from self
+    fn document_prefix(&self) -> &str {
+        ""
+    }
+
+    /// Embed a search query. Asymmetric models (BGE, E5, ...) expect a
+    /// different instruction for queries than for the passages they match.
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(&format!("{}{text}", self.query_prefix()))
+    }
+
+    /// Embed documents for indexing.
+    fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let prefix = self.document_prefix();
+        if prefix.is_empty() {
+            return self.embed_batch(texts);
+        }
+        let prefixed = texts
+            .iter()
+            .map(|text| format!("{prefix}{text}"))
+            .collect::<Vec<_>>();
+        self.embed_batch(&prefixed.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+/// Query and document instructions for a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingPrefixes {
+    pub query: String,
+    pub document: String,
+}
+
+impl EmbeddingPrefixes {
+    /// The instructions each model family was trained with.
+    #[must_use]
+    pub fn for_model(model: &str) -> Self {
+        let token = normalize_model_token(model);
+        let (query, document) = if token.contains("nomicembed") {
+            ("search_query: ", "search_document: ")
+        } else if token.contains("bge") && token.contains("en") {
+            (
+                "Represent this sentence for searching relevant passages: ",
+                "",
+            )
+        } else if token.contains("e5") {
+            ("query: ", "passage: ")
+        } else {
+            ("", "")
+        };
+        Self {
+            query: query.to_string(),
+            document: document.to_string(),
+        }
+    }
+
+    /// Model defaults with `query_prefix` / `document_prefix` overrides applied.
+    #[must_use]
+    pub fn from_config(config: &EmbeddingConfig, model: &str) -> Self {
+        let defaults = Self::for_model(model);
+        Self {
+            query: config.query_prefix.clone().unwrap_or(defaults.query),
+            document: config.document_prefix.clone().unwrap_or(defaults.document),
+        }
+    }
 }
 
 pub struct Embedder {
     provider: Box<dyn EmbeddingProvider>,
+    cache: Option<EmbeddingCache>,
 }
 
 impl Embedder {
@@ -81,20 +188,117 @@ impl Embedder {
     }
 
     pub fn with_config(config: &EmbeddingConfig) -> Result<Self> {
-        let provider = provider_from_config(config)?;
-        Ok(Self { provider })
+        let provider = provider_from_config(config, None)?;
+        Ok(Self::from_provider(provider))
+    }
+
+    /// Load models from `store` instead of the providers' default caches.
+    pub fn with_model_store(config: &EmbeddingConfig, store: &ModelStore) -> Result<Self> {
+        let provider = provider_from_config(config, Some(store))?;
+        Ok(Self::from_provider(provider))
     }
 
     pub fn from_provider(provider: Box<dyn EmbeddingProvider>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            cache: None,
+        }
+    }
+
+    /// Reuse vectors persisted under `dir` and store new ones there.
+    #[must_use]
+    pub fn with_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = Some(EmbeddingCache::new(dir));
+        self
     }
 
     pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        self.provider.embed(text)
+        let vectors = self.cached(&[text], |_| Ok(vec![self.provider.embed(text)?]))?;
+        vectors
+            .into_iter()
+            .next()
+            .ok_or(EmbeddingError::EmptyResult)
     }
 
     pub fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
-        self.provider.embed_batch(texts)
+        self.cached(texts, |missing| {
+            let inputs = missing.iter().map(|&idx| texts[idx]).collect::<Vec<_>>();
+            self.provider.embed_batch(&inputs)
+        })
+    }
+
+    /// Embed a search query with the model's query instruction.
+    pub fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let key = format!("{}{text}", self.query_prefix());
+        let vectors = self.cached(&[key.as_str()], |_| {
+            Ok(vec![self.provider.embed_query(text)?])
+        })?;
+        vectors
+            .into_iter()
+            .next()
+            .ok_or(EmbeddingError::EmptyResult)
+    }
+
+    /// Embed documents for indexing with the model's document instruction.
+    pub fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let prefix = self.document_prefix();
+        let keys = texts
+            .iter()
+            .map(|text| format!("{prefix}{text}"))
+            .collect::<Vec<_>>();
+        let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+        self.cached(&keys, |missing| {
+            let inputs = missing.iter().map(|&idx| texts[idx]).collect::<Vec<_>>();
+            self.provider.embed_documents(&inputs)
+        })
+    }
+
+    /// Look up `keys` (the exact model input) in the cache and compute the
+    /// misses, given by index, with `compute`.
+    fn cached<F>(&self, keys: &[&str], compute: F) -> Result<Vec<Vec<f32>>>
+    where
+        F: FnOnce(&[usize]) -> Result<Vec<Vec<f32>>>,
+    {
+        let Some(cache) = &self.cache else {
+            return compute(&(0..keys.len()).collect::<Vec<_>>());
+        };
+
+        let (provider, model, dimension) =
+            (self.provider_name(), self.model_name(), self.dimension());
+        let mut vectors = keys
+            .iter()
+            .map(|key| cache.get(provider, model, dimension, key))
+            .collect::<Vec<_>>();
+        let missing = (0..keys.len())
+            .filter(|&idx| vectors[idx].is_none())
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            let embedded = compute(&missing)?;
+            if embedded.len() != missing.len() {
+                return Err(EmbeddingError::EmbedError(format!(
+                    "provider returned {} embeddings for {} inputs",
+                    embedded.len(),
+                    missing.len()
+                )));
+            }
+            for (idx, vector) in missing.into_iter().zip(embedded) {
+                // The cache is best-effort; a failed write only costs a recompute later.
+                let _ = cache.put(provider, model, dimension, keys[idx], &vector);
+                vectors[idx] = Some(vector);
+            }
+        }
+
+        Ok(vectors.into_iter().flatten().collect())
+    }
+
+    /// Cache hit/miss counts so far; zero when no cache is configured.
+    #[must_use]
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache
+            .as_ref()
+            .map(EmbeddingCache::stats)
+            .unwrap_or_default()
     }
 
     #[must_use]
@@ -116,6 +320,136 @@ impl Embedder {
     pub fn provider_name(&self) -> &'static str {
         self.provider.provider_name()
     }
+
+    #[must_use]
+    pub fn query_prefix(&self) -> &str {
+        self.provider.query_prefix()
+    }
+
+    #[must_use]
+    pub fn document_prefix(&self) -> &str {
+        self.provider.document_prefix()
+    }
+}
+
+// =============================================================================
+// Hash Provider
+// =============================================================================
+
+pub const HASH_MODEL_NAME: &str = "hash-v1";
+pub const DEFAULT_HASH_DIMENSION: usize = 384;
+
+const HASH_WORD_WEIGHT: f32 = 1.0;
+const HASH_BIGRAM_WEIGHT: f32 = 0.5;
+const HASH_TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Deterministic feature-hashing provider.
+///
+/// Lowercased words, adjacent word pairs and character trigrams are hashed
+/// with blake3 into a signed bucket, and the result is L2-normalized. Vectors
+/// depend only on the text and dimension, so sync and search results are
+/// reproducible without downloading a model.
+pub struct HashEmbeddingProvider {
+    dimension: usize,
+    batch_size: usize,
+    prefixes: EmbeddingPrefixes,
+}
+
+impl HashEmbeddingProvider {
+    #[must_use]
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension: dimension.max(1),
+            batch_size: 1,
+            prefixes: EmbeddingPrefixes::default(),
+        }
+    }
+
+    fn from_config(config: &EmbeddingConfig) -> Self {
+        Self {
+            batch_size: config.batch_size.max(1),
+            prefixes: EmbeddingPrefixes::from_config(config, HASH_MODEL_NAME),
+            ..Self::new(config.dimension.unwrap_or(DEFAULT_HASH_DIMENSION))
+        }
+    }
+
+    fn embed_impl(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimension];
+        let words = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        for word in &words {
+            self.add_feature(&mut vector, "w", word, HASH_WORD_WEIGHT);
+
+            let chars = format!("<{word}>").chars().collect::<Vec<_>>();
+            for trigram in chars.windows(3) {
+                let trigram = trigram.iter().collect::<String>();
+                self.add_feature(&mut vector, "c", &trigram, HASH_TRIGRAM_WEIGHT);
+            }
+        }
+        for pair in words.windows(2) {
+            let bigram = format!("{} {}", pair[0], pair[1]);
+            self.add_feature(&mut vector, "b", &bigram, HASH_BIGRAM_WEIGHT);
+        }
+
+        l2_normalize(&mut vector);
+        vector
+    }
+
+    fn add_feature(&self, vector: &mut [f32], namespace: &str, feature: &str, weight: f32) {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(namespace.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(feature.as_bytes());
+        let hash = hasher.finalize();
+        let bytes = hash.as_bytes();
+
+        let mut bucket = [0u8; 8];
+        bucket.copy_from_slice(&bytes[..8]);
+        let idx = u64::from_le_bytes(bucket) % self.dimension as u64;
+        let sign = if bytes[8] & 1 == 0 { 1.0 } else { -1.0 };
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            vector[idx as usize] += sign * weight;
+        }
+    }
+}
+
+impl EmbeddingProvider for HashEmbeddingProvider {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_impl(text))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_impl(text)).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn model_name(&self) -> &'static str {
+        HASH_MODEL_NAME
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "hash"
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn query_prefix(&self) -> &str {
+        &self.prefixes.query
+    }
+
+    fn document_prefix(&self) -> &str {
+        &self.prefixes.document
+    }
 }
 
 // =============================================================================
@@ -128,13 +462,14 @@ struct FastEmbedProvider {
     model_name: String,
     dimension: usize,
     batch_size: usize,
+    prefixes: EmbeddingPrefixes,
 }
 
 #[cfg(feature = "fastembed")]
 impl FastEmbedProvider {
-    fn new(config: &EmbeddingConfig) -> Result<Self> {
+    fn new(config: &EmbeddingConfig, store: Option<&ModelStore>) -> Result<Self> {
         let embedding_model = fastembed_model_from_string(&config.model)?;
-        let (model_name, dimension) = {
+        let (model_name, dimension, options) = {
             let model_info = TextEmbedding::get_model_info(&embedding_model)
                 .map_err(|e| EmbeddingError::UnknownModel(format!("{}: {e}", config.model)))?;
 
@@ -148,14 +483,25 @@ impl FastEmbedProvider {
                 });
             }
 
-            (model_info.model_code.clone(), model_info.dim)
+            let mut options = InitOptions::new(embedding_model.clone());
+            if let Some(store) = store {
+                store.require_fastembed_offline(
+                    &model_info.model_code,
+                    &model_info.model_file,
+                    &model_info.additional_files,
+                )?;
+                options = options.with_cache_dir(store.dir().to_path_buf());
+            }
+
+            (model_info.model_code.clone(), model_info.dim, options)
         };
 
-        let model = TextEmbedding::try_new(InitOptions::new(embedding_model))
+        let model = TextEmbedding::try_new(options)
             .map_err(|e| EmbeddingError::InitError(e.to_string()))?;
 
         Ok(Self {
             model: Mutex::new(model),
+            prefixes: EmbeddingPrefixes::from_config(config, &model_name),
             model_name,
             dimension,
             batch_size: config.batch_size.max(1),
@@ -226,6 +572,14 @@ impl EmbeddingProvider for FastEmbedProvider {
     fn batch_size(&self) -> usize {
         self.batch_size
     }
+
+    fn query_prefix(&self) -> &str {
+        &self.prefixes.query
+    }
+
+    fn document_prefix(&self) -> &str {
+        &self.prefixes.document
+    }
 }
 
 #[cfg(feature = "fastembed")]
@@ -290,6 +644,218 @@ fn l2_normalize(embedding: &mut [f32]) {
     }
 }
 
+// =============================================================================
+// HTTP Provider
+// =============================================================================
+
+#[cfg(feature = "http")]
+const DEFAULT_HTTP_BASE_URL: &str = "http://localhost:11434/v1";
+
+#[cfg(feature = "http")]
+const HTTP_RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+#[cfg(feature = "http")]
+const HTTP_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Delay before retry `attempt` (from 0): doubling from
+/// [`HTTP_RETRY_BACKOFF`], capped at [`HTTP_MAX_BACKOFF`].
+#[cfg(feature = "http")]
+fn retry_backoff(attempt: u32) -> Duration {
+    HTTP_RETRY_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(HTTP_MAX_BACKOFF)
+}
+
+/// Client for OpenAI-compatible embedding servers (Ollama, llama.cpp, vLLM, ...).
+#[cfg(feature = "http")]
+struct HttpProvider {
+    agent: ureq::Agent,
+    endpoint: String,
+    api_key: Option<String>,
+    model_name: String,
+    dimension: usize,
+    batch_size: usize,
+    max_retries: u32,
+    prefixes: EmbeddingPrefixes,
+}
+
+#[cfg(feature = "http")]
+impl HttpProvider {
+    fn new(config: &EmbeddingConfig) -> Result<Self> {
+        let base_url = config
+            .base_url
+            .as_deref()
+            .unwrap_or(DEFAULT_HTTP_BASE_URL)
+            .trim_end_matches('/');
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build();
+
+        let mut provider = Self {
+            agent,
+            endpoint: format!("{base_url}/embeddings"),
+            api_key: config.api_key.clone().filter(|key| !key.is_empty()),
+            model_name: config.model.clone(),
+            dimension: config.dimension.unwrap_or(0),
+            batch_size: config.batch_size.max(1),
+            max_retries: config.max_retries,
+            prefixes: EmbeddingPrefixes::from_config(config, &config.model),
+        };
+
+        // Servers don't advertise dimensions, so detect it from a first response.
+        if provider.dimension == 0 {
+            let probe = provider
+                .request(&["dimension probe"])
+                .map_err(|e| EmbeddingError::InitError(e.to_string()))?;
+            provider.dimension = probe
+                .first()
+                .map(Vec::len)
+                .filter(|dim| *dim > 0)
+                .ok_or(EmbeddingError::EmptyResult)?;
+        }
+
+        Ok(provider)
+    }
+
+    fn request(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let body = serde_json::json!({
+            "model": self.model_name,
+            "input": texts,
+        });
+
+        let mut attempt = 0;
+        loop {
+            let mut request = self.agent.post(&self.endpoint);
+            if let Some(key) = &self.api_key {
+                request = request.set("Authorization", &format!("Bearer {key}"));
+            }
+
+            match request.send_json(&body) {
+                Ok(response) => {
+                    let value: serde_json::Value = response.into_json().map_err(|e| {
+                        EmbeddingError::EmbedError(format!("{}: {e}", self.endpoint))
+                    })?;
+                    return parse_openai_embeddings(&value, texts.len());
+                }
+                Err(err) if attempt < self.max_retries && is_retryable(&err) => {
+                    std::thread::sleep(retry_backoff(attempt));
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(EmbeddingError::ProviderUnavailable(format!(
+                        "POST {}: {err}",
+                        self.endpoint
+                    )));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl EmbeddingProvider for HttpProvider {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text])?
+            .into_iter()
+            .next()
+            .ok_or(EmbeddingError::EmptyResult)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            for embedding in self.request(batch)? {
+                if embedding.len() != self.dimension {
+                    return Err(EmbeddingError::DimensionMismatch {
+                        model: self.model_name.clone(),
+                        expected: embedding.len(),
+                        configured: self.dimension,
+                    });
+                }
+                embeddings.push(embedding);
+            }
+        }
+        Ok(embeddings)
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "http"
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn query_prefix(&self) -> &str {
+        &self.prefixes.query
+    }
+
+    fn document_prefix(&self) -> &str {
+        &self.prefixes.document
+    }
+}
+
+/// Rate limits and server errors are worth retrying; other statuses are not.
+#[cfg(feature = "http")]
+const fn is_retryable(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(code, _) => *code == 429 || *code >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
+
+/// Parse `{"data": [{"index": 0, "embedding": [...]}, ...]}` into input order.
+#[cfg(feature = "http")]
+fn parse_openai_embeddings(value: &serde_json::Value, expected: usize) -> Result<Vec<Vec<f32>>> {
+    let data = value
+        .get("data")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| EmbeddingError::EmbedError("response has no data array".to_string()))?;
+
+    let mut indexed = Vec::with_capacity(data.len());
+    for (position, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|idx| usize::try_from(idx).ok())
+            .unwrap_or(position);
+        let values = item
+            .get("embedding")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| EmbeddingError::EmbedError("response item has no embedding".into()))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut embedding = values
+            .iter()
+            .map(|v| v.as_f64().map(|f| f as f32))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| EmbeddingError::EmbedError("embedding is not numeric".into()))?;
+        l2_normalize(&mut embedding);
+        indexed.push((index, embedding));
+    }
+
+    if indexed.len() != expected {
+        return Err(EmbeddingError::EmbedError(format!(
+            "server returned {} embeddings for {expected} inputs",
+            indexed.len()
+        )));
+    }
+
+    indexed.sort_by_key(|(index, _)| *index);
+    Ok(indexed
+        .into_iter()
+        .map(|(_, embedding)| embedding)
+        .collect())
+}
+
 // =============================================================================
 // Candle Provider
 // =============================================================================
@@ -302,19 +868,16 @@ struct CandleProvider {
     model_name: String,
     dimension: usize,
     batch_size: usize,
+    prefixes: EmbeddingPrefixes,
 }
 
 #[cfg(feature = "candle")]
 impl CandleProvider {
-    fn new(config: &EmbeddingConfig) -> Result<Self> {
+    fn new(config: &EmbeddingConfig, store: Option<&ModelStore>) -> Result<Self> {
         let device = Self::select_device();
-        let model_id = if config.model.is_empty() {
-            "sentence-transformers/all-MiniLM-L6-v2"
-        } else {
-            &config.model
-        };
+        let model_id = candle_model_id(config);
 
-        let (model, tokenizer, dimension) = Self::load_model(model_id, &device)?;
+        let (model, tokenizer, dimension) = Self::load_model(model_id, &device, store)?;
 
         if let Some(configured_dim) = config.dimension
             && configured_dim != dimension
@@ -333,6 +896,7 @@ impl CandleProvider {
             model_name: model_id.to_string(),
             dimension,
             batch_size: config.batch_size.max(1),
+            prefixes: EmbeddingPrefixes::from_config(config, model_id),
         })
     }
 
@@ -352,8 +916,28 @@ impl CandleProvider {
         }
     }
 
-    fn load_model(model_id: &str, device: &Device) -> Result<(BertModel, Tokenizer, usize)> {
-        let api = Api::new().map_err(|e| EmbeddingError::InitError(e.to_string()))?;
+    fn load_model(
+        model_id: &str,
+        device: &Device,
+        store: Option<&ModelStore>,
+    ) -> Result<(BertModel, Tokenizer, usize)> {
+        let api = match store {
+            Some(store) => {
+                store.require_offline(
+                    model_id,
+                    &[
+                        "config.json",
+                        "tokenizer.json",
+                        "model.safetensors|pytorch_model.bin",
+                    ],
+                )?;
+                ApiBuilder::new()
+                    .with_cache_dir(store.dir().to_path_buf())
+                    .build()
+            }
+            None => Api::new(),
+        }
+        .map_err(|e| EmbeddingError::InitError(e.to_string()))?;
         let repo = api.repo(Repo::new(model_id.to_string(), RepoType::Model));
 
         // Download model files
@@ -591,17 +1175,34 @@ impl EmbeddingProvider for CandleProvider {
     fn batch_size(&self) -> usize {
         self.batch_size
     }
+
+    fn query_prefix(&self) -> &str {
+        &self.prefixes.query
+    }
+
+    fn document_prefix(&self) -> &str {
+        &self.prefixes.document
+    }
 }
 
 // =============================================================================
 // Provider Factory
 // =============================================================================
 
-fn provider_from_config(config: &EmbeddingConfig) -> Result<Box<dyn EmbeddingProvider>> {
+#[cfg_attr(
+    not(any(feature = "fastembed", feature = "candle")),
+    allow(unused_variables)
+)]
+fn provider_from_config(
+    config: &EmbeddingConfig,
+    store: Option<&ModelStore>,
+) -> Result<Box<dyn EmbeddingProvider>> {
     let provider = config.provider.trim().to_lowercase();
     match provider.as_str() {
+        "hash" => Ok(Box::new(HashEmbeddingProvider::from_config(config))),
+
         #[cfg(feature = "fastembed")]
-        "fastembed" | "fastembed-rs" => Ok(Box::new(FastEmbedProvider::new(config)?)),
+        "fastembed" | "fastembed-rs" => Ok(Box::new(FastEmbedProvider::new(config, store)?)),
 
         #[cfg(not(feature = "fastembed"))]
         "fastembed" | "fastembed-rs" => Err(EmbeddingError::ProviderNotCompiled {
@@ -610,7 +1211,7 @@ fn provider_from_config(config: &EmbeddingConfig) -> Result<Box<dyn EmbeddingPro
         }),
 
         #[cfg(feature = "candle")]
-        "candle" | "candle-rs" => Ok(Box::new(CandleProvider::new(config)?)),
+        "candle" | "candle-rs" => Ok(Box::new(CandleProvider::new(config, store)?)),
 
         #[cfg(not(feature = "candle"))]
         "candle" | "candle-rs" => Err(EmbeddingError::ProviderNotCompiled {
@@ -618,10 +1219,54 @@ fn provider_from_config(config: &EmbeddingConfig) -> Result<Box<dyn EmbeddingPro
             feature: "candle".to_string(),
         }),
 
+        #[cfg(feature = "http")]
+        "http" | "openai" => Ok(Box::new(HttpProvider::new(config)?)),
+
+        #[cfg(not(feature = "http"))]
+        "http" | "openai" => Err(EmbeddingError::ProviderNotCompiled {
+            provider: "http".to_string(),
+            feature: "http".to_string(),
+        }),
+
         _ => Err(EmbeddingError::UnknownProvider(config.provider.clone())),
     }
 }
 
+/// Hub repository the configured provider downloads its model from, or
+/// `None` for providers without local model files (hash, http).
+pub fn embedding_model_repo(config: &EmbeddingConfig) -> Result<Option<String>> {
+    let provider = config.provider.trim().to_lowercase();
+    match provider.as_str() {
+        "hash" | "http" | "openai" => Ok(None),
+
+        #[cfg(feature = "fastembed")]
+        "fastembed" | "fastembed-rs" => {
+            let model = fastembed_model_from_string(&config.model)?;
+            let info = TextEmbedding::get_model_info(&model)
+                .map_err(|e| EmbeddingError::UnknownModel(format!("{}: {e}", config.model)))?;
+            Ok(Some(info.model_code.clone()))
+        }
+
+        #[cfg(not(feature = "fastembed"))]
+        "fastembed" | "fastembed-rs" => Err(EmbeddingError::ProviderNotCompiled {
+            provider: "fastembed".to_string(),
+            feature: "fastembed".to_string(),
+        }),
+
+        "candle" | "candle-rs" => Ok(Some(candle_model_id(config).to_string())),
+
+        _ => Err(EmbeddingError::UnknownProvider(config.provider.clone())),
+    }
+}
+
+fn candle_model_id(config: &EmbeddingConfig) -> &str {
+    if config.model.is_empty() {
+        "sentence-transformers/all-MiniLM-L6-v2"
+    } else {
+        &config.model
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -659,6 +1304,124 @@ mod tests {
         assert!(embeddings.iter().all(|e| e.len() == embedder.dimension()));
     }
 
+    #[test]
+    fn test_embedder_cache_reuses_vectors_across_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let first =
+            Embedder::from_provider(Box::new(HashEmbeddingProvider::new(8))).with_cache(dir.path());
+        let vectors = first.embed_batch(&["alpha", "beta"]).unwrap();
+        assert_eq!(first.cache_stats(), CacheStats { hits: 0, misses: 2 });
+
+        let second =
+            Embedder::from_provider(Box::new(HashEmbeddingProvider::new(8))).with_cache(dir.path());
+        assert_eq!(
+            second.embed_batch(&["beta", "alpha", "gamma"]).unwrap()[..2],
+            [vectors[1].clone(), vectors[0].clone()]
+        );
+        assert_eq!(second.embed("alpha").unwrap(), vectors[0]);
+        assert_eq!(second.cache_stats(), CacheStats { hits: 3, misses: 1 });
+    }
+
+    fn hash_config(dimension: usize) -> EmbeddingConfig {
+        EmbeddingConfig {
+            provider: "hash".to_string(),
+            dimension: Some(dimension),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hash_provider_is_deterministic_and_normalized() {
+        let embedder = Embedder::with_config(&hash_config(64)).unwrap();
+        assert_eq!(embedder.provider_name(), "hash");
+        assert_eq!(embedder.model_name(), HASH_MODEL_NAME);
+        assert_eq!(embedder.dimension(), 64);
+
+        let first = embedder.embed("Redis keys expire hourly").unwrap();
+        let second = embedder.embed("redis KEYS expire hourly").unwrap();
+        assert_eq!(first.len(), 64);
+        assert_eq!(first, second);
+
+        let norm = first.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5, "norm was {norm}");
+
+        let empty = embedder.embed("").unwrap();
+        assert!(empty.iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_hash_provider_ranks_overlapping_text_higher() {
+        let embedder = Embedder::with_config(&hash_config(256)).unwrap();
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+
+        let query = embedder.embed("cache expiry").unwrap();
+        let related = embedder.embed("redis cache keys expire").unwrap();
+        let unrelated = embedder.embed("postgres migrations").unwrap();
+        assert!(dot(&query, &related) > dot(&query, &unrelated));
+    }
+
+    #[test]
+    fn test_hash_provider_defaults_dimension() {
+        let config = EmbeddingConfig {
+            provider: "hash".to_string(),
+            ..Default::default()
+        };
+        let embedder = Embedder::with_config(&config).unwrap();
+        assert_eq!(embedder.dimension(), DEFAULT_HASH_DIMENSION);
+        assert_eq!(embedder.batch_size(), config.batch_size);
+    }
+
+    #[test]
+    fn test_prefixes_follow_model_family_and_config() {
+        let bge = EmbeddingPrefixes::for_model("BAAI/bge-small-en-v1.5");
+        assert!(bge.query.starts_with("Represent this sentence"));
+        assert!(bge.document.is_empty());
+
+        let e5 = EmbeddingPrefixes::for_model("intfloat/e5-small-v2");
+        assert_eq!(
+            (e5.query.as_str(), e5.document.as_str()),
+            ("query: ", "passage: ")
+        );
+
+        let nomic = EmbeddingPrefixes::for_model("nomic-embed-text");
+        assert_eq!(nomic.document, "search_document: ");
+
+        let minilm = EmbeddingPrefixes::for_model("sentence-transformers/all-MiniLM-L6-v2");
+        assert_eq!(minilm, EmbeddingPrefixes::default());
+
+        let config = EmbeddingConfig {
+            query_prefix: Some(String::new()),
+            document_prefix: Some("doc: ".to_string()),
+            ..Default::default()
+        };
+        let overridden = EmbeddingPrefixes::from_config(&config, "BAAI/bge-small-en-v1.5");
+        assert_eq!(overridden.query, "");
+        assert_eq!(overridden.document, "doc: ");
+    }
+
+    #[test]
+    fn test_embed_query_and_documents_apply_prefixes() {
+        let config = EmbeddingConfig {
+            query_prefix: Some("query: ".to_string()),
+            document_prefix: Some("passage: ".to_string()),
+            ..hash_config(64)
+        };
+        let embedder = Embedder::with_config(&config).unwrap();
+
+        assert_eq!(
+            embedder.embed_query("redis").unwrap(),
+            embedder.embed("query: redis").unwrap()
+        );
+        assert_eq!(
+            embedder.embed_documents(&["redis"]).unwrap(),
+            vec![embedder.embed("passage: redis").unwrap()]
+        );
+        assert_ne!(
+            embedder.embed_query("redis").unwrap(),
+            embedder.embed_documents(&["redis"]).unwrap()[0]
+        );
+    }
+
     #[test]
     fn test_embed_batch_empty() {
         let config = EmbeddingConfig::default();
@@ -728,4 +1491,141 @@ mod tests {
         assert_eq!(embeddings.len(), 2);
         assert!(embeddings.iter().all(|e| e.len() == 1024));
     }
+
+    /// Serve one canned response per status, embedding each input as
+    /// `[len, 1, 0]`, and return the JSON request bodies received.
+    #[cfg(feature = "http")]
+    fn spawn_embedding_server(
+        statuses: Vec<u16>,
+    ) -> (String, std::thread::JoinHandle<Vec<serde_json::Value>>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body = loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    raw.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&raw).to_string();
+                    let Some(header_end) = text.find("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length = text[..header_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if raw.len() >= header_end + 4 + length {
+                        break raw[header_end + 4..header_end + 4 + length].to_vec();
+                    }
+                };
+
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let payload = if status == 200 {
+                    let data = request["input"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .enumerate()
+                        .map(|(index, input)| {
+                            let len = input.as_str().unwrap().len();
+                            serde_json::json!({ "index": index, "embedding": [len, 1, 0] })
+                        })
+                        .collect::<Vec<_>>();
+                    serde_json::json!({ "data": data }).to_string()
+                } else {
+                    String::new()
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                )
+                .unwrap();
+                bodies.push(request);
+            }
+            bodies
+        });
+
+        (base_url, handle)
+    }
+
+    #[cfg(feature = "http")]
+    fn http_config(base_url: String, dimension: Option<usize>) -> EmbeddingConfig {
+        EmbeddingConfig {
+            provider: "http".to_string(),
+            model: "nomic-embed-text".to_string(),
+            batch_size: 2,
+            dimension,
+            base_url: Some(base_url),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_provider_detects_dimension_and_batches() {
+        let (base_url, server) = spawn_embedding_server(vec![200, 200, 200]);
+        let embedder = Embedder::with_config(&http_config(base_url, None)).unwrap();
+        assert_eq!(embedder.provider_name(), "http");
+        assert_eq!(embedder.dimension(), 3);
+
+        let embeddings = embedder.embed_batch(&["a", "bb", "ccc"]).unwrap();
+        assert_eq!(embeddings.len(), 3);
+        for embedding in &embeddings {
+            let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+        }
+        assert!(embeddings[2][0] > embeddings[0][0]);
+
+        let requests = server.join().unwrap();
+        let batch_sizes = requests
+            .iter()
+            .map(|r| r["input"].as_array().unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(batch_sizes, vec![1, 2, 1]);
+        assert!(requests.iter().all(|r| r["model"] == "nomic-embed-text"));
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_provider_retries_server_errors() {
+        let (base_url, server) = spawn_embedding_server(vec![503, 200]);
+        let embedder = Embedder::with_config(&http_config(base_url, Some(3))).unwrap();
+
+        assert_eq!(embedder.embed("hello").unwrap().len(), 3);
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_retry_backoff_doubles_and_saturates() {
+        assert_eq!(retry_backoff(0), HTTP_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(2), HTTP_RETRY_BACKOFF * 4);
+        assert_eq!(retry_backoff(40), HTTP_MAX_BACKOFF);
+        assert_eq!(retry_backoff(u32::MAX), HTTP_MAX_BACKOFF);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_provider_rejects_unexpected_dimension() {
+        let (base_url, server) = spawn_embedding_server(vec![200]);
+        let embedder = Embedder::with_config(&http_config(base_url, Some(4))).unwrap();
+
+        let err = embedder.embed("hello").unwrap_err();
+        assert!(
+            matches!(err, EmbeddingError::DimensionMismatch { .. }),
+            "{err}"
+        );
+        server.join().unwrap();
+    }
 }
@@ -0,0 +1,511 @@
+//! Local model files for offline use.
+//!
+//! Models live in a Hugging Face hub cache layout
+//! (`models--<org>--<name>/{blobs,refs,snapshots}`), which both `fastembed`
+//! and `hf-hub` read before touching the network. A `ixchel.b3sums` file in
+//! each model directory records the blake3 checksum of every file so copies
+//! carried to air-gapped machines can be verified.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ix_config::ModelsConfig;
+
+use crate::{EmbeddingError, Result};
+
+/// Per-model checksum manifest, one `<blake3>  <size>  <path>` line per file.
+pub const CHECKSUM_FILE: &str = "ixchel.b3sums";
+
+/// Revision recorded for models imported from a plain directory.
+const IMPORTED_REVISION: &str = "imported";
+
+const FOLDER_PREFIX: &str = "models--";
+
+/// Files `fastembed` loads next to a model's weights. The tokenizer configs
+/// are fetched through `hf-hub` along with `tokenizer.json`, so offline mode
+/// has to find them too.
+const FASTEMBED_TOKENIZER_FILES: &[&str] = &[
+    "tokenizer.json",
+    "config.json",
+    "special_tokens_map.json",
+    "tokenizer_config.json",
+];
+
+/// A model directory found in a [`ModelStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledModel {
+    /// Hub repository, e.g. `Qdrant/bge-small-en-v1.5-onnx-Q`.
+    pub repo_id: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub files: usize,
+    /// blake3 of the checksum manifest; `None` until checksums are recorded.
+    pub checksum: Option<String>,
+}
+
+/// Result of re-hashing a model against its checksum manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelVerification {
+    pub repo_id: String,
+    /// Files listed in the manifest.
+    pub checked: usize,
+    /// Files whose size or checksum changed.
+    pub mismatched: Vec<String>,
+    /// Files listed in the manifest but gone from disk.
+    pub missing: Vec<String>,
+    /// No manifest was recorded, so nothing could be checked.
+    pub unrecorded: bool,
+}
+
+impl ModelVerification {
+    #[must_use]
+    pub const fn is_ok(&self) -> bool {
+        !self.unrecorded && self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Directory of locally installed models, e.g. `.ixchel/models/`.
+#[derive(Debug, Clone)]
+pub struct ModelStore {
+    dir: PathBuf,
+    offline: bool,
+}
+
+impl ModelStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            offline: false,
+        }
+    }
+
+    /// The store at `dir`, offline when `[models].offline` is set.
+    pub fn from_config(dir: impl Into<PathBuf>, config: &ModelsConfig) -> Self {
+        Self::new(dir).with_offline(config.offline)
+    }
+
+    /// In offline mode providers never download and fail fast when a model
+    /// is missing.
+    #[must_use]
+    pub const fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    #[must_use]
+    pub const fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Directory holding `repo_id`, e.g. `models--BAAI--bge-small-en-v1.5`.
+    #[must_use]
+    pub fn model_dir(&self, repo_id: &str) -> PathBuf {
+        self.dir
+            .join(format!("{FOLDER_PREFIX}{}", repo_id.replace('/', "--")))
+    }
+
+    /// Path of `filename` in the snapshot `refs/main` points at.
+    #[must_use]
+    pub fn file(&self, repo_id: &str, filename: &str) -> Option<PathBuf> {
+        let model_dir = self.model_dir(repo_id);
+        let revision = fs::read_to_string(model_dir.join("refs").join("main")).ok()?;
+        let path = model_dir
+            .join("snapshots")
+            .join(revision.trim())
+            .join(filename);
+        path.exists().then_some(path)
+    }
+
+    /// In offline mode, fail unless every file in `files` is installed; each
+    /// entry may list alternatives separated by `|`.
+    pub fn require_offline(&self, repo_id: &str, files: &[&str]) -> Result<()> {
+        if !self.offline {
+            return Ok(());
+        }
+        let missing = files
+            .iter()
+            .filter(|alternatives| {
+                !alternatives
+                    .split('|')
+                    .any(|file| self.file(repo_id, file).is_some())
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(EmbeddingError::ModelMissing {
+            model: repo_id.to_string(),
+            dir: self.dir.clone(),
+            files: missing.join(", "),
+        })
+    }
+
+    /// [`Self::require_offline`] for a `fastembed` model: its weights, any
+    /// additional files and the tokenizer files loaded alongside them.
+    pub fn require_fastembed_offline(
+        &self,
+        repo_id: &str,
+        model_file: &str,
+        additional_files: &[String],
+    ) -> Result<()> {
+        let mut files = vec![model_file];
+        files.extend(FASTEMBED_TOKENIZER_FILES);
+        files.extend(additional_files.iter().map(String::as_str));
+        self.require_offline(repo_id, &files)
+    }
+
+    /// Installed models, sorted by repository.
+    pub fn list(&self) -> Result<Vec<InstalledModel>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(store_error(&self.dir, &err)),
+        };
+
+        let mut models = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| store_error(&self.dir, &e))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(repo_id) = repo_id_from_folder(&name) else {
+                continue;
+            };
+            if entry.path().is_dir() {
+                models.push(self.installed(&repo_id)?);
+            }
+        }
+        models.sort_by(|a, b| a.repo_id.cmp(&b.repo_id));
+        Ok(models)
+    }
+
+    /// Size, file count and recorded checksum of `repo_id`.
+    pub fn installed(&self, repo_id: &str) -> Result<InstalledModel> {
+        let path = self.model_dir(repo_id);
+        let files = model_files(&path)?;
+        let checksum = match fs::read(path.join(CHECKSUM_FILE)) {
+            Ok(manifest) => Some(blake3::hash(&manifest).to_hex().to_string()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(store_error(&path, &err)),
+        };
+        Ok(InstalledModel {
+            repo_id: repo_id.to_string(),
+            size_bytes: files.iter().map(|(_, size)| size).sum(),
+            files: files.len(),
+            checksum,
+            path,
+        })
+    }
+
+    /// Hash every file of `repo_id` into its checksum manifest.
+    pub fn record_checksums(&self, repo_id: &str) -> Result<InstalledModel> {
+        let path = self.model_dir(repo_id);
+        let mut manifest = String::new();
+        for (relative, size) in model_files(&path)? {
+            let hash = hash_file(&path.join(&relative))?;
+            let _ = writeln!(manifest, "{hash}  {size}  {relative}");
+        }
+        let manifest_path = path.join(CHECKSUM_FILE);
+        fs::write(&manifest_path, manifest).map_err(|e| store_error(&manifest_path, &e))?;
+        self.installed(repo_id)
+    }
+
+    /// Re-hash `repo_id` and compare against its checksum manifest.
+    pub fn verify(&self, repo_id: &str) -> Result<ModelVerification> {
+        let path = self.model_dir(repo_id);
+        let mut report = ModelVerification {
+            repo_id: repo_id.to_string(),
+            ..ModelVerification::default()
+        };
+        let manifest = match fs::read_to_string(path.join(CHECKSUM_FILE)) {
+            Ok(manifest) => manifest,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                report.unrecorded = true;
+                return Ok(report);
+            }
+            Err(err) => return Err(store_error(&path, &err)),
+        };
+
+        for line in manifest.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.splitn(3, "  ");
+            let (Some(hash), Some(size), Some(relative)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(store_error(
+                    &path.join(CHECKSUM_FILE),
+                    &io::Error::new(io::ErrorKind::InvalidData, format!("bad line: {line}")),
+                ));
+            };
+            report.checked += 1;
+
+            let file = path.join(relative);
+            match fs::metadata(&file) {
+                Ok(meta) if meta.len().to_string() != size => {
+                    report.mismatched.push(relative.to_string());
+                }
+                Ok(_) => {
+                    if hash_file(&file)? != hash {
+                        report.mismatched.push(relative.to_string());
+                    }
+                }
+                Err(_) => report.missing.push(relative.to_string()),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Copy a pre-downloaded model into the store and record its checksums.
+    ///
+    /// `source` is either a hub cache directory (`models--<org>--<name>`, as
+    /// found under `~/.cache/huggingface/hub`) or a plain directory with the
+    /// repository's files, which needs `repo_id`.
+    pub fn import(&self, source: &Path, repo_id: Option<&str>) -> Result<InstalledModel> {
+        if !source.is_dir() {
+            return Err(EmbeddingError::ModelStoreError(format!(
+                "{} is not a directory",
+                source.display()
+            )));
+        }
+        let is_cache_dir = source.join("snapshots").is_dir() && source.join("refs").is_dir();
+        let folder_repo = source
+            .file_name()
+            .and_then(|name| repo_id_from_folder(&name.to_string_lossy()));
+        let repo_id = repo_id
+            .map(str::to_string)
+            .or_else(|| folder_repo.filter(|_| is_cache_dir))
+            .ok_or_else(|| {
+                EmbeddingError::ModelStoreError(format!(
+                    "Can't tell which model {} holds; pass its repository name (e.g. BAAI/bge-small-en-v1.5)",
+                    source.display()
+                ))
+            })?;
+
+        let target = self.model_dir(&repo_id);
+        if target.exists() {
+            return Err(EmbeddingError::ModelStoreError(format!(
+                "{repo_id} is already installed at {}; prune or remove it first",
+                target.display()
+            )));
+        }
+
+        // Copy next to the target and rename, so an interrupted import never
+        // looks installed.
+        let staging = self
+            .dir
+            .join(format!(".import-{}", repo_id.replace('/', "--")));
+        if staging.exists() {
+            fs::remove_dir_all(&staging).map_err(|e| store_error(&staging, &e))?;
+        }
+        if is_cache_dir {
+            copy_tree(source, &staging)?;
+        } else {
+            copy_tree(source, &staging.join("snapshots").join(IMPORTED_REVISION))?;
+            let refs = staging.join("refs");
+            fs::create_dir_all(&refs).map_err(|e| store_error(&refs, &e))?;
+            fs::write(refs.join("main"), IMPORTED_REVISION).map_err(|e| store_error(&refs, &e))?;
+        }
+        fs::rename(&staging, &target).map_err(|e| store_error(&target, &e))?;
+
+        self.record_checksums(&repo_id)
+    }
+
+    /// Delete `repo_id` from the store.
+    pub fn remove(&self, repo_id: &str) -> Result<()> {
+        let path = self.model_dir(repo_id);
+        fs::remove_dir_all(&path).map_err(|e| store_error(&path, &e))
+    }
+}
+
+fn repo_id_from_folder(name: &str) -> Option<String> {
+    let rest = name.strip_prefix(FOLDER_PREFIX)?;
+    (!rest.is_empty()).then(|| rest.replace("--", "/"))
+}
+
+fn store_error(path: &Path, err: &io::Error) -> EmbeddingError {
+    EmbeddingError::ModelStoreError(format!("{}: {err}", path.display()))
+}
+
+/// Regular files under `root` with their sizes, as sorted `/`-separated
+/// relative paths. Snapshot symlinks point into `blobs/` and aren't counted
+/// twice; the checksum manifest itself is skipped.
+fn model_files(root: &Path) -> Result<Vec<(String, u64)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).map_err(|e| store_error(&dir, &e))? {
+            let entry = entry.map_err(|e| store_error(&dir, &e))?;
+            let path = entry.path();
+            let meta = fs::symlink_metadata(&path).map_err(|e| store_error(&path, &e))?;
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.is_file() {
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if relative != CHECKSUM_FILE {
+                    files.push((relative, meta.len()));
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).map_err(|e| store_error(path, &e))?;
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut file, &mut hasher).map_err(|e| store_error(path, &e))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Copy `source` to `target`, keeping symlinks (hub snapshots link into
+/// `blobs/`) where the platform supports them.
+fn copy_tree(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target).map_err(|e| store_error(target, &e))?;
+    for entry in fs::read_dir(source).map_err(|e| store_error(source, &e))? {
+        let entry = entry.map_err(|e| store_error(source, &e))?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        let meta = fs::symlink_metadata(&from).map_err(|e| store_error(&from, &e))?;
+        if meta.is_dir() {
+            copy_tree(&from, &to)?;
+        } else if meta.file_type().is_symlink() {
+            #[cfg(unix)]
+            {
+                let link = fs::read_link(&from).map_err(|e| store_error(&from, &e))?;
+                std::os::unix::fs::symlink(link, &to).map_err(|e| store_error(&to, &e))?;
+            }
+            #[cfg(not(unix))]
+            {
+                fs::copy(&from, &to).map_err(|e| store_error(&from, &e))?;
+            }
+        } else {
+            fs::copy(&from, &to).map_err(|e| store_error(&from, &e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_plain_directory_lists_and_verifies() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let source = root.join("download");
+        fs::create_dir_all(source.join("onnx")).unwrap();
+        fs::write(source.join("config.json"), "{}").unwrap();
+        fs::write(source.join("onnx").join("model.onnx"), b"weights").unwrap();
+
+        let store = ModelStore::new(root.join("models")).with_offline(true);
+        assert!(store.list().unwrap().is_empty());
+        assert!(matches!(
+            store.require_offline("org/model", &["config.json"]),
+            Err(EmbeddingError::ModelMissing { .. })
+        ));
+        assert!(
+            store.import(&source, None).is_err(),
+            "plain dirs need a name"
+        );
+
+        let model = store.import(&source, Some("org/model")).unwrap();
+        assert_eq!(model.repo_id, "org/model");
+        assert_eq!(model.files, 3, "two files plus refs/main");
+        assert!(model.checksum.is_some());
+        assert_eq!(store.list().unwrap(), vec![model]);
+        assert!(store.file("org/model", "onnx/model.onnx").is_some());
+        store
+            .require_offline(
+                "org/model",
+                &["config.json", "model.safetensors|onnx/model.onnx"],
+            )
+            .unwrap();
+        assert!(store.import(&source, Some("org/model")).is_err());
+
+        assert!(store.verify("org/model").unwrap().is_ok());
+        let weights = store.file("org/model", "onnx/model.onnx").unwrap();
+        fs::write(&weights, b"tampered").unwrap();
+        fs::remove_file(store.file("org/model", "config.json").unwrap()).unwrap();
+        let report = store.verify("org/model").unwrap();
+        assert_eq!(
+            report.mismatched,
+            vec!["snapshots/imported/onnx/model.onnx"]
+        );
+        assert_eq!(report.missing, vec!["snapshots/imported/config.json"]);
+
+        store.remove("org/model").unwrap();
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fastembed_offline_requires_tokenizer_configs() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let source = root.join("download");
+        fs::create_dir_all(source.join("onnx")).unwrap();
+        fs::write(source.join("onnx").join("model.onnx"), b"weights").unwrap();
+        for file in [
+            "tokenizer.json",
+            "special_tokens_map.json",
+            "tokenizer_config.json",
+        ] {
+            fs::write(source.join(file), "{}").unwrap();
+        }
+
+        let store = ModelStore::new(root.join("models")).with_offline(true);
+        store.import(&source, Some("org/model")).unwrap();
+        let err = store
+            .require_fastembed_offline("org/model", "onnx/model.onnx", &[])
+            .unwrap_err();
+        assert!(
+            matches!(&err, EmbeddingError::ModelMissing { files, .. } if files == "config.json"),
+            "{err}"
+        );
+
+        fs::write(source.join("config.json"), "{}").unwrap();
+        store.remove("org/model").unwrap();
+        store.import(&source, Some("org/model")).unwrap();
+        store
+            .require_fastembed_offline("org/model", "onnx/model.onnx", &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_import_hub_cache_directory_infers_name() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let source = root.join("models--BAAI--bge-small-en-v1.5");
+        fs::create_dir_all(source.join("refs")).unwrap();
+        fs::create_dir_all(source.join("snapshots").join("abc123")).unwrap();
+        fs::write(source.join("refs").join("main"), "abc123").unwrap();
+        fs::write(
+            source
+                .join("snapshots")
+                .join("abc123")
+                .join("tokenizer.json"),
+            "{}",
+        )
+        .unwrap();
+
+        let store = ModelStore::new(root.join("models"));
+        let model = store.import(&source, None).unwrap();
+        assert_eq!(model.repo_id, "BAAI/bge-small-en-v1.5");
+        assert!(store.file(&model.repo_id, "tokenizer.json").is_some());
+        let report = store.verify(&model.repo_id).unwrap();
+        assert_eq!(report.checked, 2);
+        assert!(report.is_ok());
+    }
+}
@@ -0,0 +1,253 @@
+//! Cross-encoder reranking of search candidates.
+//!
+//! A reranker scores each (query, document) pair jointly, which ranks close
+//! matches better than comparing independently computed vectors. It is slower,
+//! so callers only rerank the top candidates of a vector search.
+
+use ix_config::RerankConfig;
+
+use crate::Result;
+use crate::{
+    DEFAULT_HASH_DIMENSION, EmbeddingError, EmbeddingProvider, HashEmbeddingProvider, ModelStore,
+};
+
+#[cfg(feature = "fastembed")]
+use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+#[cfg(feature = "fastembed")]
+use std::sync::Mutex;
+
+pub trait RerankProvider: Send + Sync {
+    /// Relevance of each document to `query`, in input order (higher is better).
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>>;
+    fn model_name(&self) -> &str;
+    fn provider_name(&self) -> &'static str;
+}
+
+pub struct Reranker {
+    provider: Box<dyn RerankProvider>,
+}
+
+impl Reranker {
+    pub fn with_config(config: &RerankConfig) -> Result<Self> {
+        let provider = reranker_from_config(config, None)?;
+        Ok(Self::from_provider(provider))
+    }
+
+    /// Load the model from `store` instead of the provider's default cache.
+    pub fn with_model_store(config: &RerankConfig, store: &ModelStore) -> Result<Self> {
+        let provider = reranker_from_config(config, Some(store))?;
+        Ok(Self::from_provider(provider))
+    }
+
+    pub fn from_provider(provider: Box<dyn RerankProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let scores = self.provider.rerank(query, documents)?;
+        if scores.len() != documents.len() {
+            return Err(EmbeddingError::EmbedError(format!(
+                "reranker returned {} scores for {} documents",
+                scores.len(),
+                documents.len()
+            )));
+        }
+        Ok(scores)
+    }
+
+    #[must_use]
+    pub fn model_name(&self) -> &str {
+        self.provider.model_name()
+    }
+
+    #[must_use]
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.provider_name()
+    }
+}
+
+// =============================================================================
+// Hash Reranker
+// =============================================================================
+
+/// Deterministic reranker scoring pairs by the cosine similarity of their
+/// hash embeddings. No model download; meant for tests and demos.
+struct HashReranker {
+    embedder: HashEmbeddingProvider,
+}
+
+impl RerankProvider for HashReranker {
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        let query = self.embedder.embed(query)?;
+        self.embedder
+            .embed_batch(documents)?
+            .iter()
+            .map(|doc| Ok(doc.iter().zip(&query).map(|(a, b)| a * b).sum()))
+            .collect()
+    }
+
+    fn model_name(&self) -> &'static str {
+        crate::HASH_MODEL_NAME
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "hash"
+    }
+}
+
+// =============================================================================
+// FastEmbed Reranker
+// =============================================================================
+
+#[cfg(feature = "fastembed")]
+struct FastEmbedReranker {
+    model: Mutex<TextRerank>,
+    model_name: String,
+}
+
+#[cfg(feature = "fastembed")]
+impl FastEmbedReranker {
+    fn new(config: &RerankConfig, store: Option<&ModelStore>) -> Result<Self> {
+        let model_name = config.model.trim();
+        let reranker_model = model_name
+            .parse::<RerankerModel>()
+            .map_err(EmbeddingError::UnknownModel)?;
+        let info = TextRerank::get_model_info(&reranker_model);
+        let mut options = RerankInitOptions::new(reranker_model);
+        if let Some(store) = store {
+            store.require_fastembed_offline(
+                &info.model_code,
+                &info.model_file,
+                &info.additional_files,
+            )?;
+            options = options.with_cache_dir(store.dir().to_path_buf());
+        }
+        let model =
+            TextRerank::try_new(options).map_err(|e| EmbeddingError::InitError(e.to_string()))?;
+
+        Ok(Self {
+            model: Mutex::new(model),
+            model_name: model_name.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "fastembed")]
+impl RerankProvider for FastEmbedReranker {
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        let results = self
+            .model
+            .lock()
+            .map_err(|e| EmbeddingError::EmbedError(e.to_string()))?
+            .rerank(query, documents.to_vec(), false, None)
+            .map_err(|e| EmbeddingError::EmbedError(e.to_string()))?;
+
+        // Results come back sorted by score; restore input order.
+        let mut scores = vec![f32::MIN; documents.len()];
+        for result in results {
+            if let Some(score) = scores.get_mut(result.index) {
+                *score = result.score;
+            }
+        }
+        Ok(scores)
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "fastembed"
+    }
+}
+
+#[cfg_attr(not(feature = "fastembed"), allow(unused_variables))]
+fn reranker_from_config(
+    config: &RerankConfig,
+    store: Option<&ModelStore>,
+) -> Result<Box<dyn RerankProvider>> {
+    let provider = config.provider.trim().to_lowercase();
+    match provider.as_str() {
+        "hash" => Ok(Box::new(HashReranker {
+            embedder: HashEmbeddingProvider::new(DEFAULT_HASH_DIMENSION),
+        })),
+
+        #[cfg(feature = "fastembed")]
+        "fastembed" | "fastembed-rs" => Ok(Box::new(FastEmbedReranker::new(config, store)?)),
+
+        #[cfg(not(feature = "fastembed"))]
+        "fastembed" | "fastembed-rs" => Err(EmbeddingError::ProviderNotCompiled {
+            provider: "fastembed".to_string(),
+            feature: "fastembed".to_string(),
+        }),
+
+        _ => Err(EmbeddingError::UnknownProvider(config.provider.clone())),
+    }
+}
+
+/// Hub repository the configured reranker downloads its model from, or
+/// `None` for the hash reranker.
+pub fn rerank_model_repo(config: &RerankConfig) -> Result<Option<String>> {
+    let provider = config.provider.trim().to_lowercase();
+    match provider.as_str() {
+        "hash" => Ok(None),
+
+        #[cfg(feature = "fastembed")]
+        "fastembed" | "fastembed-rs" => {
+            let model = config
+                .model
+                .trim()
+                .parse::<RerankerModel>()
+                .map_err(EmbeddingError::UnknownModel)?;
+            Ok(Some(TextRerank::get_model_info(&model).model_code))
+        }
+
+        #[cfg(not(feature = "fastembed"))]
+        "fastembed" | "fastembed-rs" => Err(EmbeddingError::ProviderNotCompiled {
+            provider: "fastembed".to_string(),
+            feature: "fastembed".to_string(),
+        }),
+
+        _ => Err(EmbeddingError::UnknownProvider(config.provider.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_reranker_prefers_overlapping_documents() {
+        let config = RerankConfig {
+            enabled: true,
+            provider: "hash".to_string(),
+            ..Default::default()
+        };
+        let reranker = Reranker::with_config(&config).unwrap();
+        assert_eq!(reranker.provider_name(), "hash");
+
+        let scores = reranker
+            .rerank(
+                "connection pooling",
+                &["quarterly report", "use a connection pool for postgres"],
+            )
+            .unwrap();
+        assert_eq!(scores.len(), 2);
+        assert!(scores[1] > scores[0], "{scores:?}");
+        assert!(reranker.rerank("anything", &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "fastembed")]
+    #[ignore = "Requires downloading model (~280MB)"]
+    fn test_fastembed_reranker() {
+        let reranker = Reranker::with_config(&RerankConfig::default()).unwrap();
+        let scores = reranker
+            .rerank("what is a panda?", &["hi", "The giant panda is a bear"])
+            .unwrap();
+        assert!(scores[1] > scores[0]);
+    }
+}
@@ -98,3 +98,143 @@ pub fn incoming_neighbors(
 
     Ok(neighbors)
 }
+
+/// Edges leaving `node_id` under any label, as `(edge id, neighbor node id)`.
+pub fn outgoing_edges(
+    storage: &HelixGraphStorage,
+    rtxn: &RoTxn<'_>,
+    node_id: u128,
+) -> Result<Vec<(u128, u128)>> {
+    // Adjacency keys are the node ID followed by a 4-byte label hash
+    let key = HelixGraphStorage::out_edge_key(&node_id, &[0; 4]);
+    let prefix = &key[..key.len() - 4];
+    let mut edges = Vec::new();
+
+    let iter = storage.out_edges_db.prefix_iter(rtxn, prefix)?;
+    for result in iter {
+        let (_, value) = result?;
+        edges.push(HelixGraphStorage::unpack_adj_edge_data(value)?);
+    }
+
+    Ok(edges)
+}
+
+/// Edges arriving at `node_id` under any label, as `(edge id, neighbor node
+/// id)`.
+pub fn incoming_edges(
+    storage: &HelixGraphStorage,
+    rtxn: &RoTxn<'_>,
+    node_id: u128,
+) -> Result<Vec<(u128, u128)>> {
+    let key = HelixGraphStorage::in_edge_key(&node_id, &[0; 4]);
+    let prefix = &key[..key.len() - 4];
+    let mut edges = Vec::new();
+
+    let iter = storage.in_edges_db.prefix_iter(rtxn, prefix)?;
+    for result in iter {
+        let (_, value) = result?;
+        edges.push(HelixGraphStorage::unpack_adj_edge_data(value)?);
+    }
+
+    Ok(edges)
+}
+
+/// Remove `node` and the secondary index entries [`update_secondary_indices`]
+/// wrote for it. Its edges are left in place.
+pub fn delete_node(
+    storage: &HelixGraphStorage,
+    wtxn: &mut RwTxn<'_>,
+    node: &Node<'_>,
+) -> Result<()> {
+    for (index_name, db) in &storage.secondary_indices {
+        if let Some(value) = node.get_property(index_name) {
+            let serialized = bincode::serialize(value)?;
+            db.0.delete(wtxn, &serialized)?;
+        }
+    }
+    storage
+        .nodes_db
+        .delete(wtxn, HelixGraphStorage::node_key(&node.id))?;
+    Ok(())
+}
+
+/// Remove every edge leaving `node_id`, with both of their adjacency entries.
+pub fn delete_outgoing_edges(
+    storage: &HelixGraphStorage,
+    wtxn: &mut RwTxn<'_>,
+    node_id: u128,
+) -> Result<()> {
+    let key = HelixGraphStorage::out_edge_key(&node_id, &[0; 4]);
+    let prefix = &key[..key.len() - 4];
+    let mut edges = Vec::new();
+
+    let iter = storage.out_edges_db.prefix_iter(wtxn, prefix)?;
+    for result in iter {
+        let (key, value) = result?;
+        let (edge_id, to_node) = HelixGraphStorage::unpack_adj_edge_data(value)?;
+        edges.push((edge_id, to_node, label_hash_of(key)));
+    }
+
+    for (edge_id, to_node, label_hash) in edges {
+        unlink_edge(storage, wtxn, edge_id, node_id, to_node, &label_hash)?;
+    }
+    Ok(())
+}
+
+/// Remove every edge arriving at `node_id`, with both of their adjacency
+/// entries.
+pub fn delete_incoming_edges(
+    storage: &HelixGraphStorage,
+    wtxn: &mut RwTxn<'_>,
+    node_id: u128,
+) -> Result<()> {
+    let key = HelixGraphStorage::in_edge_key(&node_id, &[0; 4]);
+    let prefix = &key[..key.len() - 4];
+    let mut edges = Vec::new();
+
+    let iter = storage.in_edges_db.prefix_iter(wtxn, prefix)?;
+    for result in iter {
+        let (key, value) = result?;
+        let (edge_id, from_node) = HelixGraphStorage::unpack_adj_edge_data(value)?;
+        edges.push((edge_id, from_node, label_hash_of(key)));
+    }
+
+    for (edge_id, from_node, label_hash) in edges {
+        unlink_edge(storage, wtxn, edge_id, from_node, node_id, &label_hash)?;
+    }
+    Ok(())
+}
+
+/// The label hash an adjacency key ends with.
+fn label_hash_of(adjacency_key: &[u8]) -> [u8; 4] {
+    let mut label_hash = [0; 4];
+    label_hash.copy_from_slice(&adjacency_key[adjacency_key.len() - 4..]);
+    label_hash
+}
+
+fn unlink_edge(
+    storage: &HelixGraphStorage,
+    wtxn: &mut RwTxn<'_>,
+    edge_id: u128,
+    from_node: u128,
+    to_node: u128,
+    label_hash: &[u8; 4],
+) -> Result<()> {
+    storage
+        .edges_db
+        .delete(wtxn, HelixGraphStorage::edge_key(&edge_id))?;
+
+    let out_key = HelixGraphStorage::out_edge_key(&from_node, label_hash);
+    let out_val = HelixGraphStorage::pack_edge_data(&edge_id, &to_node);
+    storage
+        .out_edges_db
+        .delete_one_duplicate(wtxn, &out_key, &out_val)?;
+
+    let in_key = HelixGraphStorage::in_edge_key(&to_node, label_hash);
+    let in_val = HelixGraphStorage::pack_edge_data(&edge_id, &from_node);
+    storage
+        .in_edges_db
+        .delete_one_duplicate(wtxn, &in_key, &in_val)?;
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,10 +9,25 @@ pub enum IdError {
     InvalidFormat(String),
     #[error("Invalid hex in ID: {0}")]
     InvalidHex(String),
+    #[error("Unknown ID strategy: {0} (expected random, time_sorted, or content_keyed)")]
+    UnknownStrategy(String),
 }
 
 const DEFAULT_HASH_BYTES: usize = 3;
 
+/// Shortest hash (in bytes) accepted by [`parse_id`].
+pub const MIN_ID_BYTES: usize = 3;
+
+/// Longest hash (in bytes) accepted by [`parse_id`].
+pub const MAX_ID_BYTES: usize = 6;
+
+/// Bytes of a time-sortable ID taken by the big-endian Unix timestamp (seconds).
+const TIMESTAMP_BYTES: usize = 4;
+
+/// Shortest time-sortable ID (in bytes): the timestamp plus two random bytes,
+/// since a single one collides within a second after a few dozen IDs.
+pub const MIN_TIME_SORTED_BYTES: usize = TIMESTAMP_BYTES + 2;
+
 pub fn id_from_key(prefix: &str, key: &str) -> String {
     id_from_key_with_length(prefix, key, DEFAULT_HASH_BYTES)
 }
@@ -40,6 +58,101 @@ pub fn id_random_with_length(prefix: &str, bytes: usize) -> String {
     format!("{prefix}-{hex}")
 }
 
+/// Generate an ID whose hex sorts in creation order.
+///
+/// The hash starts with the Unix time in seconds followed by random bytes, so
+/// it is never shorter than [`MIN_TIME_SORTED_BYTES`] regardless of `bytes`.
+pub fn id_time_sorted(prefix: &str) -> String {
+    id_time_sorted_with_length(prefix, DEFAULT_HASH_BYTES)
+}
+
+pub fn id_time_sorted_with_length(prefix: &str, bytes: usize) -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let secs = u32::try_from(secs).unwrap_or(u32::MAX);
+
+    let uuid = uuid::Uuid::new_v4();
+    let random = blake3::hash(uuid.as_bytes());
+    let random_bytes = bytes.clamp(MIN_TIME_SORTED_BYTES, MAX_ID_BYTES) - TIMESTAMP_BYTES;
+
+    let mut raw = secs.to_be_bytes().to_vec();
+    raw.extend_from_slice(&random.as_bytes()[..random_bytes]);
+    format!("{prefix}-{}", hex::encode(raw))
+}
+
+/// How new IDs are generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum IdStrategy {
+    /// Random hex, see [`id_random`].
+    #[default]
+    Random,
+    /// Timestamp followed by random hex, see [`id_time_sorted`].
+    #[cfg_attr(feature = "serde", serde(alias = "time-sorted", alias = "sortable"))]
+    TimeSorted,
+    /// Hash of a natural key, see [`id_from_key`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "content-keyed", alias = "content", alias = "key")
+    )]
+    ContentKeyed,
+}
+
+impl IdStrategy {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::TimeSorted => "time_sorted",
+            Self::ContentKeyed => "content_keyed",
+        }
+    }
+
+    /// Generate an ID for `prefix`. `key` is only used by [`IdStrategy::ContentKeyed`].
+    #[must_use]
+    pub fn generate(self, prefix: &str, key: &str, bytes: usize) -> String {
+        match self {
+            Self::Random => id_random_with_length(prefix, bytes),
+            Self::TimeSorted => id_time_sorted_with_length(prefix, bytes),
+            Self::ContentKeyed => id_from_key_with_length(prefix, key, bytes),
+        }
+    }
+}
+
+impl FromStr for IdStrategy {
+    type Err = IdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "random" => Ok(Self::Random),
+            "time_sorted" | "sortable" => Ok(Self::TimeSorted),
+            "content_keyed" | "content" | "key" => Ok(Self::ContentKeyed),
+            other => Err(IdError::UnknownStrategy(other.to_string())),
+        }
+    }
+}
+
+/// Lengthen `id` to `bytes` of hash while keeping the existing hash as a prefix.
+///
+/// The extra hex is derived from the original ID, so widening is deterministic and
+/// the old short ID keeps resolving as a prefix of the new one. IDs that are
+/// already at least `bytes` long are returned unchanged.
+pub fn widen_id(id: &str, bytes: usize) -> Result<String, IdError> {
+    let (prefix, hash) = parse_id(id)?;
+    let target = bytes.min(MAX_ID_BYTES) * 2;
+    if hash.len() >= target {
+        return Ok(id.to_string());
+    }
+
+    let extra = hex::encode(blake3::hash(id.as_bytes()).as_bytes());
+    Ok(format!("{prefix}-{hash}{}", &extra[..target - hash.len()]))
+}
+
 #[deprecated(
     since = "0.2.0",
     note = "Use id_random() for random IDs or id_from_key() for deterministic IDs"
@@ -290,6 +403,52 @@ mod tests {
         assert!(parse_id("bd-xyz123").is_err());
     }
 
+    #[test]
+    fn test_id_time_sorted_orders_by_time() {
+        let id = id_time_sorted("iss");
+        let (_, hash) = parse_id(&id).unwrap();
+        assert_eq!(hash.len(), MIN_TIME_SORTED_BYTES * 2);
+        assert_eq!(id_time_sorted_with_length("iss", 12).len(), id.len());
+
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let later = format!("{:08x}", secs + 1);
+        assert!(hash.as_str() < later.as_str());
+    }
+
+    #[test]
+    fn test_id_strategy_from_str() {
+        assert_eq!("random".parse::<IdStrategy>().unwrap(), IdStrategy::Random);
+        assert_eq!(
+            "time-sorted".parse::<IdStrategy>().unwrap(),
+            IdStrategy::TimeSorted
+        );
+        assert_eq!(
+            "content_keyed".parse::<IdStrategy>().unwrap(),
+            IdStrategy::ContentKeyed
+        );
+        assert!("uuid".parse::<IdStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_id_strategy_content_keyed_is_deterministic() {
+        let id1 = IdStrategy::ContentKeyed.generate("dec", "use postgres", 4);
+        let id2 = IdStrategy::ContentKeyed.generate("dec", "use postgres", 4);
+        assert_eq!(id1, id2);
+        assert_eq!(id1.len(), "dec-".len() + 8);
+    }
+
+    #[test]
+    fn test_widen_id_keeps_prefix() {
+        let wide = widen_id("dec-a1b2c3", 4).unwrap();
+        assert!(wide.starts_with("dec-a1b2c3"));
+        assert_eq!(wide.len(), "dec-".len() + 8);
+        assert_eq!(wide, widen_id("dec-a1b2c3", 4).unwrap());
+        assert_eq!(widen_id(&wide, 3).unwrap(), wide);
+    }
+
     define_id!(SourceId, "src");
     define_id!(DocId, "doc");
     define_id!(IssueId, "bd");
@@ -0,0 +1,945 @@
+//! Conformance checks shared by every [`IndexBackend`].
+//!
+//! Each check is a function generic over the backend, taking the backend's
+//! `(repo, embedder)` constructor. Storage crates run the whole suite from an
+//! integration test:
+//!
+//! ```ignore
+//! ix_storage_conformance::index_backend_tests!(ix_storage_flat::FlatIndex::open_with_embedder);
+//! ```
+
+use anyhow::Result;
+use ix_core::context::{ContextConfig, build_context, estimate_tokens};
+use ix_core::entity::EntityKind;
+use ix_core::graph::{Direction, EdgeFilter};
+use ix_core::index::{
+    DateRange, IndexBackend, SearchFilters, SearchMode, SearchRequest, parse_date,
+};
+use ix_core::markdown::{parse_markdown, render_markdown, set_string};
+use ix_core::repo::IxchelRepo;
+use ix_core::similarity::{DUPLICATE_THRESHOLD, cosine_similarity, find_duplicates};
+use ix_embeddings::Embedder;
+use tempfile::TempDir;
+
+/// Define a `#[test]` for every conformance check, opening the backend with
+/// `$open`.
+#[macro_export]
+macro_rules! index_backend_tests {
+    ($open:expr) => {
+        $crate::index_backend_tests!(@each $open;
+            deterministic_sync_indexes_graph_and_vectors,
+            search_returns_best_matching_passage,
+            embedding_change_requires_and_triggers_rebuild,
+            lexical_and_hybrid_search_match_exact_identifiers,
+            search_filters_are_applied_by_the_backend,
+            date_filters_are_applied_by_the_backend,
+            related_and_duplicates_use_entity_vectors,
+            vector_scores_follow_the_score_contract,
+            graph_operations_follow_indexed_relationships,
+            incremental_sync_rewrites_only_changed_entities,
+            unchanged_entities_relink_re_added_targets,
+            context_expands_through_weighted_relationships_within_budget,
+            context_keeps_linked_seeds_at_their_own_score,
+            context_always_includes_seeds_truncated_to_budget,
+        );
+    };
+    (@each $open:expr; $($check:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $check() {
+                $crate::$check($open);
+            }
+        )*
+    };
+}
+
+/// A fresh ixchel repository in a temporary directory.
+pub fn temp_repo() -> (TempDir, IxchelRepo) {
+    let temp = TempDir::new().expect("tempdir");
+    let repo = IxchelRepo::init_at(temp.path(), false).expect("init ixchel repo");
+    (temp, repo)
+}
+
+/// The deterministic `hash` provider at `dimension`, so checks never
+/// download a model.
+pub fn hash_embedder(repo: &IxchelRepo, dimension: usize) -> Embedder {
+    let mut config = repo.config.embedding.clone();
+    config.provider = "hash".to_string();
+    config.dimension = Some(dimension);
+    Embedder::with_config(&config).expect("hash embedder")
+}
+
+pub fn replace_entity_body(repo: &IxchelRepo, id: &str, body: &str) {
+    let path = repo.paths.entity_path(id).expect("entity path");
+    let raw = std::fs::read_to_string(&path).expect("read entity");
+    let mut doc = parse_markdown(&path, &raw).expect("parse markdown");
+    doc.body = body.to_string();
+    let out = render_markdown(&doc).expect("render markdown");
+    std::fs::write(&path, out).expect("write entity");
+}
+
+/// Set a frontmatter string, or remove the key when `value` is `None`.
+pub fn set_frontmatter_string(repo: &IxchelRepo, id: &str, key: &str, value: Option<&str>) {
+    let path = repo.paths.entity_path(id).expect("entity path");
+    let raw = std::fs::read_to_string(&path).expect("read entity");
+    let mut doc = parse_markdown(&path, &raw).expect("parse markdown");
+    match value {
+        Some(value) => set_string(&mut doc.frontmatter, key, value),
+        None => {
+            doc.frontmatter.remove(key);
+        }
+    }
+    let out = render_markdown(&doc).expect("render markdown");
+    std::fs::write(&path, out).expect("write entity");
+}
+
+fn outgoing(index: &impl IndexBackend, id: &str, rel: &str) -> Vec<String> {
+    let filter = EdgeFilter::new(Direction::Outgoing).with_labels(vec![rel.to_string()]);
+    index
+        .neighbors(id, &filter)
+        .expect("outgoing neighbors")
+        .into_iter()
+        .map(|neighbor| neighbor.entity.id)
+        .collect()
+}
+
+/// Sync indexes every entity, its vectors and its relationships.
+pub fn deterministic_sync_indexes_graph_and_vectors<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let alpha = repo
+        .create_entity(EntityKind::Source, "Alpha Source", None)
+        .expect("create alpha source");
+    replace_entity_body(&repo, &alpha.id, "alpha");
+
+    let beta = repo
+        .create_entity(EntityKind::Source, "Beta Source", None)
+        .expect("create beta source");
+    replace_entity_body(&repo, &beta.id, "beta");
+
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Decision One", Some("accepted"))
+        .expect("create decision");
+    replace_entity_body(&repo, &decision.id, "decision");
+
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Issue One", Some("open"))
+        .expect("create issue");
+    replace_entity_body(&repo, &issue.id, "issue");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link issue implements decision");
+
+    let embedder = hash_embedder(&repo, 32);
+    let mut index = open(&repo, embedder).expect("open index");
+
+    let stats = index.sync(&repo).expect("sync");
+    assert_eq!(stats.scanned, 4);
+    assert_eq!(stats.added, 4);
+
+    let hits = index
+        .search(&SearchRequest::new("alpha", 5))
+        .expect("search alpha");
+    assert!(!hits.is_empty(), "expected search hits");
+    assert_eq!(hits[0].id, alpha.id, "{hits:#?}");
+
+    let hits = index
+        .search(&SearchRequest::new("beta", 5))
+        .expect("search beta");
+    assert!(!hits.is_empty(), "expected search hits");
+    assert_eq!(hits[0].id, beta.id, "{hits:#?}");
+
+    assert_eq!(outgoing(&index, &issue.id, "implements"), vec![decision.id]);
+}
+
+/// Chunk hits group to one entity carrying its best passage and file lines.
+pub fn search_returns_best_matching_passage<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let report = repo
+        .create_entity(EntityKind::Report, "Architecture Notes", None)
+        .expect("create report");
+    replace_entity_body(
+        &repo,
+        &report.id,
+        "## Storage\n\npostgres tables and migrations\n\n## Caching\n\nredis keys expire hourly\n",
+    );
+
+    let embedder = hash_embedder(&repo, 64);
+    let mut index = open(&repo, embedder).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let hits = index
+        .search(&SearchRequest::new("redis keys expire", 5))
+        .expect("search");
+    assert_eq!(
+        hits.len(),
+        1,
+        "chunks should group to one entity: {hits:#?}"
+    );
+    assert_eq!(hits[0].id, report.id);
+
+    let passage = hits[0].passage.as_ref().expect("passage");
+    assert_eq!(passage.heading_path, vec!["Caching".to_string()]);
+    assert_eq!(passage.text, "redis keys expire hourly");
+
+    let raw = std::fs::read_to_string(&report.path).expect("read report");
+    let line = raw
+        .lines()
+        .nth(passage.start_line - 1)
+        .expect("line in file");
+    assert_eq!(line, "redis keys expire hourly");
+}
+
+/// A different embedder blocks search until the next sync re-embeds everything.
+pub fn embedding_change_requires_and_triggers_rebuild<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let alpha = repo
+        .create_entity(EntityKind::Source, "Alpha Source", None)
+        .expect("create alpha source");
+    replace_entity_body(&repo, &alpha.id, "alpha");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+    assert!(!index.metadata().expect("metadata").needs_rebuild());
+    drop(index);
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("reopen index");
+    let metadata = index.metadata().expect("metadata");
+    assert_eq!(
+        metadata.differences(),
+        vec!["dimension: 32 -> 64".to_string()]
+    );
+    assert!(index.search(&SearchRequest::new("alpha", 5)).is_err());
+    assert!(index.related(&alpha.id, 5).is_err());
+    assert!(index.health_check().is_err());
+
+    let stats = index.sync(&repo).expect("resync");
+    assert_eq!(stats.added, 1, "mismatch should re-embed everything");
+    assert!(index.metadata().expect("metadata").differences().is_empty());
+    assert_eq!(
+        index
+            .search(&SearchRequest::new("alpha", 5))
+            .expect("search")[0]
+            .id,
+        alpha.id
+    );
+}
+
+/// Lexical and hybrid search find exact identifiers.
+pub fn lexical_and_hybrid_search_match_exact_identifiers<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let error = repo
+        .create_entity(EntityKind::Issue, "Build fails on nightly", Some("open"))
+        .expect("create issue");
+    replace_entity_body(&repo, &error.id, "rustc reports E0425 in the ix-core crate");
+
+    let other = repo
+        .create_entity(EntityKind::Issue, "Nightly build is slow", Some("open"))
+        .expect("create other issue");
+    replace_entity_body(&repo, &other.id, "the nightly build takes an hour");
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let hits = index
+        .search(&SearchRequest::new("E0425", 5).with_mode(SearchMode::Lexical))
+        .expect("lexical search");
+    assert_eq!(hits.len(), 1, "{hits:#?}");
+    assert_eq!(hits[0].id, error.id);
+    let passage = hits[0].passage.as_ref().expect("passage");
+    assert!(passage.text.contains("E0425"));
+
+    let hits = index
+        .search(&SearchRequest::new("E0425 ix-core", 5).with_mode(SearchMode::Hybrid))
+        .expect("hybrid search");
+    assert_eq!(hits[0].id, error.id, "{hits:#?}");
+}
+
+/// Kind, status and tag filters and offsets apply in every search mode.
+pub fn search_filters_are_applied_by_the_backend<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (temp, repo) = temp_repo();
+
+    let open_issue = repo
+        .create_entity(EntityKind::Issue, "Auth token refresh fails", Some("open"))
+        .expect("create open issue");
+    replace_entity_body(&repo, &open_issue.id, "auth tokens expire early");
+    repo.add_tags(&open_issue.id, &["auth".to_string()])
+        .expect("tag open issue");
+
+    let closed = repo
+        .create_entity(EntityKind::Issue, "Auth login loop", Some("closed"))
+        .expect("create closed issue");
+    replace_entity_body(&repo, &closed.id, "auth redirect loops");
+
+    let source = repo
+        .create_entity(EntityKind::Source, "Auth RFC", None)
+        .expect("create source");
+    replace_entity_body(&repo, &source.id, "auth tokens and scopes");
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    for mode in [SearchMode::Vector, SearchMode::Lexical, SearchMode::Hybrid] {
+        let all = index
+            .search(&SearchRequest::new("auth", 10).with_mode(mode))
+            .expect("unfiltered search");
+        assert_eq!(all.len(), 3, "{mode:?}: {all:#?}");
+
+        let filters = SearchFilters {
+            kinds: vec![EntityKind::Issue],
+            statuses: vec!["OPEN".to_string()],
+            ..SearchFilters::default()
+        };
+        let hits = index
+            .search(
+                &SearchRequest::new("auth", 10)
+                    .with_mode(mode)
+                    .with_filters(filters),
+            )
+            .expect("filtered search");
+        assert_eq!(hits.len(), 1, "{mode:?}: {hits:#?}");
+        assert_eq!(hits[0].id, open_issue.id);
+
+        let filters = SearchFilters {
+            tags: vec!["auth".to_string()],
+            ..SearchFilters::default()
+        };
+        let hits = index
+            .search(
+                &SearchRequest::new("auth", 10)
+                    .with_mode(mode)
+                    .with_filters(filters),
+            )
+            .expect("tag search");
+        assert_eq!(hits.len(), 1, "{mode:?}: {hits:#?}");
+        assert_eq!(hits[0].id, open_issue.id);
+        assert_eq!(hits[0].status.as_deref(), Some("open"));
+        assert_eq!(hits[0].tags, vec!["auth".to_string()]);
+        let path = hits[0].path.as_deref().expect("path");
+        assert!(temp.path().join(path).exists(), "{path}");
+        let snippet = hits[0].snippet.as_ref().expect("snippet");
+        assert_eq!(snippet.render("[", "]"), "[auth] tokens expire early");
+
+        let page = index
+            .search(&SearchRequest::new("auth", 2).with_mode(mode).with_offset(2))
+            .expect("second page");
+        assert_eq!(page.len(), 1, "{mode:?}: {page:#?}");
+        assert_eq!(page[0].id, all[2].id);
+    }
+}
+
+/// Date ranges bound both timestamps, and statuses match however they are
+/// padded or cased.
+pub fn date_filters_are_applied_by_the_backend<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let old = repo
+        .create_entity(EntityKind::Issue, "Auth token refresh fails", Some("open"))
+        .expect("create old issue");
+    replace_entity_body(&repo, &old.id, "auth tokens expire early");
+    set_frontmatter_string(&repo, &old.id, "created_at", Some("2024-01-10T00:00:00Z"));
+    set_frontmatter_string(&repo, &old.id, "updated_at", Some("2024-03-01T00:00:00Z"));
+
+    let new = repo
+        .create_entity(EntityKind::Issue, "Auth login loop", Some("open"))
+        .expect("create new issue");
+    replace_entity_body(&repo, &new.id, "auth redirect loops");
+    set_frontmatter_string(&repo, &new.id, "status", Some(" Open "));
+    set_frontmatter_string(&repo, &new.id, "created_at", Some("2025-01-10T00:00:00Z"));
+    set_frontmatter_string(&repo, &new.id, "updated_at", Some("2025-03-01T00:00:00Z"));
+
+    let undated = repo
+        .create_entity(EntityKind::Issue, "Auth scopes", Some("closed"))
+        .expect("create undated issue");
+    replace_entity_body(&repo, &undated.id, "auth scopes are missing");
+    set_frontmatter_string(&repo, &undated.id, "created_at", None);
+    set_frontmatter_string(&repo, &undated.id, "updated_at", None);
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let date = |value: &str| Some(parse_date(value).expect("date"));
+    let cases = [
+        (
+            "created after",
+            SearchFilters {
+                created: DateRange {
+                    after: date("2025-01-01"),
+                    before: None,
+                },
+                ..SearchFilters::default()
+            },
+            vec![new.id.clone()],
+        ),
+        (
+            "created before",
+            SearchFilters {
+                created: DateRange {
+                    after: None,
+                    before: date("2025-01-01"),
+                },
+                ..SearchFilters::default()
+            },
+            vec![old.id.clone()],
+        ),
+        (
+            "updated between",
+            SearchFilters {
+                updated: DateRange {
+                    after: date("2024-02-01"),
+                    before: date("2025-03-01"),
+                },
+                ..SearchFilters::default()
+            },
+            vec![old.id.clone()],
+        ),
+        (
+            "padded status",
+            SearchFilters {
+                statuses: vec![" open".to_string()],
+                ..SearchFilters::default()
+            },
+            vec![new.id, old.id],
+        ),
+    ];
+
+    for mode in [SearchMode::Vector, SearchMode::Lexical, SearchMode::Hybrid] {
+        for (name, filters, expected) in &cases {
+            let hits = index
+                .search(
+                    &SearchRequest::new("auth", 10)
+                        .with_mode(mode)
+                        .with_filters(filters.clone()),
+                )
+                .expect("filtered search");
+            let mut ids: Vec<String> = hits.into_iter().map(|hit| hit.id).collect();
+            ids.sort();
+            let mut expected = expected.clone();
+            expected.sort();
+            assert_eq!(ids, expected, "{mode:?} {name}");
+        }
+    }
+}
+
+/// Related entities, duplicates and nearest entities use pooled entity vectors.
+pub fn related_and_duplicates_use_entity_vectors<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let first = repo
+        .create_entity(EntityKind::Issue, "Login page crashes", Some("open"))
+        .expect("create first issue");
+    replace_entity_body(&repo, &first.id, "the login page crashes on submit");
+
+    let second = repo
+        .create_entity(EntityKind::Issue, "Login page crashes", Some("open"))
+        .expect("create second issue");
+    replace_entity_body(&repo, &second.id, "the login page crashes on submit");
+
+    let other = repo
+        .create_entity(EntityKind::Issue, "Export to CSV", Some("open"))
+        .expect("create other issue");
+    replace_entity_body(&repo, &other.id, "add a csv export for reports");
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let related = index.related(&first.id, 5).expect("related");
+    assert!(related.iter().all(|hit| hit.id != first.id), "{related:#?}");
+    assert_eq!(related[0].id, second.id, "{related:#?}");
+    assert!(related[0].score > 0.99, "{related:#?}");
+    assert!(index.related("iss-missing", 5).is_err());
+
+    let vectors = index.entity_vectors().expect("entity vectors");
+    assert_eq!(vectors.len(), 3);
+    let pairs = find_duplicates(&vectors, DUPLICATE_THRESHOLD, false);
+    assert_eq!(pairs.len(), 1, "{pairs:#?}");
+    let mut ids = [first.id.as_str(), second.id.as_str()];
+    ids.sort_unstable();
+    assert_eq!(
+        [pairs[0].first.id.as_str(), pairs[0].second.id.as_str()],
+        ids
+    );
+
+    let nearest = index
+        .nearest_entities("Export to CSV", 1)
+        .expect("nearest entities");
+    assert_eq!(nearest[0].id, other.id, "{nearest:#?}");
+}
+
+/// Vector hits score cosine similarity clamped to `[0, 1]` and report
+/// `1 - cosine` as their distance; lexical hits have no distance.
+pub fn vector_scores_follow_the_score_contract<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+    for (title, body) in [
+        (
+            "Connection pooling",
+            "reuse database connections across requests",
+        ),
+        (
+            "Query timeouts",
+            "cancel slow database queries after five seconds",
+        ),
+        ("Dark mode", "add a dark theme to the settings page"),
+    ] {
+        let entity = repo
+            .create_entity(EntityKind::Issue, title, Some("open"))
+            .expect("create issue");
+        replace_entity_body(&repo, &entity.id, body);
+    }
+
+    let mut index = open(&repo, hash_embedder(&repo, 64)).expect("open index");
+    index.sync(&repo).expect("sync");
+    let embedder = hash_embedder(&repo, 64);
+
+    let query = "database connections";
+    let hits = index
+        .search(&SearchRequest::new(query, 3))
+        .expect("vector search");
+    assert_eq!(hits.len(), 3, "{hits:#?}");
+    let query_vector = embedder.embed_query(query).expect("embed query");
+    for hit in &hits {
+        let passage = hit.passage.as_ref().expect("passage");
+        let kind = hit.kind.expect("kind");
+        let text = passage.embedding_text(&hit.title, &hit.tags, kind);
+        let chunk_vector = embedder
+            .embed_documents(&[text.as_str()])
+            .expect("embed chunk");
+        let cosine = cosine_similarity(&query_vector, &chunk_vector[0]);
+
+        let distance = hit.distance.expect("distance");
+        assert!((0.0..=2.0).contains(&distance), "{hit:#?}");
+        assert!((0.0..=1.0).contains(&hit.score), "{hit:#?}");
+        assert!((distance - (1.0 - cosine)).abs() < 1e-4, "{hit:#?}");
+        assert!(
+            (hit.score - cosine.clamp(0.0, 1.0)).abs() < 1e-4,
+            "{hit:#?}"
+        );
+    }
+    assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    let vectors = index.entity_vectors().expect("entity vectors");
+    let first = &vectors[0];
+    for hit in index.related(&first.id, 5).expect("related") {
+        let other = vectors
+            .iter()
+            .find(|entity| entity.id == hit.id)
+            .expect("related entity is indexed");
+        let cosine = cosine_similarity(&first.vector, &other.vector);
+        assert!(
+            (hit.score - cosine.clamp(0.0, 1.0)).abs() < 1e-4,
+            "{hit:#?}"
+        );
+        assert!((hit.distance.expect("distance") - (1.0 - cosine)).abs() < 1e-4);
+    }
+
+    let lexical = index
+        .search(&SearchRequest::new(query, 3).with_mode(SearchMode::Lexical))
+        .expect("lexical search");
+    assert!(
+        lexical.iter().all(|hit| hit.distance.is_none()),
+        "{lexical:#?}"
+    );
+}
+
+/// Neighbors, traversal, shortest paths and subgraphs follow indexed relationships.
+pub fn graph_operations_follow_indexed_relationships<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let source = repo
+        .create_entity(EntityKind::Source, "Postgres docs", None)
+        .expect("create source");
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", Some("accepted"))
+        .expect("create decision");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Pool connections", Some("open"))
+        .expect("create issue");
+    let blocked = repo
+        .create_entity(EntityKind::Issue, "Load test", Some("open"))
+        .expect("create blocked issue");
+    let loner = repo
+        .create_entity(EntityKind::Idea, "Unrelated idea", None)
+        .expect("create idea");
+    repo.link(&decision.id, "cites", &source.id)
+        .expect("link decision cites source");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link issue implements decision");
+    repo.link(&issue.id, "blocks", &blocked.id)
+        .expect("link issue blocks issue");
+
+    let embedder = hash_embedder(&repo, 32);
+    let mut index = open(&repo, embedder).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let neighbors = index
+        .neighbors(&decision.id, &EdgeFilter::default())
+        .expect("neighbors");
+    let edges = neighbors
+        .iter()
+        .map(|n| (n.rel.as_str(), n.entity.id.as_str(), n.outgoing))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            ("cites", source.id.as_str(), true),
+            ("implements", issue.id.as_str(), false),
+        ]
+    );
+    assert_eq!(neighbors[1].entity.title, "Pool connections");
+    let outgoing = index
+        .neighbors(&decision.id, &EdgeFilter::new(Direction::Outgoing))
+        .expect("outgoing neighbors");
+    assert_eq!(outgoing.len(), 1);
+
+    let graph = index
+        .traverse(&source.id, &EdgeFilter::default(), 2)
+        .expect("traverse");
+    let depths = graph
+        .nodes
+        .iter()
+        .map(|node| (node.entity.id.as_str(), node.depth))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        depths,
+        vec![
+            (source.id.as_str(), 0),
+            (decision.id.as_str(), 1),
+            (issue.id.as_str(), 2),
+        ]
+    );
+    assert_eq!(graph.edges.len(), 2);
+
+    let filter = EdgeFilter::default().with_labels(vec!["cites".to_string()]);
+    let graph = index
+        .traverse(&source.id, &filter, 3)
+        .expect("traverse cites");
+    assert_eq!(graph.nodes.len(), 2);
+
+    let path = index
+        .shortest_path(&source.id, &blocked.id, &EdgeFilter::default())
+        .expect("shortest path")
+        .expect("path exists");
+    let rels = path
+        .iter()
+        .map(|edge| edge.rel.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(rels, vec!["cites", "implements", "blocks"]);
+    assert_eq!(path[0].from, decision.id);
+    assert_eq!(path[2].to, blocked.id);
+    let none = index
+        .shortest_path(&source.id, &loner.id, &EdgeFilter::default())
+        .expect("shortest path");
+    assert!(none.is_none());
+    assert!(
+        index
+            .shortest_path(
+                &source.id,
+                &blocked.id,
+                &EdgeFilter::new(Direction::Outgoing)
+            )
+            .expect("outgoing path")
+            .is_none()
+    );
+
+    let ids = vec![decision.id, issue.id, loner.id];
+    let graph = index
+        .subgraph(&ids, &EdgeFilter::default())
+        .expect("subgraph");
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.edges.len(), 1);
+    assert_eq!(graph.edges[0].rel, "implements");
+}
+
+/// Resync rewrites only added, changed and deleted entities, keeping edges
+/// into rewritten entities.
+pub fn incremental_sync_rewrites_only_changed_entities<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", Some("accepted"))
+        .expect("create decision");
+    replace_entity_body(&repo, &decision.id, "postgres as the primary store");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Add connection pool", Some("open"))
+        .expect("create issue");
+    replace_entity_body(&repo, &issue.id, "pool database connections");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link issue implements decision");
+    let stale = repo
+        .create_entity(EntityKind::Idea, "Try sqlite", None)
+        .expect("create idea");
+    replace_entity_body(&repo, &stale.id, "embedded sqlite");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    let stats = index.sync(&repo).expect("sync");
+    assert_eq!(stats.added, 3);
+
+    let stats = index.sync(&repo).expect("resync");
+    assert_eq!((stats.added, stats.modified, stats.unchanged), (0, 0, 3));
+
+    replace_entity_body(&repo, &decision.id, "mysql replaces postgres");
+    repo.delete_entity(&stale.id).expect("delete idea");
+    let source = repo
+        .create_entity(EntityKind::Source, "Postgres docs", None)
+        .expect("create source");
+    replace_entity_body(&repo, &source.id, "postgres manual");
+    repo.link(&decision.id, "cites", &source.id)
+        .expect("link decision cites source");
+
+    let stats = index.sync(&repo).expect("incremental sync");
+    assert_eq!(
+        (stats.added, stats.modified, stats.deleted, stats.unchanged),
+        (1, 1, 1, 1)
+    );
+
+    // The unchanged issue keeps its edge to the rewritten decision
+    assert_eq!(
+        outgoing(&index, &issue.id, "implements"),
+        vec![decision.id.clone()]
+    );
+    assert_eq!(outgoing(&index, &decision.id, "cites"), vec![source.id]);
+    assert!(index.entity(&stale.id).expect("entity").is_none());
+
+    let hits = index
+        .search(&SearchRequest::new("mysql", 5).with_mode(SearchMode::Lexical))
+        .expect("search mysql");
+    assert_eq!(hits[0].id, decision.id, "{hits:#?}");
+    let hits = index
+        .search(&SearchRequest::new("sqlite", 5))
+        .expect("search sqlite");
+    assert!(hits.iter().all(|hit| hit.id != stale.id), "{hits:#?}");
+    assert_eq!(index.entity_vectors().expect("entity vectors").len(), 3);
+}
+
+/// An unchanged entity regains its edge to a target that was deleted and
+/// then added back.
+pub fn unchanged_entities_relink_re_added_targets<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", Some("accepted"))
+        .expect("create decision");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Add connection pool", Some("open"))
+        .expect("create issue");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link issue implements decision");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    // Remove the file directly so the issue's frontmatter stays untouched
+    let path = repo.paths.entity_path(&decision.id).expect("decision path");
+    let raw = std::fs::read_to_string(&path).expect("read decision");
+    std::fs::remove_file(&path).expect("remove decision");
+    let stats = index.sync(&repo).expect("sync without decision");
+    assert_eq!((stats.deleted, stats.unchanged), (1, 1));
+    assert!(outgoing(&index, &issue.id, "implements").is_empty());
+
+    std::fs::write(&path, raw).expect("restore decision");
+    let stats = index.sync(&repo).expect("sync restored decision");
+    assert_eq!((stats.added, stats.unchanged), (1, 1));
+    assert_eq!(outgoing(&index, &issue.id, "implements"), vec![decision.id]);
+}
+
+/// Context packs expand seeds through indexed relationships, weighted and
+/// decayed per hop, within the token budget.
+pub fn context_expands_through_weighted_relationships_within_budget<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create decision");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Pool connections", None)
+        .expect("create issue");
+    let report = repo
+        .create_entity(EntityKind::Report, "Load test", None)
+        .expect("create report");
+    let idea = repo
+        .create_entity(EntityKind::Idea, "Try SQLite", None)
+        .expect("create idea");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link implements");
+    repo.link(&report.id, "references", &issue.id)
+        .expect("link references");
+    repo.link(&idea.id, "references", &decision.id)
+        .expect("link idea");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let config = ContextConfig {
+        hops: 2,
+        hop_decay: 0.5,
+        budget_tokens: 100_000,
+        ..ContextConfig::default()
+    };
+    let seeds = [(decision.id.clone(), 1.0)];
+    let pack = build_context(&repo, &index, &seeds, &config).expect("build context");
+    let ranked = pack
+        .items
+        .iter()
+        .map(|item| (item.id.as_str(), item.hops))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranked,
+        vec![
+            (decision.id.as_str(), 0),
+            (issue.id.as_str(), 1),
+            (idea.id.as_str(), 1),
+            (report.id.as_str(), 2),
+        ]
+    );
+    let via = pack.items[1].via.as_ref().expect("via");
+    assert_eq!(
+        (via.from.as_str(), via.rel.as_str()),
+        (decision.id.as_str(), "implements")
+    );
+    assert!(!via.outgoing);
+    assert!((pack.items[1].score - 0.5).abs() < 1e-6);
+    let expected = 0.25 * config.weight("references");
+    assert!((pack.items[3].score - expected).abs() < 1e-6);
+
+    let one_hop = ContextConfig {
+        hops: 1,
+        ..config.clone()
+    };
+    let pack = build_context(&repo, &index, &seeds, &one_hop).expect("one hop");
+    assert_eq!(pack.items.len(), 3);
+
+    let no_refs = ContextConfig {
+        weights: std::collections::BTreeMap::from([("references".to_string(), 0.0)]),
+        default_weight: 1.0,
+        ..config
+    };
+    let pack = build_context(&repo, &index, &seeds, &no_refs).expect("weights");
+    let ids = pack
+        .items
+        .iter()
+        .map(|item| item.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![decision.id.as_str(), issue.id.as_str()]);
+
+    let full = build_context(&repo, &index, &seeds, &config).expect("full");
+    let budget = full.items[0].tokens + full.items[1].tokens;
+    let tight = ContextConfig {
+        budget_tokens: budget,
+        ..config
+    };
+    let pack = build_context(&repo, &index, &seeds, &tight).expect("tight");
+    assert_eq!(pack.items.len(), 2, "{pack:#?}");
+    assert!(pack.tokens <= budget);
+    assert_eq!(pack.omitted.len(), 2);
+    assert!(pack.items.iter().all(|item| !item.truncated));
+    assert_eq!(estimate_tokens("abcdefgh"), 2);
+}
+
+/// A seed reached from a better-scoring seed stays a seed.
+pub fn context_keeps_linked_seeds_at_their_own_score<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+    let decision = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create decision");
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Pool connections", None)
+        .expect("create issue");
+    repo.link(&issue.id, "implements", &decision.id)
+        .expect("link implements");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let config = ContextConfig {
+        hops: 1,
+        budget_tokens: 100_000,
+        ..ContextConfig::default()
+    };
+    let seeds = [(issue.id.clone(), 1.0), (decision.id.clone(), 0.1)];
+    let pack = build_context(&repo, &index, &seeds, &config).expect("build context");
+    let ranked = pack
+        .items
+        .iter()
+        .map(|item| (item.id.as_str(), item.hops, item.via.is_none()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranked,
+        vec![
+            (issue.id.as_str(), 0, true),
+            (decision.id.as_str(), 0, true),
+        ],
+        "{pack:#?}"
+    );
+    assert!((pack.items[1].score - 0.1).abs() < f32::EPSILON);
+}
+
+/// Seeds that don't fit the budget are truncated rather than dropped.
+pub fn context_always_includes_seeds_truncated_to_budget<B: IndexBackend>(
+    open: impl Fn(&IxchelRepo, Embedder) -> Result<B>,
+) {
+    let (_temp, repo) = temp_repo();
+    let root = repo
+        .create_entity(EntityKind::Decision, "Use Postgres", None)
+        .expect("create root");
+    replace_entity_body(&repo, &root.id, &"postgres ".repeat(200));
+    let issue = repo
+        .create_entity(EntityKind::Issue, "Pool connections", None)
+        .expect("create issue");
+    repo.link(&issue.id, "implements", &root.id)
+        .expect("link implements");
+
+    let mut index = open(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let config = ContextConfig {
+        hops: 1,
+        budget_tokens: 50,
+        ..ContextConfig::default()
+    };
+    let pack =
+        build_context(&repo, &index, &[(root.id.clone(), 1.0)], &config).expect("build context");
+    assert_eq!(pack.items.len(), 1, "{pack:#?}");
+    let seed = &pack.items[0];
+    assert_eq!(seed.id, root.id);
+    assert!(seed.truncated);
+    assert!(seed.body.trim_start().starts_with("postgres postgres"));
+    assert!(seed.body.len() < "postgres ".repeat(200).len());
+    assert!(pack.tokens <= config.budget_tokens, "{pack:#?}");
+    assert_eq!(pack.omitted, vec![issue.id]);
+
+    let no_budget = ContextConfig {
+        budget_tokens: 0,
+        ..config
+    };
+    let pack = build_context(&repo, &index, &[(root.id, 1.0)], &no_budget).expect("no budget");
+    assert_eq!(pack.items.len(), 1, "{pack:#?}");
+    assert_eq!(pack.items[0].title, "Use Postgres");
+    assert!(pack.items[0].body.is_empty());
+}
@@ -0,0 +1,374 @@
+//! Dependency-free storage adapter for Ixchel.
+//!
+//! [`FlatIndex`] keeps entities, chunk vectors and relationships in one file
+//! under `.ixchel/data`, loads it into memory, and searches it by exact
+//! (brute-force) cosine similarity. It suits small repositories, tests and
+//! CI, where building a native database isn't worth it.
+
+mod store;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use ix_core::chunk::{DEFAULT_MAX_CHUNK_CHARS, chunk_body, pool_embeddings};
+use ix_core::entity::{EntityKind, kind_from_id};
+use ix_core::graph::{EdgeFilter, Neighbor};
+use ix_core::index::{
+    IndexBackend, IndexMetadata, IndexMetadataStatus, SearchFacets, SearchFilters, SearchHit,
+    SearchRequest, SyncStats, group_chunk_hits, search_with_mode,
+};
+use ix_core::lexical::LexicalIndex;
+use ix_core::markdown::{body_line_offset, get_string, get_string_list, parse_markdown};
+use ix_core::repo::{IxchelRepo, extract_relationships, iter_entity_paths, normalize_path};
+use ix_core::similarity::{EntityRef, EntityVector, cosine_similarity};
+use ix_embeddings::{Embedder, ModelStore};
+use store::{FlatStore, StoredChunk, StoredEntity};
+
+const BACKEND_NAME: &str = "flat";
+
+pub struct FlatIndex {
+    repo_root: PathBuf,
+    db_path: PathBuf,
+    store: FlatStore,
+    lexical: LexicalIndex,
+    embedder: Embedder,
+}
+
+impl FlatIndex {
+    pub fn open(repo: &IxchelRepo) -> Result<Self> {
+        let models = ModelStore::from_config(repo.paths.models_dir(), &repo.config.models);
+        let embedder = Embedder::with_model_store(&repo.index_embedding_config(), &models)
+            .map_err(|e| anyhow::anyhow!("Failed to initialize embedder: {e}"))?
+            .with_cache(repo.paths.embedding_cache_dir());
+        Self::open_with_embedder(repo, embedder)
+    }
+
+    pub fn open_with_embedder(repo: &IxchelRepo, embedder: Embedder) -> Result<Self> {
+        let repo_root = repo.paths.repo_root().to_path_buf();
+        let db_path = repo.index_path();
+
+        let store = FlatStore::load(&db_path)?.unwrap_or_default();
+        let lexical = store.lexical_index();
+
+        Ok(Self {
+            repo_root,
+            db_path,
+            store,
+            lexical,
+            embedder,
+        })
+    }
+
+    /// The file the index is stored in.
+    pub fn path(&self) -> PathBuf {
+        FlatStore::path(&self.db_path)
+    }
+
+    /// Best chunk of each entity passing `filters`, nearest to the query
+    /// embedding first.
+    fn search_vector(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let embedding = self.embed_query(query)?;
+
+        let mut hits = Vec::new();
+        for (id, entity) in &self.store.entities {
+            if !filters.matches(entity.kind(), &entity.facets) {
+                continue;
+            }
+            let best = entity
+                .chunks
+                .iter()
+                .map(|stored| (stored, cosine_similarity(&embedding, &stored.vector)))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((stored, similarity)) = best else {
+                continue;
+            };
+            hits.push(
+                SearchHit {
+                    id: id.clone(),
+                    kind: entity.kind(),
+                    title: entity.title.clone(),
+                    passage: Some(stored.chunk.clone()),
+                    ..SearchHit::default()
+                }
+                .with_facets(entity.facets.clone())
+                .with_cosine_distance(1.0 - similarity),
+            );
+        }
+
+        Ok(group_chunk_hits(hits, limit))
+    }
+
+    /// Entities nearest to `vector` by their pooled vectors, skipping
+    /// `exclude`.
+    fn nearest_to_vector(
+        &self,
+        vector: &[f32],
+        limit: usize,
+        exclude: Option<&str>,
+    ) -> Vec<SearchHit> {
+        if limit == 0 || vector.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits = self
+            .store
+            .entities
+            .iter()
+            .filter(|(id, _)| exclude != Some(id.as_str()))
+            .map(|(id, entity)| {
+                SearchHit {
+                    id: id.clone(),
+                    kind: entity.kind(),
+                    title: entity.title.clone(),
+                    ..SearchHit::default()
+                }
+                .with_facets(entity.facets.clone())
+                .with_cosine_distance(1.0 - cosine_similarity(vector, &entity.vector))
+            })
+            .collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+
+    /// Metadata a sync with the current embedder would write.
+    fn expected_metadata(&self) -> IndexMetadata {
+        IndexMetadata::current(
+            BACKEND_NAME,
+            self.embedder.provider_name(),
+            self.embedder.model_name(),
+            self.embedder.dimension(),
+            self.embedder.document_prefix(),
+        )
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embedder
+            .embed_query(text)
+            .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
+    }
+
+    fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.embedder
+            .embed_documents(texts)
+            .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
+    }
+}
+
+impl IndexBackend for FlatIndex {
+    /// Sync entities from the filesystem into the store file.
+    ///
+    /// Entities whose content hash and path are unchanged keep their stored
+    /// vectors; everything is re-embedded when the embedder changed.
+    fn sync(&mut self, repo: &IxchelRepo) -> Result<SyncStats> {
+        let cache_before = self.embedder.cache_stats();
+
+        // Vectors from a different embedder or index layout can't be reused
+        let mut previous = if self.metadata()?.needs_rebuild() {
+            BTreeMap::new()
+        } else {
+            std::mem::take(&mut self.store.entities)
+        };
+
+        let mut stats = SyncStats::default();
+        let mut entities: BTreeMap<String, StoredEntity> = BTreeMap::new();
+
+        for entity_path in iter_entity_paths(repo)? {
+            stats.scanned += 1;
+
+            let raw = std::fs::read_to_string(&entity_path)
+                .with_context(|| format!("Failed to read {}", entity_path.display()))?;
+            let doc = parse_markdown(&entity_path, &raw)?;
+
+            let id = get_string(&doc.frontmatter, "id")
+                .or_else(|| {
+                    entity_path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .map(std::string::ToString::to_string)
+                })
+                .unwrap_or_default();
+            if id.trim().is_empty() {
+                continue;
+            }
+
+            let content_hash = blake3::hash(raw.as_bytes()).to_hex().to_string();
+            let normalized_path = normalize_path(&self.repo_root, &entity_path);
+
+            match previous.remove(&id) {
+                Some(entity)
+                    if entity.content_hash == content_hash
+                        && entity.facets.path == normalized_path =>
+                {
+                    stats.unchanged += 1;
+                    entities.insert(id, entity);
+                    continue;
+                }
+                Some(_) => stats.modified += 1,
+                None => stats.added += 1,
+            }
+
+            let kind = get_string(&doc.frontmatter, "type")
+                .and_then(|t| t.parse::<EntityKind>().ok())
+                .or_else(|| kind_from_id(&id))
+                .unwrap_or(EntityKind::Report);
+
+            let title = get_string(&doc.frontmatter, "title").unwrap_or_default();
+            let tags = get_string_list(&doc.frontmatter, "tags");
+            let facets = SearchFacets {
+                path: normalized_path,
+                ..SearchFacets::from_frontmatter(&doc.frontmatter)
+            };
+
+            let chunks = chunk_body(&doc.body, body_line_offset(&raw), DEFAULT_MAX_CHUNK_CHARS);
+            let chunk_texts = chunks
+                .iter()
+                .map(|chunk| chunk.embedding_text(&title, &tags, kind))
+                .collect::<Vec<_>>();
+            let chunk_embeddings =
+                self.embed_documents(&chunk_texts.iter().map(String::as_str).collect::<Vec<_>>())?;
+            let vector = pool_embeddings(&chunk_embeddings);
+
+            entities.insert(
+                id,
+                StoredEntity {
+                    kind: kind.as_str().to_string(),
+                    title,
+                    content_hash,
+                    facets,
+                    vector,
+                    chunks: chunks
+                        .into_iter()
+                        .zip(chunk_embeddings)
+                        .map(|(chunk, vector)| StoredChunk { chunk, vector })
+                        .collect(),
+                    relationships: extract_relationships(&doc.frontmatter),
+                },
+            );
+        }
+
+        // Whatever wasn't seen on disk has been deleted
+        stats.deleted = u32::try_from(previous.len()).unwrap_or(u32::MAX);
+
+        self.store.entities = entities;
+        self.lexical = self.store.lexical_index();
+        self.store.save(&self.db_path)?;
+        self.expected_metadata().save(&self.db_path)?;
+
+        let cache = self.embedder.cache_stats().since(cache_before);
+        stats.cache_hits = cache.hits;
+        stats.cache_misses = cache.misses;
+        Ok(stats)
+    }
+
+    fn search(&self, request: &SearchRequest) -> Result<Vec<SearchHit>> {
+        // Vectors from another embedder would be compared silently otherwise
+        self.metadata()?.ensure_compatible()?;
+        let hits = search_with_mode(
+            request.mode,
+            request.window(),
+            |n| self.search_vector(&request.query, n, &request.filters),
+            |n| {
+                Ok(self
+                    .lexical
+                    .search_filtered(&request.query, n, &request.filters))
+            },
+        )?;
+        Ok(request.page(hits))
+    }
+
+    fn related(&self, id: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let entity = self
+            .store
+            .entities
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("{id} is not in the index. Run `ixchel sync`."))?;
+        Ok(self.nearest_to_vector(&entity.vector, limit, Some(id)))
+    }
+
+    fn nearest_entities(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let vector = pool_embeddings(&self.embed_documents(&[text])?);
+        Ok(self.nearest_to_vector(&vector, limit, None))
+    }
+
+    fn entity_vectors(&self) -> Result<Vec<EntityVector>> {
+        Ok(self
+            .store
+            .entities
+            .iter()
+            .map(|(id, entity)| entity.entity_vector(id))
+            .collect())
+    }
+
+    fn entity(&self, id: &str) -> Result<Option<EntityRef>> {
+        Ok(self
+            .store
+            .entities
+            .get(id)
+            .map(|entity| entity.entity_ref(id)))
+    }
+
+    fn neighbors(&self, id: &str, filter: &EdgeFilter) -> Result<Vec<Neighbor>> {
+        let Some(entity) = self.store.entities.get(id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut out = Vec::new();
+        if filter.direction.outgoing() {
+            for (rel, targets) in &entity.relationships {
+                if !filter.allows(rel) {
+                    continue;
+                }
+                for target in targets {
+                    let Some(other) = self.store.entities.get(target) else {
+                        continue;
+                    };
+                    out.push(Neighbor {
+                        entity: other.entity_ref(target),
+                        rel: rel.to_ascii_lowercase(),
+                        outgoing: true,
+                    });
+                }
+            }
+        }
+        if filter.direction.incoming() {
+            for (other_id, other) in &self.store.entities {
+                for (rel, targets) in &other.relationships {
+                    if filter.allows(rel) && targets.iter().any(|target| target == id) {
+                        out.push(Neighbor {
+                            entity: other.entity_ref(other_id),
+                            rel: rel.to_ascii_lowercase(),
+                            outgoing: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        out.sort_by(|a, b| {
+            (&a.rel, &a.entity.id, !a.outgoing).cmp(&(&b.rel, &b.entity.id, !b.outgoing))
+        });
+        out.dedup();
+        Ok(out)
+    }
+
+    fn health_check(&self) -> Result<()> {
+        self.metadata()?.ensure_compatible()
+    }
+
+    fn metadata(&self) -> Result<IndexMetadataStatus> {
+        Ok(IndexMetadataStatus {
+            expected: self.expected_metadata(),
+            stored: IndexMetadata::load(&self.db_path)?,
+        })
+    }
+}
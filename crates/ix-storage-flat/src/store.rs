@@ -0,0 +1,219 @@
+//! The single file a [`FlatIndex`](crate::FlatIndex) keeps its entities in.
+//!
+//! Layout: the [`MAGIC`] tag, the length of a JSON header as a little-endian
+//! `u64`, the header (every entity without its vectors), then each vector as
+//! a little-endian `u32` length followed by its `f32`s, in header order.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ix_core::chunk::Chunk;
+use ix_core::entity::EntityKind;
+use ix_core::index::{SearchFacets, write_atomic};
+use ix_core::lexical::LexicalIndex;
+use ix_core::similarity::{EntityRef, EntityVector};
+use serde::{Deserialize, Serialize};
+
+/// Identifies the file format; bump the version when the layout changes.
+const MAGIC: &[u8; 8] = b"IXFLAT01";
+
+/// Every indexed entity, by ID.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FlatStore {
+    pub entities: BTreeMap<String, StoredEntity>,
+}
+
+/// An entity as of the sync that last read its file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEntity {
+    pub kind: String,
+    pub title: String,
+    /// Content hash (blake3) of the file at last sync.
+    pub content_hash: String,
+    /// Search facets; `path` is the file relative to the repo root.
+    pub facets: SearchFacets,
+    /// Pooled (mean) vector of the chunks.
+    #[serde(skip)]
+    pub vector: Vec<f32>,
+    pub chunks: Vec<StoredChunk>,
+    /// Relationships declared in frontmatter, including targets that aren't
+    /// indexed.
+    pub relationships: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredChunk {
+    pub chunk: Chunk,
+    #[serde(skip)]
+    pub vector: Vec<f32>,
+}
+
+impl StoredEntity {
+    pub fn kind(&self) -> Option<EntityKind> {
+        self.kind.parse::<EntityKind>().ok()
+    }
+
+    pub fn entity_ref(&self, id: &str) -> EntityRef {
+        EntityRef {
+            id: id.to_string(),
+            kind: self.kind(),
+            title: self.title.clone(),
+        }
+    }
+
+    /// The pooled vector, then each chunk's, in file order.
+    fn vectors(&self) -> impl Iterator<Item = &Vec<f32>> {
+        std::iter::once(&self.vector).chain(self.chunks.iter().map(|c| &c.vector))
+    }
+
+    fn vectors_mut(&mut self) -> impl Iterator<Item = &mut Vec<f32>> {
+        std::iter::once(&mut self.vector).chain(self.chunks.iter_mut().map(|c| &mut c.vector))
+    }
+
+    pub fn entity_vector(&self, id: &str) -> EntityVector {
+        EntityVector {
+            id: id.to_string(),
+            kind: self.kind(),
+            title: self.title.clone(),
+            vector: self.vector.clone(),
+        }
+    }
+}
+
+impl FlatStore {
+    /// The store lives next to the other sidecars, e.g. `data/ixchel.flat`.
+    pub fn path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".flat");
+        db_path.with_file_name(name)
+    }
+
+    /// Read the store kept for `db_path`, or `None` before the first sync.
+    pub fn load(db_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(db_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw =
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::decode(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+            .map(Some)
+    }
+
+    pub fn save(&self, db_path: &Path) -> Result<()> {
+        let path = Self::path(db_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        write_atomic(&path, self.encode()?)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let header = serde_json::to_vec(self)?;
+        let mut out = Vec::with_capacity(MAGIC.len() + 8 + header.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        out.extend_from_slice(&header);
+        for vector in self.entities.values().flat_map(StoredEntity::vectors) {
+            let len = u32::try_from(vector.len()).context("Vector too long")?;
+            out.extend_from_slice(&len.to_le_bytes());
+            for value in vector {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        Ok(out)
+    }
+
+    fn decode(raw: &[u8]) -> Result<Self> {
+        let mut reader = Reader(raw);
+        anyhow::ensure!(reader.take(MAGIC.len())? == MAGIC, "Not a flat index file");
+        let header_len = usize::try_from(u64::from_le_bytes(reader.array()?))?;
+        let mut store: Self = serde_json::from_slice(reader.take(header_len)?)?;
+        for vector in store
+            .entities
+            .values_mut()
+            .flat_map(StoredEntity::vectors_mut)
+        {
+            let len = u32::from_le_bytes(reader.array()?) as usize;
+            *vector = (0..len)
+                .map(|_| reader.array().map(f32::from_le_bytes))
+                .collect::<Result<_>>()?;
+        }
+        anyhow::ensure!(reader.0.is_empty(), "Trailing bytes after the last vector");
+        Ok(store)
+    }
+
+    /// BM25 index over the stored chunks.
+    pub fn lexical_index(&self) -> LexicalIndex {
+        let mut index = LexicalIndex::new();
+        for (id, entity) in &self.entities {
+            let Some(kind) = entity.kind() else {
+                continue;
+            };
+            for stored in &entity.chunks {
+                index.add_with_facets(id, kind, &entity.title, &entity.facets, &stored.chunk);
+            }
+        }
+        index
+    }
+}
+
+/// Cursor over the encoded store.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        anyhow::ensure!(self.0.len() >= len, "Truncated flat index file");
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_entity() -> StoredEntity {
+        StoredEntity {
+            kind: "issue".to_string(),
+            title: "Pool connections".to_string(),
+            content_hash: "abc123".to_string(),
+            facets: SearchFacets::default(),
+            vector: vec![0.5, -0.25],
+            chunks: vec![StoredChunk {
+                chunk: Chunk::default(),
+                vector: vec![1.0, f32::MIN_POSITIVE],
+            }],
+            relationships: vec![("implements".to_string(), vec!["dec-1".to_string()])],
+        }
+    }
+
+    #[test]
+    fn test_store_round_trips_vectors_as_binary() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let db_path = temp.path().join("data").join("ixchel");
+        assert!(FlatStore::load(&db_path).expect("load").is_none());
+
+        let mut store = FlatStore::default();
+        store.entities.insert("iss-1".to_string(), stored_entity());
+        store.save(&db_path).expect("save");
+
+        let loaded = FlatStore::load(&db_path).expect("load").expect("store");
+        let entity = &loaded.entities["iss-1"];
+        assert_eq!(entity.vector, vec![0.5, -0.25]);
+        assert_eq!(entity.chunks[0].vector, vec![1.0, f32::MIN_POSITIVE]);
+        assert_eq!(entity.relationships, stored_entity().relationships);
+
+        let raw = std::fs::read(FlatStore::path(&db_path)).expect("read");
+        assert!(raw.starts_with(MAGIC));
+        assert!(FlatStore::decode(&raw[..raw.len() - 1]).is_err());
+    }
+}
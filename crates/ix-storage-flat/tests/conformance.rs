@@ -0,0 +1,26 @@
+use ix_core::entity::EntityKind;
+use ix_core::index::IndexBackend;
+use ix_storage_conformance::{hash_embedder, replace_entity_body, temp_repo};
+use ix_storage_flat::FlatIndex;
+
+ix_storage_conformance::index_backend_tests!(FlatIndex::open_with_embedder);
+
+#[test]
+fn store_is_one_file_under_the_data_dir() {
+    let (temp, repo) = temp_repo();
+    let source = repo
+        .create_entity(EntityKind::Source, "Alpha Source", None)
+        .expect("create source");
+    replace_entity_body(&repo, &source.id, "alpha");
+
+    let mut index =
+        FlatIndex::open_with_embedder(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let path = index.path();
+    assert!(
+        path.starts_with(temp.path().join(".ixchel/data")),
+        "{path:?}"
+    );
+    assert!(path.is_file(), "{path:?}");
+}
@@ -1,5 +1,6 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use bumpalo::Bump;
@@ -11,29 +12,30 @@ use helix_db::helix_engine::types::SecondaryIndex;
 use helix_db::helix_engine::vector_core::hnsw::HNSW;
 use helix_db::protocol::value::Value;
 use helix_db::utils::items::{Edge, Node};
-use helix_db::utils::label_hash::hash_label;
 use helix_db::utils::properties::ImmutablePropertiesMap;
+use ix_core::chunk::{Chunk, DEFAULT_MAX_CHUNK_CHARS, chunk_body, pool_embeddings};
 use ix_core::entity::{EntityKind, kind_from_id};
-use ix_core::index::{IndexBackend, SearchHit, SyncStats};
-use ix_core::markdown::{get_string, get_string_list, parse_markdown};
-use ix_core::repo::IxchelRepo;
-use ix_embeddings::Embedder;
+use ix_core::graph::{Direction, EdgeFilter, Neighbor};
+use ix_core::index::{
+    IndexBackend, IndexMetadata, IndexMetadataStatus, SearchFacets, SearchFilters, SearchHit,
+    SearchRequest, SyncStats, group_chunk_hits, search_with_mode,
+};
+use ix_core::lexical::LexicalIndex;
+use ix_core::manifest::SyncAction;
+use ix_core::markdown::{body_line_offset, get_string, get_string_list, parse_markdown};
+use ix_core::repo::{IxchelRepo, extract_relationships, iter_entity_paths, normalize_path};
+use ix_core::similarity::{EntityRef, EntityVector, cosine_similarity};
+use ix_embeddings::{Embedder, ModelStore};
 use ix_helixdb_ops as graph_ops;
-use serde_yaml::Value as YamlValue;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 const NODE_LABEL: &str = "IXCHEL_ENTITY";
-const METADATA_KEYS: &[&str] = &[
-    "id",
-    "type",
-    "title",
-    "status",
-    "date",
-    "created_at",
-    "updated_at",
-    "created_by",
-    "tags",
-];
+const CHUNK_LABEL: &str = "IXCHEL_CHUNK";
+const BACKEND_NAME: &str = "helixdb";
+/// Chunks fetched per requested hit, so several passages of one entity
+/// don't crowd other entities out of the results.
+const CHUNK_OVERSAMPLE: usize = 4;
 
 pub struct HelixDbIndex {
     repo_root: PathBuf,
@@ -44,17 +46,16 @@ pub struct HelixDbIndex {
 
 impl HelixDbIndex {
     pub fn open(repo: &IxchelRepo) -> Result<Self> {
-        let embedder = Embedder::with_config(&repo.config.embedding)
-            .map_err(|e| anyhow::anyhow!("Failed to initialize embedder: {e}"))?;
+        let models = ModelStore::from_config(repo.paths.models_dir(), &repo.config.models);
+        let embedder = Embedder::with_model_store(&repo.index_embedding_config(), &models)
+            .map_err(|e| anyhow::anyhow!("Failed to initialize embedder: {e}"))?
+            .with_cache(repo.paths.embedding_cache_dir());
         Self::open_with_embedder(repo, embedder)
     }
 
     pub fn open_with_embedder(repo: &IxchelRepo, embedder: Embedder) -> Result<Self> {
         let repo_root = repo.paths.repo_root().to_path_buf();
-        let db_path = repo
-            .paths
-            .ixchel_dir()
-            .join(PathBuf::from(&repo.config.storage.path));
+        let db_path = repo.index_path();
 
         std::fs::create_dir_all(&db_path)
             .with_context(|| format!("Failed to create {}", db_path.display()))?;
@@ -69,87 +70,251 @@ impl HelixDbIndex {
         })
     }
 
+    /// IDs of the entities `from_id` has a `rel` relationship to.
     pub fn outgoing(&self, from_id: &str, rel: &str) -> Result<Vec<String>> {
+        self.neighbor_ids(from_id, rel, Direction::Outgoing)
+    }
+
+    /// IDs of the entities that have a `rel` relationship to `to_id`.
+    pub fn incoming(&self, to_id: &str, rel: &str) -> Result<Vec<String>> {
+        self.neighbor_ids(to_id, rel, Direction::Incoming)
+    }
+
+    /// [`IndexBackend::neighbors`] of `id` across `rel` edges, as sorted IDs.
+    fn neighbor_ids(&self, id: &str, rel: &str, direction: Direction) -> Result<Vec<String>> {
+        if rel.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter = EdgeFilter::new(direction).with_labels(vec![rel.to_string()]);
+        let mut ids = self
+            .neighbors(id, &filter)?
+            .into_iter()
+            .map(|neighbor| neighbor.entity.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn rebuild_storage(&mut self) -> Result<()> {
+        self.storage.take();
+        if self.db_path.exists() {
+            std::fs::remove_dir_all(&self.db_path)
+                .with_context(|| format!("Failed to remove {}", self.db_path.display()))?;
+        }
+        for sidecar in [
+            IndexMetadata::sidecar_path(&self.db_path),
+            LexicalIndex::sidecar_path(&self.db_path),
+            SyncManifest::sidecar_path(&self.db_path),
+        ] {
+            if sidecar.exists() {
+                std::fs::remove_file(&sidecar)
+                    .with_context(|| format!("Failed to remove {}", sidecar.display()))?;
+            }
+        }
+        std::fs::create_dir_all(&self.db_path)
+            .with_context(|| format!("Failed to create {}", self.db_path.display()))?;
+
+        self.storage = Some(open_storage(&self.db_path)?);
+        Ok(())
+    }
+
+    /// Nearest chunks to the query embedding, grouped per entity. Filters are
+    /// checked inside the HNSW search so they don't eat into `limit`.
+    fn search_vector(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
         let storage = self
             .storage
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Storage not initialized"))?;
+        let embedding = self.embed_query(query)?;
+        let query_f64: Vec<f64> = embedding.iter().map(|&x| f64::from(x)).collect();
 
+        let arena = Bump::new();
         let rtxn = storage
             .graph_env
             .read_txn()
             .map_err(|e| anyhow::anyhow!("Failed to start read transaction: {e}"))?;
+        let label = arena.alloc_str(CHUNK_LABEL);
+        let candidates = limit.saturating_mul(CHUNK_OVERSAMPLE).max(limit);
+        let matches_filters =
+            |vector: &helix_db::helix_engine::vector_core::vector::HVector<'_>, txn: &RoTxn<'_>| {
+                chunk_matches(storage, txn, vector.id, filters)
+            };
+        let filter = [matches_filters];
+        let filter = (!filters.is_empty()).then_some(&filter[..]);
 
-        let Some(from_node) = lookup_node_by_entity_id(storage, &rtxn, from_id)? else {
-            return Ok(Vec::new());
-        };
+        let vector_results = storage
+            .vectors
+            .search(&rtxn, &query_f64, candidates, label, filter, false, &arena)
+            .map_err(|e| anyhow::anyhow!("Vector search failed: {e:?}"))?;
 
-        let label = rel.trim().to_ascii_uppercase();
-        if label.is_empty() {
-            return Ok(Vec::new());
-        }
+        let mut hits = Vec::new();
 
-        let label_hash = hash_label(&label, None);
-        let neighbors = graph_ops::outgoing_neighbors(storage, &rtxn, from_node, &label_hash)
-            .map_err(|e| anyhow::anyhow!("Failed to read outgoing neighbors: {e}"))?;
+        for hvector in vector_results {
+            let vector_id = hvector.id;
+            // HelixDB measures cosine distance, `1 - cosine similarity`
+            #[allow(clippy::cast_possible_truncation)]
+            let distance = hvector.get_distance() as f32;
 
-        let arena = Bump::new();
-        let mut out = neighbors
-            .into_iter()
-            .filter_map(|node_id| node_entity_id(storage, &rtxn, node_id, &arena).transpose())
-            .collect::<Result<Vec<_>>>()?;
+            let Some(node_id) = lookup_node_by_vector_id(storage, &rtxn, vector_id)? else {
+                continue;
+            };
 
-        out.sort();
-        out.dedup();
-        Ok(out)
+            let node = storage
+                .get_node(&rtxn, &node_id, &arena)
+                .map_err(|e| anyhow::anyhow!("Failed to get node: {e:?}"))?;
+            let property = |key: &str| {
+                node.get_property(key).and_then(|v| match v {
+                    Value::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+            };
+            let number = |key: &str| {
+                property(key)
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or_default()
+            };
+
+            let heading_path = property("heading_path")
+                .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+                .unwrap_or_default();
+
+            hits.push(
+                SearchHit {
+                    id: property("entity_id").unwrap_or_default(),
+                    kind: property("kind").and_then(|k| k.parse::<EntityKind>().ok()),
+                    title: property("title").unwrap_or_default(),
+                    passage: Some(Chunk {
+                        index: number("chunk_index"),
+                        heading_path,
+                        start_line: number("start_line"),
+                        end_line: number("end_line"),
+                        text: property("text").unwrap_or_default(),
+                    }),
+                    ..SearchHit::default()
+                }
+                .with_facets(chunk_facets(&node))
+                .with_cosine_distance(distance),
+            );
+        }
+
+        Ok(group_chunk_hits(hits, limit))
     }
 
-    pub fn incoming(&self, to_id: &str, rel: &str) -> Result<Vec<String>> {
+    /// BM25 matches from the lexical index written by the last sync.
+    fn search_lexical(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        Ok(LexicalIndex::load(&self.db_path)?
+            .map(|index| index.search_filtered(query, limit, filters))
+            .unwrap_or_default())
+    }
+
+    /// Entities nearest to `vector` in the entity vector index, skipping
+    /// `exclude`, scored by exact cosine similarity.
+    fn nearest_to_vector(
+        &self,
+        vector: &[f32],
+        limit: usize,
+        exclude: Option<&str>,
+    ) -> Result<Vec<SearchHit>> {
+        if limit == 0 || vector.is_empty() {
+            return Ok(Vec::new());
+        }
         let storage = self
             .storage
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Storage not initialized"))?;
+        let query_f64: Vec<f64> = vector.iter().map(|&x| f64::from(x)).collect();
 
+        let arena = Bump::new();
         let rtxn = storage
             .graph_env
             .read_txn()
             .map_err(|e| anyhow::anyhow!("Failed to start read transaction: {e}"))?;
+        let label = arena.alloc_str(NODE_LABEL);
+        let candidates = limit + usize::from(exclude.is_some());
 
-        let Some(to_node) = lookup_node_by_entity_id(storage, &rtxn, to_id)? else {
-            return Ok(Vec::new());
-        };
+        let vector_results =
+            storage
+                .vectors
+                .search::<fn(
+                    &helix_db::helix_engine::vector_core::vector::HVector<'_>,
+                    &RoTxn<'_>,
+                ) -> bool>(&rtxn, &query_f64, candidates, label, None, false, &arena)
+                .map_err(|e| anyhow::anyhow!("Vector search failed: {e:?}"))?;
 
-        let label = rel.trim().to_ascii_uppercase();
-        if label.is_empty() {
-            return Ok(Vec::new());
+        let mut hits = Vec::new();
+        for hvector in vector_results {
+            let Some(node_id) = lookup_node_by_vector_id(storage, &rtxn, hvector.id)? else {
+                continue;
+            };
+            let node = storage
+                .get_node(&rtxn, &node_id, &arena)
+                .map_err(|e| anyhow::anyhow!("Failed to get node: {e:?}"))?;
+            let entity = entity_vector(&node);
+            if exclude == Some(entity.id.as_str()) {
+                continue;
+            }
+            hits.push(
+                SearchHit {
+                    id: entity.id,
+                    kind: entity.kind,
+                    title: entity.title,
+                    ..SearchHit::default()
+                }
+                .with_facets(node_facets(&node))
+                .with_cosine_distance(1.0 - cosine_similarity(vector, &entity.vector)),
+            );
         }
 
-        let label_hash = hash_label(&label, None);
-        let neighbors = graph_ops::incoming_neighbors(storage, &rtxn, to_node, &label_hash)
-            .map_err(|e| anyhow::anyhow!("Failed to read incoming neighbors: {e}"))?;
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
 
-        let arena = Bump::new();
-        let mut out = neighbors
-            .into_iter()
-            .filter_map(|node_id| node_entity_id(storage, &rtxn, node_id, &arena).transpose())
-            .collect::<Result<Vec<_>>>()?;
+    /// Stored vectors of `entity_ids`, skipping ids that aren't indexed.
+    fn stored_entity_vectors(&self, entity_ids: &[String]) -> Result<Vec<EntityVector>> {
+        let storage = self
+            .storage
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Storage not initialized"))?;
+        let rtxn = storage
+            .graph_env
+            .read_txn()
+            .map_err(|e| anyhow::anyhow!("Failed to start read transaction: {e}"))?;
 
-        out.sort();
-        out.dedup();
+        let mut out = Vec::new();
+        for entity_id in entity_ids {
+            let Some(node_id) = lookup_node_by_entity_id(storage, &rtxn, entity_id)? else {
+                continue;
+            };
+            let arena = Bump::new();
+            let node = storage
+                .get_node(&rtxn, &node_id, &arena)
+                .map_err(|e| anyhow::anyhow!("Failed to get node: {e:?}"))?;
+            out.push(entity_vector(&node));
+        }
         Ok(out)
     }
 
-    fn rebuild_storage(&mut self) -> Result<()> {
-        self.storage.take();
-        if self.db_path.exists() {
-            std::fs::remove_dir_all(&self.db_path)
-                .with_context(|| format!("Failed to remove {}", self.db_path.display()))?;
-        }
-        std::fs::create_dir_all(&self.db_path)
-            .with_context(|| format!("Failed to create {}", self.db_path.display()))?;
-
-        self.storage = Some(open_storage(&self.db_path)?);
-        Ok(())
+    /// Metadata a sync with the current embedder would write.
+    fn expected_metadata(&self) -> IndexMetadata {
+        IndexMetadata::current(
+            BACKEND_NAME,
+            self.embedder.provider_name(),
+            self.embedder.model_name(),
+            self.embedder.dimension(),
+            self.embedder.document_prefix(),
+        )
     }
 
     fn begin_write_txn(&self) -> Result<RwTxn<'_>> {
@@ -166,9 +331,15 @@ impl HelixDbIndex {
             .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {e}"))
     }
 
-    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
         self.embedder
-            .embed(text)
+            .embed_query(text)
+            .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
+    }
+
+    fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.embedder
+            .embed_documents(texts)
             .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
     }
 
@@ -213,12 +384,45 @@ impl HelixDbIndex {
 }
 
 impl IndexBackend for HelixDbIndex {
+    /// Sync entities from the filesystem to the database.
+    ///
+    /// Compares content hashes against the stored manifest and only rewrites
+    /// the nodes, vectors and edges of changed entities.
+    #[allow(clippy::too_many_lines)]
     fn sync(&mut self, repo: &IxchelRepo) -> Result<SyncStats> {
-        self.rebuild_storage()?;
+        let cache_before = self.embedder.cache_stats();
+
+        // A different embedder or index layout can't be updated incrementally.
+        // Without a readable manifest the stored nodes can't be matched to
+        // files, and without a readable lexical index unchanged entities would
+        // drop out of lexical search.
+        let (mut manifest, mut lexical) = match (
+            SyncManifest::load(&self.db_path),
+            LexicalIndex::load(&self.db_path),
+        ) {
+            (Ok(Some(manifest)), Ok(Some(lexical))) if !self.metadata()?.needs_rebuild() => {
+                (manifest, lexical)
+            }
+            _ => {
+                self.rebuild_storage()?;
+                (SyncManifest::new(), LexicalIndex::new())
+            }
+        };
+        // Chunks of rewritten entities, swapped in with one reindex at the end
+        let mut lexical_stale: HashSet<String> = HashSet::new();
+        let mut lexical_added = LexicalIndex::new();
 
         let mut stats = SyncStats::default();
         let mut records: Vec<EntityRecord> = Vec::new();
+        let mut unchanged: Vec<EntityRecord> = Vec::new();
         let mut id_to_node: BTreeMap<String, u128> = BTreeMap::new();
+        let mut seen_entity_ids: HashSet<String> = HashSet::new();
+        let mut inserted_ids: HashSet<String> = HashSet::new();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
 
         let mut wtxn = self.begin_write_txn()?;
         let storage = self
@@ -245,6 +449,34 @@ impl IndexBackend for HelixDbIndex {
                 continue;
             }
 
+            seen_entity_ids.insert(id.clone());
+            let content_hash = blake3::hash(raw.as_bytes()).to_hex().to_string();
+            let normalized_path = normalize_path(&self.repo_root, &entity_path);
+
+            let action = manifest.action_for(&id, &content_hash, &normalized_path);
+            let previous = manifest.get(&id).cloned();
+
+            if let (SyncAction::Skip, Some(previous)) = (action, &previous) {
+                // Unchanged - its nodes and edges are already correct
+                stats.unchanged += 1;
+                id_to_node.insert(id, previous.written.node_id);
+                unchanged.push(EntityRecord {
+                    from_node: previous.written.node_id,
+                    rels: extract_relationships(&doc.frontmatter),
+                });
+                continue;
+            }
+
+            // Keep the entity node's ID so edges pointing at it stay valid
+            let node_id = match &previous {
+                Some(previous) => {
+                    delete_entity_content(storage, &mut wtxn, previous)?;
+                    lexical_stale.insert(id.clone());
+                    previous.written.node_id
+                }
+                None => Uuid::new_v4().as_u128(),
+            };
+
             let kind = get_string(&doc.frontmatter, "type")
                 .and_then(|t| t.parse::<EntityKind>().ok())
                 .or_else(|| kind_from_id(&id))
@@ -257,19 +489,35 @@ impl IndexBackend for HelixDbIndex {
 
             let entity_status = get_string(&doc.frontmatter, "status").unwrap_or_default();
 
-            let content_hash = blake3::hash(raw.as_bytes()).to_hex().to_string();
-            let normalized_path = normalize_path(&self.repo_root, &entity_path);
+            let facets = SearchFacets {
+                path: normalized_path.clone(),
+                ..SearchFacets::from_frontmatter(&doc.frontmatter)
+            };
 
-            let embedding_text = build_embedding_text(&title, &doc.body, &tags, kind);
-            let embedding = self.embed(&embedding_text)?;
+            let chunks = chunk_body(&doc.body, body_line_offset(&raw), DEFAULT_MAX_CHUNK_CHARS);
+            let chunk_texts = chunks
+                .iter()
+                .map(|chunk| chunk.embedding_text(&title, &tags, kind))
+                .collect::<Vec<_>>();
+            let chunk_embeddings =
+                self.embed_documents(&chunk_texts.iter().map(String::as_str).collect::<Vec<_>>())?;
+            let embedding = pool_embeddings(&chunk_embeddings);
+
+            let mut chunk_node_ids = Vec::with_capacity(chunks.len());
+            for (chunk, chunk_embedding) in chunks.iter().zip(&chunk_embeddings) {
+                let vector_id = insert_vector(storage, &mut wtxn, chunk_embedding, CHUNK_LABEL)?;
+                chunk_node_ids.push(put_chunk_node(
+                    storage, &mut wtxn, &id, kind, &title, &facets, chunk, vector_id,
+                )?);
+                lexical_added.add_with_facets(&id, kind, &title, &facets, chunk);
+            }
 
-            let node_id = Uuid::new_v4().as_u128();
-            let vector_id = insert_vector(storage, &mut wtxn, &embedding)?;
+            let vector_id = insert_vector(storage, &mut wtxn, &embedding, NODE_LABEL)?;
 
             let arena = Bump::new();
             let label = arena.alloc_str(NODE_LABEL);
 
-            let mut props: Vec<(&str, Value)> = Vec::with_capacity(10);
+            let mut props: Vec<(&str, Value)> = Vec::with_capacity(11);
             props.push((arena.alloc_str("id"), Value::String(id.clone())));
             props.push((
                 arena.alloc_str("kind"),
@@ -277,14 +525,24 @@ impl IndexBackend for HelixDbIndex {
             ));
             props.push((arena.alloc_str("title"), Value::String(title.clone())));
             props.push((arena.alloc_str("status"), Value::String(entity_status)));
-            props.push((arena.alloc_str("file_path"), Value::String(normalized_path)));
-            props.push((arena.alloc_str("content_hash"), Value::String(content_hash)));
+            props.push((
+                arena.alloc_str("file_path"),
+                Value::String(normalized_path.clone()),
+            ));
+            props.push((
+                arena.alloc_str("content_hash"),
+                Value::String(content_hash.clone()),
+            ));
             props.push((
                 arena.alloc_str("vector_id"),
                 Value::String(vector_id.to_string()),
             ));
             props.push((arena.alloc_str("tags"), Value::String(tags_json)));
             props.push((arena.alloc_str("body"), Value::String(doc.body.clone())));
+            props.push((
+                arena.alloc_str("embedding"),
+                Value::String(serde_json::to_string(&embedding).unwrap_or_default()),
+            ));
 
             let properties = ImmutablePropertiesMap::new(props.len(), props.into_iter(), &arena);
             let node = Node {
@@ -299,95 +557,177 @@ impl IndexBackend for HelixDbIndex {
             graph_ops::update_secondary_indices(storage, &mut wtxn, &node)
                 .map_err(|e| anyhow::anyhow!("Failed to update secondary index: {e}"))?;
 
-            id_to_node.insert(id.clone(), node_id);
+            if previous.is_some() {
+                stats.modified += 1;
+            } else {
+                inserted_ids.insert(id.clone());
+                stats.added += 1;
+            }
+
+            manifest.insert(
+                id.clone(),
+                ManifestEntry {
+                    content_hash,
+                    file_path: normalized_path,
+                    last_synced: now,
+                    written: WrittenNodes {
+                        node_id,
+                        chunk_node_ids,
+                    },
+                },
+            );
+            id_to_node.insert(id, node_id);
             records.push(EntityRecord {
                 from_node: node_id,
                 rels: extract_relationships(&doc.frontmatter),
             });
+        }
 
-            stats.added += 1;
+        // Find and delete entities that no longer exist on disk
+        let manifest_ids: Vec<String> = manifest.entity_ids().cloned().collect();
+        for entity_id in manifest_ids {
+            if seen_entity_ids.contains(&entity_id) {
+                continue;
+            }
+            if let Some(entry) = manifest.remove(&entity_id) {
+                delete_entity_content(storage, &mut wtxn, &entry)?;
+                graph_ops::delete_incoming_edges(storage, &mut wtxn, entry.written.node_id)
+                    .map_err(|e| anyhow::anyhow!("Failed to delete edges: {e}"))?;
+                lexical_stale.insert(entity_id);
+                stats.deleted += 1;
+            }
         }
 
+        // Changed entities get all their edges back; unchanged ones only gain
+        // edges to entities that didn't exist when they were last synced
+        for mut record in unchanged {
+            for (_, targets) in &mut record.rels {
+                targets.retain(|target| inserted_ids.contains(target));
+            }
+            records.push(record);
+        }
         self.insert_edges(&mut wtxn, &id_to_node, records)?;
 
-        Self::commit_txn(wtxn)?;
+        lexical.remove_entities(lexical_stale.iter().map(String::as_str));
+        lexical.append(lexical_added);
+
+        // The manifest must never describe nodes the database doesn't have:
+        // write the sidecars before committing, and if anything fails drop
+        // the manifest so the next sync rebuilds instead of trusting it
+        let saved = lexical
+            .save(&self.db_path)
+            .and_then(|()| manifest.save(&self.db_path))
+            .and_then(|()| Self::commit_txn(wtxn));
+        if let Err(err) = saved {
+            SyncManifest::discard(&self.db_path)?;
+            return Err(err);
+        }
+        self.expected_metadata().save(&self.db_path)?;
+
+        let cache = self.embedder.cache_stats().since(cache_before);
+        stats.cache_hits = cache.hits;
+        stats.cache_misses = cache.misses;
         Ok(stats)
     }
 
-    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+    fn search(&self, request: &SearchRequest) -> Result<Vec<SearchHit>> {
+        // Vectors from another embedder would be compared silently otherwise
+        self.metadata()?.ensure_compatible()?;
+        let hits = search_with_mode(
+            request.mode,
+            request.window(),
+            |n| self.search_vector(&request.query, n, &request.filters),
+            |n| self.search_lexical(&request.query, n, &request.filters),
+        )?;
+        Ok(request.page(hits))
+    }
+
+    fn related(&self, id: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let entity = self
+            .stored_entity_vectors(&[id.to_string()])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("{id} is not in the index. Run `ixchel sync`."))?;
+        self.nearest_to_vector(&entity.vector, limit, Some(id))
+    }
+
+    fn nearest_entities(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let vector = pool_embeddings(&self.embed_documents(&[text])?);
+        self.nearest_to_vector(&vector, limit, None)
+    }
+
+    fn entity_vectors(&self) -> Result<Vec<EntityVector>> {
+        // The manifest lists every entity node the last sync committed
+        let mut ids = SyncManifest::load(&self.db_path)?
+            .map(|manifest| manifest.entity_ids().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        ids.sort();
+        self.stored_entity_vectors(&ids)
+    }
+
+    fn entity(&self, id: &str) -> Result<Option<EntityRef>> {
+        Ok(self
+            .stored_entity_vectors(&[id.to_string()])?
+            .first()
+            .map(EntityRef::from))
+    }
+
+    fn neighbors(&self, id: &str, filter: &EdgeFilter) -> Result<Vec<Neighbor>> {
         let storage = self
             .storage
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Storage not initialized"))?;
-        let embedding = self.embed(query)?;
-        let query_f64: Vec<f64> = embedding.iter().map(|&x| f64::from(x)).collect();
-
-        let arena = Bump::new();
         let rtxn = storage
             .graph_env
             .read_txn()
             .map_err(|e| anyhow::anyhow!("Failed to start read transaction: {e}"))?;
-        let label = arena.alloc_str(NODE_LABEL);
-
-        let vector_results =
-            storage
-                .vectors
-                .search::<fn(
-                    &helix_db::helix_engine::vector_core::vector::HVector<'_>,
-                    &RoTxn<'_>,
-                ) -> bool>(&rtxn, &query_f64, limit, label, None, false, &arena)
-                .map_err(|e| anyhow::anyhow!("Vector search failed: {e:?}"))?;
 
-        let mut hits = Vec::new();
+        let Some(node_id) = lookup_node_by_entity_id(storage, &rtxn, id)? else {
+            return Ok(Vec::new());
+        };
 
-        for hvector in vector_results {
-            let vector_id = hvector.id;
-            let distance = hvector.get_distance();
-            #[allow(clippy::cast_possible_truncation)]
-            let score = (1.0 / (1.0 + distance)) as f32;
+        let mut edges = Vec::new();
+        if filter.direction.outgoing() {
+            let outgoing = graph_ops::outgoing_edges(storage, &rtxn, node_id)
+                .map_err(|e| anyhow::anyhow!("Failed to read outgoing edges: {e}"))?;
+            edges.extend(outgoing.into_iter().map(|edge| (edge, true)));
+        }
+        if filter.direction.incoming() {
+            let incoming = graph_ops::incoming_edges(storage, &rtxn, node_id)
+                .map_err(|e| anyhow::anyhow!("Failed to read incoming edges: {e}"))?;
+            edges.extend(incoming.into_iter().map(|edge| (edge, false)));
+        }
 
-            let Some(node_id) = lookup_node_by_vector_id(storage, &rtxn, vector_id)? else {
+        let arena = Bump::new();
+        let mut out = Vec::new();
+        for ((edge_id, neighbor_id), outgoing) in edges {
+            let edge = storage
+                .get_edge(&rtxn, &edge_id, &arena)
+                .map_err(|e| anyhow::anyhow!("Failed to get edge: {e:?}"))?;
+            if !filter.allows(edge.label) {
                 continue;
-            };
-
+            }
             let node = storage
-                .get_node(&rtxn, &node_id, &arena)
+                .get_node(&rtxn, &neighbor_id, &arena)
                 .map_err(|e| anyhow::anyhow!("Failed to get node: {e:?}"))?;
-
-            let id = node
-                .get_property("id")
-                .and_then(|v| match v {
-                    Value::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .unwrap_or_default();
-
-            let title = node
-                .get_property("title")
-                .and_then(|v| match v {
-                    Value::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .unwrap_or_default();
-
-            let kind = node.get_property("kind").and_then(|v| match v {
-                Value::String(s) => s.parse::<EntityKind>().ok(),
-                _ => None,
-            });
-
-            hits.push(SearchHit {
-                score,
-                id,
-                kind,
-                title,
+            out.push(Neighbor {
+                entity: EntityRef::from(&entity_vector(&node)),
+                rel: edge.label.to_ascii_lowercase(),
+                outgoing,
             });
         }
 
-        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
-        Ok(hits)
+        out.sort_by(|a, b| {
+            (&a.rel, &a.entity.id, !a.outgoing).cmp(&(&b.rel, &b.entity.id, !b.outgoing))
+        });
+        out.dedup();
+        Ok(out)
     }
 
     fn health_check(&self) -> Result<()> {
+        self.metadata()?.ensure_compatible()?;
         let _rtxn = self
             .storage
             .as_ref()
@@ -397,8 +737,27 @@ impl IndexBackend for HelixDbIndex {
             .map_err(|e| anyhow::anyhow!("Failed to start read transaction: {e}"))?;
         Ok(())
     }
+
+    fn metadata(&self) -> Result<IndexMetadataStatus> {
+        Ok(IndexMetadataStatus {
+            expected: self.expected_metadata(),
+            stored: IndexMetadata::load(&self.db_path)?,
+        })
+    }
+}
+
+/// Nodes a sync wrote for an entity, recorded in its manifest entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WrittenNodes {
+    /// Entity node, kept across updates so incoming edges stay valid.
+    node_id: u128,
+    /// Chunk nodes written for the entity's body.
+    chunk_node_ids: Vec<u128>,
 }
 
+type ManifestEntry = ix_core::manifest::ManifestEntry<WrittenNodes>;
+type SyncManifest = ix_core::manifest::SyncManifest<WrittenNodes>;
+
 #[derive(Debug)]
 struct EntityRecord {
     from_node: u128,
@@ -427,55 +786,14 @@ fn open_storage(db_path: &Path) -> Result<HelixGraphStorage> {
         .map_err(|e| anyhow::anyhow!("Failed to create storage: {e:?}"))
 }
 
-fn normalize_path(repo_root: &Path, file_path: &Path) -> String {
-    file_path
-        .strip_prefix(repo_root)
-        .unwrap_or(file_path)
-        .to_string_lossy()
-        .replace('\\', "/")
-}
-
-fn iter_entity_paths(repo: &IxchelRepo) -> Result<Vec<PathBuf>> {
-    let mut out = Vec::new();
-
-    for kind in [
-        EntityKind::Decision,
-        EntityKind::Issue,
-        EntityKind::Idea,
-        EntityKind::Report,
-        EntityKind::Source,
-        EntityKind::Citation,
-        EntityKind::Agent,
-        EntityKind::Session,
-    ] {
-        let dir = repo.paths.kind_dir(kind);
-        if !dir.exists() {
-            continue;
-        }
-
-        for entry in
-            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
-        {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("md") {
-                continue;
-            }
-            out.push(path);
-        }
-    }
-
-    out.sort();
-    Ok(out)
-}
-
 fn insert_vector<'a>(
     storage: &'a HelixGraphStorage,
     wtxn: &mut RwTxn<'a>,
     embedding: &[f32],
+    vector_label: &str,
 ) -> Result<u128> {
     let arena = Bump::new();
-    let label = arena.alloc_str(NODE_LABEL);
+    let label = arena.alloc_str(vector_label);
 
     let embedding_f64: Vec<f64> = embedding.iter().map(|&x| f64::from(x)).collect();
     let vector =
@@ -510,76 +828,176 @@ fn lookup_node_by_entity_id(
         .map_err(|e| anyhow::anyhow!("Failed to lookup id: {e}"))
 }
 
-fn node_entity_id(
+/// Whether the chunk node behind `vector_id` belongs to an entity passing
+/// `filters`.
+fn chunk_matches(
     storage: &HelixGraphStorage,
     rtxn: &RoTxn<'_>,
-    node_id: u128,
-    arena: &Bump,
-) -> Result<Option<String>> {
-    let node = storage
-        .get_node(rtxn, &node_id, arena)
-        .map_err(|e| anyhow::anyhow!("Failed to get node: {e:?}"))?;
+    vector_id: u128,
+    filters: &SearchFilters,
+) -> bool {
+    let Ok(Some(node_id)) = lookup_node_by_vector_id(storage, rtxn, vector_id) else {
+        return false;
+    };
+    let arena = Bump::new();
+    let Ok(node) = storage.get_node(rtxn, &node_id, &arena) else {
+        return false;
+    };
+    filters.matches(
+        string_property(&node, "kind").and_then(|k| k.parse::<EntityKind>().ok()),
+        &node_facets(&node),
+    )
+}
+
+/// An entity node with the pooled embedding stored on it during sync.
+fn entity_vector(node: &Node<'_>) -> EntityVector {
+    EntityVector {
+        id: string_property(node, "id").unwrap_or_default(),
+        kind: string_property(node, "kind").and_then(|k| k.parse::<EntityKind>().ok()),
+        title: string_property(node, "title").unwrap_or_default(),
+        vector: string_property(node, "embedding")
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    }
+}
 
-    Ok(node.get_property("id").and_then(|v| match v {
+fn string_property(node: &Node<'_>, key: &str) -> Option<String> {
+    node.get_property(key).and_then(|v| match v {
         Value::String(s) => Some(s.clone()),
         _ => None,
-    }))
+    })
 }
 
-fn build_embedding_text(title: &str, body: &str, tags: &[String], kind: EntityKind) -> String {
-    let tags_str = if tags.is_empty() {
-        String::new()
-    } else {
-        tags.join(", ")
-    };
-
-    format!(
-        "{title}\n\n{body}\n\nTags: {tags_str}\nType: {}\n",
-        kind.as_str()
-    )
+/// Entity fields stored on an entity node, or copied onto a chunk node by
+/// [`put_chunk_node`].
+fn node_facets(node: &Node<'_>) -> SearchFacets {
+    let property = |key: &str| string_property(node, key);
+    SearchFacets {
+        path: property("file_path").unwrap_or_default(),
+        status: property("status").unwrap_or_default(),
+        tags: property("tags")
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        created_at: property("created_at"),
+        updated_at: property("updated_at"),
+    }
 }
 
-fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<String>)> {
-    let mut rels = Vec::new();
-    let mut seen: BTreeSet<String> = BTreeSet::new();
-
-    for (key, value) in frontmatter {
-        let YamlValue::String(key) = key else {
-            continue;
-        };
-
-        if METADATA_KEYS.contains(&key.as_str()) {
-            continue;
+/// Store a chunk as its own node, keyed by its vector for search lookups.
+/// The entity's search facets are copied onto it for filtered search.
+#[allow(clippy::too_many_arguments)]
+fn put_chunk_node<'a>(
+    storage: &'a HelixGraphStorage,
+    wtxn: &mut RwTxn<'a>,
+    entity_id: &str,
+    kind: EntityKind,
+    title: &str,
+    facets: &SearchFacets,
+    chunk: &Chunk,
+    vector_id: u128,
+) -> Result<u128> {
+    let arena = Bump::new();
+    let label = arena.alloc_str(CHUNK_LABEL);
+    let heading_path = serde_json::to_string(&chunk.heading_path).unwrap_or_default();
+    let tags = serde_json::to_string(&facets.tags).unwrap_or_default();
+
+    let mut props: Vec<(&str, Value)> = vec![
+        (
+            arena.alloc_str("entity_id"),
+            Value::String(entity_id.to_string()),
+        ),
+        (
+            arena.alloc_str("kind"),
+            Value::String(kind.as_str().to_string()),
+        ),
+        (arena.alloc_str("title"), Value::String(title.to_string())),
+        (
+            arena.alloc_str("chunk_index"),
+            Value::String(chunk.index.to_string()),
+        ),
+        (arena.alloc_str("heading_path"), Value::String(heading_path)),
+        (
+            arena.alloc_str("start_line"),
+            Value::String(chunk.start_line.to_string()),
+        ),
+        (
+            arena.alloc_str("end_line"),
+            Value::String(chunk.end_line.to_string()),
+        ),
+        (arena.alloc_str("text"), Value::String(chunk.text.clone())),
+        (
+            arena.alloc_str("vector_id"),
+            Value::String(vector_id.to_string()),
+        ),
+        (
+            arena.alloc_str("status"),
+            Value::String(facets.status.clone()),
+        ),
+        (arena.alloc_str("tags"), Value::String(tags)),
+        (
+            arena.alloc_str("file_path"),
+            Value::String(facets.path.clone()),
+        ),
+    ];
+    for (key, timestamp) in [
+        ("created_at", &facets.created_at),
+        ("updated_at", &facets.updated_at),
+    ] {
+        if let Some(timestamp) = timestamp {
+            props.push((arena.alloc_str(key), Value::String(timestamp.clone())));
         }
+    }
 
-        let targets = match value {
-            YamlValue::Sequence(seq) => seq
-                .iter()
-                .filter_map(|v| match v {
-                    YamlValue::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .collect::<Vec<_>>(),
-            YamlValue::String(s) => vec![s.clone()],
-            _ => Vec::new(),
-        };
-
-        let targets = targets
-            .into_iter()
-            .filter(|t| ix_core::entity::looks_like_entity_id(t))
-            .collect::<Vec<_>>();
-
-        if targets.is_empty() {
-            continue;
-        }
+    let properties = ImmutablePropertiesMap::new(props.len(), props.into_iter(), &arena);
+    let node = Node {
+        id: Uuid::new_v4().as_u128(),
+        label,
+        version: 1,
+        properties: Some(properties),
+    };
 
-        let key = key.trim().to_string();
-        if key.is_empty() || !seen.insert(key.clone()) {
-            continue;
-        }
+    graph_ops::put_node(storage, wtxn, &node)
+        .map_err(|e| anyhow::anyhow!("Failed to store chunk node: {e}"))?;
+    graph_ops::update_secondary_indices(storage, wtxn, &node)
+        .map_err(|e| anyhow::anyhow!("Failed to update secondary index: {e}"))?;
+    Ok(node.id)
+}
 
-        rels.push((key, targets));
+/// Remove what a sync wrote for an entity: its chunk nodes, the edges it
+/// declares, and its entity node and vectors. Edges pointing at the entity
+/// are kept for a node rewritten under the same ID.
+fn delete_entity_content<'a>(
+    storage: &'a HelixGraphStorage,
+    wtxn: &mut RwTxn<'a>,
+    entry: &ManifestEntry,
+) -> Result<()> {
+    for &chunk_node_id in &entry.written.chunk_node_ids {
+        delete_node_and_vector(storage, wtxn, chunk_node_id)?;
     }
+    graph_ops::delete_outgoing_edges(storage, wtxn, entry.written.node_id)
+        .map_err(|e| anyhow::anyhow!("Failed to delete edges: {e}"))?;
+    delete_node_and_vector(storage, wtxn, entry.written.node_id)
+}
 
-    rels
+/// Remove a node and the vector it points at. Edges are left in place.
+fn delete_node_and_vector<'a>(
+    storage: &'a HelixGraphStorage,
+    wtxn: &mut RwTxn<'a>,
+    node_id: u128,
+) -> Result<()> {
+    let arena = Bump::new();
+    // Already gone, e.g. when the database was cleared by hand
+    let Ok(node) = storage.get_node(wtxn, &node_id, &arena) else {
+        return Ok(());
+    };
+    if let Some(vector_id) =
+        string_property(&node, "vector_id").and_then(|id| id.parse::<u128>().ok())
+    {
+        storage
+            .vectors
+            .delete(wtxn, vector_id, &arena)
+            .map_err(|e| anyhow::anyhow!("Failed to delete vector: {e:?}"))?;
+    }
+    graph_ops::delete_node(storage, wtxn, &node)
+        .map_err(|e| anyhow::anyhow!("Failed to delete node: {e}"))
 }
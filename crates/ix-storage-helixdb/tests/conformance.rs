@@ -0,0 +1,32 @@
+use ix_core::entity::EntityKind;
+use ix_core::index::{IndexBackend, SearchMode, SearchRequest};
+use ix_core::lexical::LexicalIndex;
+use ix_storage_conformance::{hash_embedder, replace_entity_body, temp_repo};
+use ix_storage_helixdb::HelixDbIndex;
+
+ix_storage_conformance::index_backend_tests!(HelixDbIndex::open_with_embedder);
+
+#[test]
+fn missing_lexical_index_forces_a_rebuild() {
+    let (_temp, repo) = temp_repo();
+    let source = repo
+        .create_entity(EntityKind::Source, "Alpha Source", None)
+        .expect("create source");
+    replace_entity_body(&repo, &source.id, "alpha");
+
+    let mut index =
+        HelixDbIndex::open_with_embedder(&repo, hash_embedder(&repo, 32)).expect("open index");
+    index.sync(&repo).expect("sync");
+
+    let db_path = repo.index_path();
+    std::fs::remove_file(LexicalIndex::sidecar_path(&db_path)).expect("remove lexical index");
+
+    let stats = index.sync(&repo).expect("resync");
+    assert_eq!(stats.added, 1, "{stats:?}");
+    assert_eq!(stats.unchanged, 0, "{stats:?}");
+    let hits = index
+        .search(&SearchRequest::new("alpha", 5).with_mode(SearchMode::Lexical))
+        .expect("lexical search");
+    assert_eq!(hits.len(), 1, "{hits:#?}");
+    assert_eq!(hits[0].id, source.id);
+}
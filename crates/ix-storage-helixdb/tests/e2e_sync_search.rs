@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use ix_core::entity::EntityKind;
-use ix_core::index::IndexBackend;
+use ix_core::index::{IndexBackend, SearchRequest};
 use ix_core::markdown::{parse_markdown, render_markdown};
 use ix_core::repo::IxchelRepo;
 use tempfile::TempDir;
@@ -50,7 +50,9 @@ fn e2e_sync_then_search_returns_expected_source() {
     assert_eq!(stats.scanned, 2);
     assert_eq!(stats.added, 2);
 
-    let hits = index.search("reader lock table", 5).expect("search");
+    let hits = index
+        .search(&SearchRequest::new("reader lock table", 5))
+        .expect("search");
     assert!(!hits.is_empty(), "expected search hits");
     assert_eq!(hits[0].id, primary.id, "{hits:#?}");
 }
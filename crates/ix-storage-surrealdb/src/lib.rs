@@ -3,41 +3,39 @@
 //! This crate provides a [`SurrealDbIndex`] that implements the [`IndexBackend`] trait
 //! using `SurrealDB`'s embedded mode with `RocksDB` or `SurrealKV` storage.
 
-mod manifest;
 mod schema;
 mod types;
 
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use manifest::{ManifestEntry, SyncAction, SyncManifest};
-
 use anyhow::{Context, Result};
+use ix_core::chunk::{Chunk, DEFAULT_MAX_CHUNK_CHARS, chunk_body, pool_embeddings};
 use ix_core::entity::{EntityKind, kind_from_id};
-use ix_core::index::{IndexBackend, SearchHit, SyncStats};
-use ix_core::markdown::{get_string, get_string_list, parse_markdown};
-use ix_core::repo::IxchelRepo;
-use ix_embeddings::Embedder;
-use serde_yaml::Value as YamlValue;
+use ix_core::graph::{Direction, EdgeFilter, Neighbor};
+use ix_core::index::{
+    IndexBackend, IndexMetadata, IndexMetadataStatus, SearchFacets, SearchFilters, SearchHit,
+    SearchRequest, SyncStats, format_timestamp, group_chunk_hits, search_with_mode,
+};
+use ix_core::manifest::{ManifestEntry, SyncAction, SyncManifest};
+use ix_core::markdown::{body_line_offset, get_string, get_string_list, parse_markdown};
+use ix_core::repo::{IxchelRepo, extract_relationships, iter_entity_paths, normalize_path};
+use ix_core::similarity::{EntityRef, EntityVector, cosine_similarity};
+use ix_embeddings::{Embedder, ModelStore};
 use surrealdb::Surreal;
 use surrealdb::engine::local::{Db, RocksDb, SurrealKv};
 use tokio::runtime::Runtime;
 
-pub use types::{EntityRecord, SearchResult};
+pub use types::{ChunkRecord, EntityRecord, SearchResult};
+
+/// Chunks fetched per requested hit, so several passages of one entity
+/// don't crowd other entities out of the results.
+const CHUNK_OVERSAMPLE: usize = 4;
 
-const METADATA_KEYS: &[&str] = &[
-    "id",
-    "type",
-    "title",
-    "status",
-    "date",
-    "created_at",
-    "updated_at",
-    "created_by",
-    "tags",
-];
+const BACKEND_NAME: &str = "surrealdb";
 
 /// SurrealDB-backed index for Ixchel entities.
 ///
@@ -59,8 +57,10 @@ impl SurrealDbIndex {
     ///
     /// Uses the embedding configuration from the repository config.
     pub fn open(repo: &IxchelRepo) -> Result<Self> {
-        let embedder = Embedder::with_config(&repo.config.embedding)
-            .map_err(|e| anyhow::anyhow!("Failed to initialize embedder: {e}"))?;
+        let models = ModelStore::from_config(repo.paths.models_dir(), &repo.config.models);
+        let embedder = Embedder::with_model_store(&repo.index_embedding_config(), &models)
+            .map_err(|e| anyhow::anyhow!("Failed to initialize embedder: {e}"))?
+            .with_cache(repo.paths.embedding_cache_dir());
         Self::open_with_embedder(repo, embedder)
     }
 
@@ -70,10 +70,7 @@ impl SurrealDbIndex {
     /// Note: The database is opened lazily on first use to avoid lock conflicts during sync.
     pub fn open_with_embedder(repo: &IxchelRepo, embedder: Embedder) -> Result<Self> {
         let repo_root = repo.paths.repo_root().to_path_buf();
-        let db_path = repo
-            .paths
-            .ixchel_dir()
-            .join(PathBuf::from(&repo.config.storage.path));
+        let db_path = repo.index_path();
 
         let runtime =
             Arc::new(Runtime::new().context("Failed to create tokio runtime for SurrealDB")?);
@@ -143,67 +140,73 @@ impl SurrealDbIndex {
         f(db)
     }
 
-    /// Query outgoing relationships from an entity.
-    ///
-    /// Returns the IDs of entities that `from_id` has the given relationship to.
+    /// IDs of the entities `from_id` has a `rel` relationship to.
     pub fn outgoing(&self, from_id: &str, rel: &str) -> Result<Vec<String>> {
-        let label = rel.trim().to_ascii_uppercase();
-        if label.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        // Clone to owned values for async block
-        let from_id_owned = from_id.to_string();
-        let label_owned = label;
-        let runtime = self.runtime.clone();
-
-        self.with_db(|db| {
-            let result: Vec<types::NeighborResult> = runtime.block_on(async {
-                db.query(
-                    "SELECT out.entity_id AS entity_id FROM relates WHERE in.entity_id = $from_id AND label = $label",
-                )
-                .bind(("from_id", from_id_owned))
-                .bind(("label", label_owned))
-                .await?
-                .take(0)
-            })?;
-
-            let mut out: Vec<String> = result.into_iter().map(|r| r.entity_id).collect();
-            out.sort();
-            out.dedup();
-            Ok(out)
-        })
+        self.neighbor_ids(from_id, rel, Direction::Outgoing)
     }
 
-    /// Query incoming relationships to an entity.
-    ///
-    /// Returns the IDs of entities that have the given relationship to `to_id`.
+    /// IDs of the entities that have a `rel` relationship to `to_id`.
     pub fn incoming(&self, to_id: &str, rel: &str) -> Result<Vec<String>> {
-        let label = rel.trim().to_ascii_uppercase();
-        if label.is_empty() {
+        self.neighbor_ids(to_id, rel, Direction::Incoming)
+    }
+
+    /// [`IndexBackend::neighbors`] of `id` across `rel` edges, as sorted IDs.
+    fn neighbor_ids(&self, id: &str, rel: &str, direction: Direction) -> Result<Vec<String>> {
+        if rel.trim().is_empty() {
             return Ok(Vec::new());
         }
+        let filter = EdgeFilter::new(direction).with_labels(vec![rel.to_string()]);
+        let mut ids = self
+            .neighbors(id, &filter)?
+            .into_iter()
+            .map(|neighbor| neighbor.entity.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Neighbors of `id` across edges in one direction, optionally limited
+    /// to uppercase `labels`.
+    fn edges(&self, id: &str, outgoing: bool, labels: &[String]) -> Result<Vec<Neighbor>> {
+        let (neighbor, own) = if outgoing {
+            ("out", "in")
+        } else {
+            ("in", "out")
+        };
+        let mut query_str = format!(
+            "SELECT {neighbor}.entity_id AS entity_id, {neighbor}.kind AS kind, \
+             {neighbor}.title AS title, label FROM relates WHERE {own}.entity_id = $entity_id"
+        );
+        if !labels.is_empty() {
+            query_str.push_str(" AND label IN $labels");
+        }
 
-        // Clone to owned values for async block
-        let to_id_owned = to_id.to_string();
-        let label_owned = label;
+        let entity_id_owned = id.to_string();
+        let labels_owned = labels.to_vec();
         let runtime = self.runtime.clone();
 
         self.with_db(|db| {
-            let result: Vec<types::NeighborResult> = runtime.block_on(async {
-                db.query(
-                    "SELECT in.entity_id AS entity_id FROM relates WHERE out.entity_id = $to_id AND label = $label",
-                )
-                .bind(("to_id", to_id_owned))
-                .bind(("label", label_owned))
-                .await?
-                .take(0)
+            let results: Vec<types::EdgeResult> = runtime.block_on(async {
+                db.query(query_str)
+                    .bind(("entity_id", entity_id_owned))
+                    .bind(("labels", labels_owned))
+                    .await?
+                    .take(0)
             })?;
 
-            let mut out: Vec<String> = result.into_iter().map(|r| r.entity_id).collect();
-            out.sort();
-            out.dedup();
-            Ok(out)
+            Ok(results
+                .into_iter()
+                .map(|r| Neighbor {
+                    entity: EntityRef {
+                        id: r.entity_id,
+                        kind: r.kind.and_then(|k| k.parse::<EntityKind>().ok()),
+                        title: r.title,
+                    },
+                    rel: r.label.to_ascii_lowercase(),
+                    outgoing,
+                })
+                .collect())
         })
     }
 
@@ -223,6 +226,11 @@ impl SurrealDbIndex {
             std::fs::remove_dir_all(&self.db_path)
                 .with_context(|| format!("Failed to remove {}", self.db_path.display()))?;
         }
+        let metadata_path = IndexMetadata::sidecar_path(&self.db_path);
+        if metadata_path.exists() {
+            std::fs::remove_file(&metadata_path)
+                .with_context(|| format!("Failed to remove {}", metadata_path.display()))?;
+        }
 
         std::fs::create_dir_all(&self.db_path)
             .with_context(|| format!("Failed to create {}", self.db_path.display()))?;
@@ -240,12 +248,272 @@ impl SurrealDbIndex {
         Ok(())
     }
 
-    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embedder
+            .embed_query(text)
+            .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
+    }
+
+    fn embed_documents(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
         self.embedder
-            .embed(text)
+            .embed_documents(texts)
             .map_err(|e| anyhow::anyhow!("Embedding failed: {e}"))
     }
 
+    /// Metadata a sync with the current embedder would write.
+    fn expected_metadata(&self) -> IndexMetadata {
+        IndexMetadata::current(
+            BACKEND_NAME,
+            self.embedder.provider_name(),
+            self.embedder.model_name(),
+            self.embedder.dimension(),
+            self.embedder.document_prefix(),
+        )
+    }
+
+    /// Nearest chunks to the query embedding, grouped per entity. Filters
+    /// are evaluated by the KNN search so they don't eat into `limit`.
+    fn search_vector(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let embedding = self.embed_query(query)?;
+        let runtime = self.runtime.clone();
+
+        // Use HNSW KNN search over chunks - results come back ordered by distance
+        // The <|K,EF|> operator returns K nearest neighbors with ef_search=EF
+        // Distance function (COSINE) is defined in the index, not the query
+        // Note: KNN operator requires literal for K, so we format it into the query
+        let candidates = limit.saturating_mul(CHUNK_OVERSAMPLE).max(limit);
+        let ef_search = 64.max(candidates); // Higher = more accurate but slower
+        let conditions = filter_conditions(filters);
+        let query_str = format!(
+            "SELECT entity_id, kind, title, chunk_index, heading_path, start_line, end_line, text, \
+             status, tags, file_path, vector::distance::knn() AS distance \
+             FROM chunk \
+             WHERE embedding <|{candidates},{ef_search}|> $query_embedding{conditions} \
+             ORDER BY distance"
+        );
+        let params = filter_params(filters);
+
+        self.with_db(|db| {
+            let results: Vec<types::ChunkSearchResult> = runtime.block_on(async {
+                db.query(&query_str)
+                    .bind(("query_embedding", embedding))
+                    .bind(params)
+                    .await?
+                    .take(0)
+            })?;
+
+            let hits = results
+                .into_iter()
+                .map(|r| {
+                    // Cosine distance: 0 = identical, 1 = orthogonal, 2 = opposite
+                    #[allow(clippy::cast_possible_truncation)]
+                    let distance = r.distance as f32;
+                    let kind = r.kind.and_then(|k| k.parse::<EntityKind>().ok());
+                    SearchHit {
+                        id: r.entity_id,
+                        kind,
+                        title: r.title,
+                        passage: Some(Chunk {
+                            index: usize::try_from(r.chunk_index).unwrap_or_default(),
+                            heading_path: r.heading_path,
+                            start_line: usize::try_from(r.start_line).unwrap_or_default(),
+                            end_line: usize::try_from(r.end_line).unwrap_or_default(),
+                            text: r.text,
+                        }),
+                        ..SearchHit::default()
+                    }
+                    .with_facets(SearchFacets {
+                        path: r.file_path,
+                        status: r.status,
+                        tags: r.tags,
+                        ..SearchFacets::default()
+                    })
+                    .with_cosine_distance(distance)
+                })
+                .collect();
+
+            Ok(group_chunk_hits(hits, limit))
+        })
+    }
+
+    /// BM25 matches from the chunk full-text index, grouped per entity.
+    fn search_lexical(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let runtime = self.runtime.clone();
+        let candidates = limit.saturating_mul(CHUNK_OVERSAMPLE).max(limit);
+        let conditions = filter_conditions(filters);
+        let query_str = format!(
+            "SELECT entity_id, kind, title, chunk_index, heading_path, start_line, \
+             end_line, text, status, tags, file_path, search::score(1) AS score \
+             FROM chunk \
+             WHERE search_text @1@ $query{conditions} \
+             ORDER BY score DESC \
+             LIMIT $limit"
+        );
+        let params = filter_params(filters);
+
+        self.with_db(|db| {
+            let results: Vec<types::ChunkTextSearchResult> = runtime.block_on(async {
+                db.query(&query_str)
+                    .bind(("query", query.to_string()))
+                    .bind(("limit", candidates))
+                    .bind(params)
+                    .await?
+                    .take(0)
+            })?;
+
+            let hits = results
+                .into_iter()
+                .map(|r| {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let score = r.score as f32;
+                    SearchHit {
+                        score,
+                        id: r.entity_id,
+                        kind: r.kind.and_then(|k| k.parse::<EntityKind>().ok()),
+                        title: r.title,
+                        passage: Some(Chunk {
+                            index: usize::try_from(r.chunk_index).unwrap_or_default(),
+                            heading_path: r.heading_path,
+                            start_line: usize::try_from(r.start_line).unwrap_or_default(),
+                            end_line: usize::try_from(r.end_line).unwrap_or_default(),
+                            text: r.text,
+                        }),
+                        ..SearchHit::default()
+                    }
+                    .with_facets(SearchFacets {
+                        path: r.file_path,
+                        status: r.status,
+                        tags: r.tags,
+                        ..SearchFacets::default()
+                    })
+                })
+                .collect();
+
+            Ok(group_chunk_hits(hits, limit))
+        })
+    }
+
+    /// Stored entity vectors, of `entity_id` only when given.
+    fn stored_entity_vectors(
+        &self,
+        entity_id: Option<&str>,
+    ) -> Result<Vec<types::EntityVectorResult>> {
+        let runtime = self.runtime.clone();
+        let query_str = if entity_id.is_some() {
+            "SELECT entity_id, kind, title, status, tags, file_path, embedding \
+             FROM entity WHERE entity_id = $entity_id"
+        } else {
+            "SELECT entity_id, kind, title, status, tags, file_path, embedding \
+             FROM entity ORDER BY entity_id"
+        };
+        let entity_id_owned = entity_id.map(str::to_string);
+
+        self.with_db(|db| {
+            let results: Vec<types::EntityVectorResult> = runtime.block_on(async {
+                db.query(query_str)
+                    .bind(("entity_id", entity_id_owned))
+                    .await?
+                    .take(0)
+            })?;
+            Ok(results)
+        })
+    }
+
+    /// Entities nearest to `vector` in the entity HNSW index, skipping
+    /// `exclude`, scored by exact cosine similarity.
+    fn nearest_to_vector(
+        &self,
+        vector: Vec<f32>,
+        limit: usize,
+        exclude: Option<&str>,
+    ) -> Result<Vec<SearchHit>> {
+        if limit == 0 || vector.is_empty() {
+            return Ok(Vec::new());
+        }
+        let runtime = self.runtime.clone();
+        let candidates = limit + usize::from(exclude.is_some());
+        let ef_search = 64.max(candidates);
+        let query_str = format!(
+            "SELECT entity_id, kind, title, status, tags, file_path, embedding \
+             FROM entity \
+             WHERE embedding <|{candidates},{ef_search}|> $vector"
+        );
+
+        self.with_db(|db| {
+            let results: Vec<types::EntityVectorResult> = runtime.block_on(async {
+                db.query(&query_str)
+                    .bind(("vector", vector.clone()))
+                    .await?
+                    .take(0)
+            })?;
+
+            let mut hits = results
+                .into_iter()
+                .filter(|r| exclude != Some(r.entity_id.as_str()))
+                .map(|r| {
+                    let distance = 1.0 - cosine_similarity(&vector, &r.embedding);
+                    SearchHit {
+                        id: r.entity_id,
+                        kind: r.kind.and_then(|k| k.parse::<EntityKind>().ok()),
+                        title: r.title,
+                        ..SearchHit::default()
+                    }
+                    .with_facets(SearchFacets {
+                        path: r.file_path,
+                        status: r.status,
+                        tags: r.tags,
+                        ..SearchFacets::default()
+                    })
+                    .with_cosine_distance(distance)
+                })
+                .collect::<Vec<_>>();
+            hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+            hits.truncate(limit);
+            Ok(hits)
+        })
+    }
+
+    /// Replace the stored chunks of an entity.
+    fn replace_chunks(
+        &self,
+        db: &Surreal<Db>,
+        entity_id: &str,
+        chunks: Vec<ChunkRecord>,
+    ) -> Result<()> {
+        self.delete_chunks(db, entity_id)?;
+        for chunk in chunks {
+            self.runtime.block_on(async {
+                db.query("CREATE chunk CONTENT $content")
+                    .bind(("content", chunk))
+                    .await?;
+                Ok::<_, anyhow::Error>(())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Delete all chunks of an entity.
+    fn delete_chunks(&self, db: &Surreal<Db>, entity_id: &str) -> Result<()> {
+        let entity_id_owned = entity_id.to_string();
+
+        self.runtime.block_on(async {
+            db.query("DELETE FROM chunk WHERE entity_id = $entity_id")
+                .bind(("entity_id", entity_id_owned))
+                .await?;
+            Ok::<_, anyhow::Error>(())
+        })
+    }
+
     fn insert_edges(
         &self,
         db: &Surreal<Db>,
@@ -301,6 +569,7 @@ impl SurrealDbIndex {
                         file_path: r.file_path,
                         #[allow(clippy::cast_sign_loss)]
                         last_synced: r.last_synced as u64,
+                        written: (),
                     },
                 )
             })
@@ -341,9 +610,10 @@ impl SurrealDbIndex {
         })
     }
 
-    /// Delete an entity and its relationships from the database.
+    /// Delete an entity, its chunks, and its relationships from the database.
     fn delete_entity(&self, db: &Surreal<Db>, entity_id: &str) -> Result<()> {
         let entity_id_owned = entity_id.to_string();
+        self.delete_chunks(db, entity_id)?;
 
         self.runtime.block_on(async {
             // Delete relationships first
@@ -406,16 +676,15 @@ impl SurrealDbIndex {
         })
     }
 
-    /// Delete all relationships involving an entity.
-    fn delete_entity_edges(&self, db: &Surreal<Db>, entity_id: &str) -> Result<()> {
+    /// Delete the relationships an entity declares. Edges from other
+    /// entities stay, since an updated entity keeps its record ID.
+    fn delete_outgoing_edges(&self, db: &Surreal<Db>, entity_id: &str) -> Result<()> {
         let entity_id_owned = entity_id.to_string();
 
         self.runtime.block_on(async {
-            db.query(
-                "DELETE relates WHERE in.entity_id = $entity_id OR out.entity_id = $entity_id",
-            )
-            .bind(("entity_id", entity_id_owned))
-            .await?;
+            db.query("DELETE relates WHERE in.entity_id = $entity_id")
+                .bind(("entity_id", entity_id_owned))
+                .await?;
             Ok::<_, anyhow::Error>(())
         })
     }
@@ -428,12 +697,19 @@ impl IndexBackend for SurrealDbIndex {
     /// a stored manifest and only updates changed entities.
     #[allow(clippy::significant_drop_tightening, clippy::too_many_lines)]
     fn sync(&mut self, repo: &IxchelRepo) -> Result<SyncStats> {
+        let cache_before = self.embedder.cache_stats();
+
         // Check if database exists - if not, do a full rebuild
-        let db_exists = self.db_path.exists();
+        let mut db_exists = self.db_path.exists();
 
         if db_exists {
             // Open existing database
             self.ensure_db_open()?;
+            // A different embedder or index layout can't be updated incrementally
+            if self.metadata()?.needs_rebuild() {
+                self.rebuild_database()?;
+                db_exists = false;
+            }
         } else {
             self.rebuild_database()?;
         }
@@ -463,8 +739,10 @@ impl IndexBackend for SurrealDbIndex {
 
         let mut stats = SyncStats::default();
         let mut pending_relations: Vec<PendingRelation> = Vec::new();
+        let mut unchanged: Vec<PendingRelation> = Vec::new();
         let mut id_to_record_id: BTreeMap<String, String> = BTreeMap::new();
         let mut seen_entity_ids: HashSet<String> = HashSet::new();
+        let mut inserted_ids: HashSet<String> = HashSet::new();
 
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -499,10 +777,14 @@ impl IndexBackend for SurrealDbIndex {
 
             match action {
                 SyncAction::Skip => {
-                    // Entity unchanged - edges are already correct, no need to re-insert
+                    // Unchanged - its record and edges are already correct
                     stats.unchanged += 1;
                     // Still track the ID for relationship target resolution
                     id_to_record_id.insert(id.clone(), id.clone());
+                    unchanged.push(PendingRelation {
+                        from_record_id: id,
+                        rels: extract_relationships(&doc.frontmatter),
+                    });
                     continue;
                 }
                 SyncAction::Insert | SyncAction::Update => {
@@ -518,9 +800,26 @@ impl IndexBackend for SurrealDbIndex {
             let title = get_string(&doc.frontmatter, "title").unwrap_or_default();
             let tags = get_string_list(&doc.frontmatter, "tags");
             let entity_status = get_string(&doc.frontmatter, "status").unwrap_or_default();
+            let facets = SearchFacets {
+                path: normalized_path.clone(),
+                ..SearchFacets::from_frontmatter(&doc.frontmatter)
+            };
 
-            let embedding_text = build_embedding_text(&title, &doc.body, &tags, kind);
-            let embedding = self.embed(&embedding_text)?;
+            let chunks = chunk_body(&doc.body, body_line_offset(&raw), DEFAULT_MAX_CHUNK_CHARS);
+            let chunk_texts = chunks
+                .iter()
+                .map(|chunk| chunk.embedding_text(&title, &tags, kind))
+                .collect::<Vec<_>>();
+            let chunk_embeddings =
+                self.embed_documents(&chunk_texts.iter().map(String::as_str).collect::<Vec<_>>())?;
+            let embedding = pool_embeddings(&chunk_embeddings);
+            let chunk_records = chunks
+                .into_iter()
+                .zip(chunk_embeddings)
+                .map(|(chunk, embedding)| {
+                    chunk_record(&id, kind, &title, &facets, chunk, embedding)
+                })
+                .collect::<Vec<_>>();
 
             let record = EntityRecord {
                 record_id: None,
@@ -546,22 +845,25 @@ impl IndexBackend for SurrealDbIndex {
                             .await?;
                         Ok::<_, anyhow::Error>(())
                     })?;
+                    inserted_ids.insert(id.clone());
                     stats.added += 1;
                 }
                 SyncAction::Update => {
-                    // Delete old edges first, then update entity
-                    self.delete_entity_edges(db, &id)?;
+                    // Delete its old edges first, then update entity
+                    self.delete_outgoing_edges(db, &id)?;
                     self.update_entity(db, &record)?;
                     stats.modified += 1;
                 }
                 SyncAction::Skip => unreachable!(),
             }
+            self.replace_chunks(db, &id, chunk_records)?;
 
             // Update manifest entry
             let manifest_entry = ManifestEntry {
                 content_hash,
                 file_path: normalized_path,
                 last_synced: now,
+                written: (),
             };
             self.save_manifest_entry(db, &id, &manifest_entry)?;
             manifest.insert(id.clone(), manifest_entry);
@@ -584,62 +886,102 @@ impl IndexBackend for SurrealDbIndex {
             }
         }
 
-        // Insert edges only for new and modified entities
-        // Unchanged entities keep their existing edges (skipped above)
-        // For Update, edges were deleted in delete_entity_edges before update
-        // For Insert, no old edges exist
+        // New and modified entities get all their edges: for Update, outgoing
+        // edges were deleted in delete_outgoing_edges before update, and for
+        // Insert no old edges exist. Unchanged entities keep theirs and only
+        // gain edges to entities that didn't exist when they were last synced
+        for mut record in unchanged {
+            for (_, targets) in &mut record.rels {
+                targets.retain(|target| inserted_ids.contains(target));
+            }
+            pending_relations.push(record);
+        }
         self.insert_edges(db, &id_to_record_id, pending_relations)?;
 
+        self.expected_metadata().save(&self.db_path)?;
+
+        let cache = self.embedder.cache_stats().since(cache_before);
+        stats.cache_hits = cache.hits;
+        stats.cache_misses = cache.misses;
         Ok(stats)
     }
 
-    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
-        let embedding = self.embed(query)?;
-        let runtime = self.runtime.clone();
+    fn search(&self, request: &SearchRequest) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let hits = search_with_mode(
+            request.mode,
+            request.window(),
+            |n| self.search_vector(&request.query, n, &request.filters),
+            |n| self.search_lexical(&request.query, n, &request.filters),
+        )?;
+        Ok(request.page(hits))
+    }
 
-        // Use HNSW KNN search - results come back ordered by distance
-        // The <|K,EF|> operator returns K nearest neighbors with ef_search=EF
-        // Distance function (COSINE) is defined in the index, not the query
-        // Note: KNN operator requires literal for K, so we format it into the query
-        let ef_search = 64; // Higher = more accurate but slower
-        let query_str = format!(
-            "SELECT entity_id, kind, title, vector::distance::knn() AS distance \
-             FROM entity \
-             WHERE embedding <|{limit},{ef_search}|> $query_embedding \
-             ORDER BY distance"
-        );
+    fn related(&self, id: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let entity = self
+            .stored_entity_vectors(Some(id))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("{id} is not in the index. Run `ixchel sync`."))?;
+        self.nearest_to_vector(entity.embedding, limit, Some(id))
+    }
 
-        self.with_db(|db| {
-            let results: Vec<SearchResult> = runtime.block_on(async {
-                db.query(&query_str)
-                    .bind(("query_embedding", embedding))
-                    .await?
-                    .take(0)
-            })?;
+    fn nearest_entities(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        self.metadata()?.ensure_compatible()?;
+        let vector = pool_embeddings(&self.embed_documents(&[text])?);
+        self.nearest_to_vector(vector, limit, None)
+    }
 
-            // Convert distance to score (lower distance = higher score)
-            let hits = results
-                .into_iter()
-                .map(|r| {
-                    // Cosine distance: 0 = identical, 2 = opposite
-                    // Convert to score: 1/(1+distance) gives ~1.0 for identical, ~0.33 for opposite
-                    #[allow(clippy::cast_possible_truncation)]
-                    let score = (1.0 / (1.0 + r.distance)) as f32;
-                    let kind = r.kind.and_then(|k| k.parse::<EntityKind>().ok());
-                    SearchHit {
-                        score,
-                        id: r.entity_id,
-                        kind,
-                        title: r.title,
-                    }
-                })
-                .collect();
+    fn entity_vectors(&self) -> Result<Vec<EntityVector>> {
+        Ok(self
+            .stored_entity_vectors(None)?
+            .into_iter()
+            .map(|r| EntityVector {
+                id: r.entity_id,
+                kind: r.kind.and_then(|k| k.parse::<EntityKind>().ok()),
+                title: r.title,
+                vector: r.embedding,
+            })
+            .collect())
+    }
 
-            Ok(hits)
-        })
+    fn entity(&self, id: &str) -> Result<Option<EntityRef>> {
+        Ok(self
+            .stored_entity_vectors(Some(id))?
+            .into_iter()
+            .next()
+            .map(|r| EntityRef {
+                id: r.entity_id,
+                kind: r.kind.and_then(|k| k.parse::<EntityKind>().ok()),
+                title: r.title,
+            }))
+    }
+
+    fn neighbors(&self, id: &str, filter: &EdgeFilter) -> Result<Vec<Neighbor>> {
+        let labels = filter
+            .labels
+            .iter()
+            .map(|label| label.trim().to_ascii_uppercase())
+            .filter(|label| !label.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut out = Vec::new();
+        if filter.direction.outgoing() {
+            out.extend(self.edges(id, true, &labels)?);
+        }
+        if filter.direction.incoming() {
+            out.extend(self.edges(id, false, &labels)?);
+        }
+        out.sort_by(|a, b| {
+            (&a.rel, &a.entity.id, !a.outgoing).cmp(&(&b.rel, &b.entity.id, !b.outgoing))
+        });
+        out.dedup();
+        Ok(out)
     }
 
     fn health_check(&self) -> Result<()> {
+        self.metadata()?.ensure_compatible()?;
         let runtime = self.runtime.clone();
 
         self.with_db(|db| {
@@ -650,6 +992,18 @@ impl IndexBackend for SurrealDbIndex {
             })
         })
     }
+
+    fn metadata(&self) -> Result<IndexMetadataStatus> {
+        let stored = if self.db_path.exists() {
+            IndexMetadata::load(&self.db_path)?
+        } else {
+            None
+        };
+        Ok(IndexMetadataStatus {
+            expected: self.expected_metadata(),
+            stored,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -673,102 +1027,80 @@ async fn open_database(db_path: &Path, engine: &str) -> Result<Surreal<Db>> {
     Ok(db)
 }
 
-fn normalize_path(repo_root: &Path, file_path: &Path) -> String {
-    file_path
-        .strip_prefix(repo_root)
-        .unwrap_or(file_path)
-        .to_string_lossy()
-        .replace('\\', "/")
-}
-
-fn iter_entity_paths(repo: &IxchelRepo) -> Result<Vec<PathBuf>> {
-    let mut out = Vec::new();
-
-    for kind in [
-        EntityKind::Decision,
-        EntityKind::Issue,
-        EntityKind::Idea,
-        EntityKind::Report,
-        EntityKind::Source,
-        EntityKind::Citation,
-        EntityKind::Agent,
-        EntityKind::Session,
+/// `WHERE` conditions for the active search filters, each prefixed with
+/// `AND`, reading the parameters bound by [`filter_params`].
+fn filter_conditions(filters: &SearchFilters) -> String {
+    let mut conditions = String::new();
+    if !filters.kinds.is_empty() {
+        conditions.push_str(" AND kind IN $kinds");
+    }
+    if !filters.statuses.is_empty() {
+        conditions.push_str(" AND string::lowercase(string::trim(status)) IN $statuses");
+    }
+    if !filters.tags.is_empty() {
+        conditions.push_str(" AND tags CONTAINSALL $tags");
+    }
+    for (field, param, range) in [
+        ("created_at", "created", &filters.created),
+        ("updated_at", "updated", &filters.updated),
     ] {
-        let dir = repo.paths.kind_dir(kind);
-        if !dir.exists() {
-            continue;
+        if !range.is_unbounded() {
+            let _ = write!(conditions, " AND {field} != NONE");
         }
-
-        for entry in
-            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
-        {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("md") {
-                continue;
-            }
-            out.push(path);
+        if range.after.is_some() {
+            let _ = write!(conditions, " AND {field} >= ${param}_after");
+        }
+        if range.before.is_some() {
+            let _ = write!(conditions, " AND {field} < ${param}_before");
         }
     }
-
-    out.sort();
-    Ok(out)
+    conditions
 }
 
-fn build_embedding_text(title: &str, body: &str, tags: &[String], kind: EntityKind) -> String {
-    let tags_str = if tags.is_empty() {
-        String::new()
-    } else {
-        tags.join(", ")
-    };
-
-    format!(
-        "{title}\n\n{body}\n\nTags: {tags_str}\nType: {}\n",
-        kind.as_str()
-    )
+fn filter_params(filters: &SearchFilters) -> types::SearchFilterParams {
+    types::SearchFilterParams {
+        kinds: filters
+            .kinds
+            .iter()
+            .map(|kind| kind.as_str().to_string())
+            .collect(),
+        statuses: filters
+            .statuses
+            .iter()
+            .map(|status| status.trim().to_ascii_lowercase())
+            .collect(),
+        tags: filters.tags.clone(),
+        created_after: filters.created.after.map(format_timestamp),
+        created_before: filters.created.before.map(format_timestamp),
+        updated_after: filters.updated.after.map(format_timestamp),
+        updated_before: filters.updated.before.map(format_timestamp),
+    }
 }
 
-fn extract_relationships(frontmatter: &serde_yaml::Mapping) -> Vec<(String, Vec<String>)> {
-    let mut rels = Vec::new();
-    let mut seen: BTreeSet<String> = BTreeSet::new();
-
-    for (key, value) in frontmatter {
-        let YamlValue::String(key) = key else {
-            continue;
-        };
-
-        if METADATA_KEYS.contains(&key.as_str()) {
-            continue;
-        }
-
-        let targets = match value {
-            YamlValue::Sequence(seq) => seq
-                .iter()
-                .filter_map(|v| match v {
-                    YamlValue::String(s) => Some(s.clone()),
-                    _ => None,
-                })
-                .collect::<Vec<_>>(),
-            YamlValue::String(s) => vec![s.clone()],
-            _ => Vec::new(),
-        };
-
-        let targets = targets
-            .into_iter()
-            .filter(|t| ix_core::entity::looks_like_entity_id(t))
-            .collect::<Vec<_>>();
-
-        if targets.is_empty() {
-            continue;
-        }
-
-        let key = key.trim().to_string();
-        if key.is_empty() || !seen.insert(key.clone()) {
-            continue;
-        }
-
-        rels.push((key, targets));
+fn chunk_record(
+    entity_id: &str,
+    kind: EntityKind,
+    title: &str,
+    facets: &SearchFacets,
+    chunk: Chunk,
+    embedding: Vec<f32>,
+) -> ChunkRecord {
+    let to_i64 = |n: usize| i64::try_from(n).unwrap_or(i64::MAX);
+    ChunkRecord {
+        search_text: chunk.search_text(title),
+        file_path: facets.path.clone(),
+        status: facets.status.clone(),
+        tags: facets.tags.clone(),
+        created_at: facets.created_at.clone(),
+        updated_at: facets.updated_at.clone(),
+        entity_id: entity_id.to_string(),
+        kind: kind.as_str().to_string(),
+        title: title.to_string(),
+        chunk_index: to_i64(chunk.index),
+        heading_path: chunk.heading_path,
+        start_line: to_i64(chunk.start_line),
+        end_line: to_i64(chunk.end_line),
+        text: chunk.text,
+        embedding,
     }
-
-    rels
 }
@@ -4,7 +4,10 @@
 ///
 /// Creates:
 /// - `entity` table with SCHEMAFULL mode
+/// - `chunk` table holding one embedded passage per heading-aware chunk, with
+///   the entity fields search filters on
 /// - `sync_manifest` table for incremental sync tracking
+/// - BM25 full-text index over chunk text for lexical search
 /// - HNSW vector index for similarity search
 /// - Relationship edge definitions
 pub const SCHEMA_INIT: &str = r"
@@ -31,6 +34,30 @@ DEFINE FIELD IF NOT EXISTS embedding ON entity TYPE array<float>;
 -- Unique index on entity_id field
 DEFINE INDEX IF NOT EXISTS entity_id_idx ON entity FIELDS entity_id UNIQUE;
 
+-- Chunk table: heading-aware passages of entity bodies, each with its own embedding
+DEFINE TABLE IF NOT EXISTS chunk SCHEMAFULL;
+DEFINE FIELD IF NOT EXISTS entity_id ON chunk TYPE string ASSERT $value != NONE;
+DEFINE FIELD IF NOT EXISTS kind ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS title ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS chunk_index ON chunk TYPE int;
+DEFINE FIELD IF NOT EXISTS heading_path ON chunk TYPE array<string>;
+DEFINE FIELD IF NOT EXISTS start_line ON chunk TYPE int;
+DEFINE FIELD IF NOT EXISTS end_line ON chunk TYPE int;
+DEFINE FIELD IF NOT EXISTS text ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS search_text ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS file_path ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS status ON chunk TYPE string;
+DEFINE FIELD IF NOT EXISTS tags ON chunk TYPE array<string>;
+DEFINE FIELD IF NOT EXISTS created_at ON chunk TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS updated_at ON chunk TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS embedding ON chunk TYPE array<float>;
+DEFINE INDEX IF NOT EXISTS chunk_entity_id_idx ON chunk FIELDS entity_id;
+DEFINE INDEX IF NOT EXISTS chunk_kind_idx ON chunk FIELDS kind;
+
+-- Full-text index for lexical search: title, headings and text of each chunk
+DEFINE ANALYZER IF NOT EXISTS ixchel_text TOKENIZERS blank, class, punct FILTERS lowercase, ascii;
+DEFINE INDEX IF NOT EXISTS chunk_search_text_idx ON chunk FIELDS search_text SEARCH ANALYZER ixchel_text BM25;
+
 -- Relationship table (for graph edges)
 DEFINE TABLE IF NOT EXISTS relates SCHEMAFULL TYPE RELATION IN entity OUT entity;
 DEFINE FIELD IF NOT EXISTS label ON relates TYPE string;
@@ -45,7 +72,7 @@ DEFINE FIELD IF NOT EXISTS last_synced ON sync_manifest TYPE int;
 DEFINE INDEX IF NOT EXISTS manifest_entity_id_idx ON sync_manifest FIELDS entity_id UNIQUE;
 ";
 
-/// Create HNSW vector index with the specified dimension.
+/// Create HNSW vector indexes on entities and chunks with the specified dimension.
 ///
 /// This must be called after schema init with the actual embedding dimension.
 /// Uses cosine distance for semantic similarity search.
@@ -56,6 +83,7 @@ pub fn hnsw_index_query(dimension: usize) -> String {
     // - M 16: max connections per layer (default)
     // - EFC 150: ef_construction (index build quality)
     format!(
-        r"DEFINE INDEX IF NOT EXISTS entity_embedding_idx ON entity FIELDS embedding HNSW DIMENSION {dimension} DIST COSINE M 16 EFC 150;"
+        r"DEFINE INDEX IF NOT EXISTS entity_embedding_idx ON entity FIELDS embedding HNSW DIMENSION {dimension} DIST COSINE M 16 EFC 150;
+DEFINE INDEX IF NOT EXISTS chunk_embedding_idx ON chunk FIELDS embedding HNSW DIMENSION {dimension} DIST COSINE M 16 EFC 150;"
     )
 }
@@ -38,6 +38,194 @@ pub struct EntityRecord {
     pub embedding: Vec<f32>,
 }
 
+/// Heading-aware passage of an entity body stored in `SurrealDB`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkRecord {
+    /// Identifier of the entity the chunk belongs to
+    pub entity_id: String,
+
+    /// Entity kind, denormalized for search results
+    pub kind: String,
+
+    /// Entity title, denormalized for search results
+    pub title: String,
+
+    /// 0-based position of the chunk within the entity
+    pub chunk_index: i64,
+
+    /// Headings enclosing the chunk, outermost first
+    pub heading_path: Vec<String>,
+
+    /// First line of the chunk in the file (1-based)
+    pub start_line: i64,
+
+    /// Last line of the chunk in the file (1-based, inclusive)
+    pub end_line: i64,
+
+    /// Chunk text
+    pub text: String,
+
+    /// Title, headings and text, indexed for full-text search
+    pub search_text: String,
+
+    /// Entity file path relative to repo root, denormalized for search results
+    pub file_path: String,
+
+    /// Entity status, denormalized for search filters
+    pub status: String,
+
+    /// Entity tags, denormalized for search filters
+    pub tags: Vec<String>,
+
+    /// Entity creation time in canonical form, denormalized for search filters
+    pub created_at: Option<String>,
+
+    /// Entity update time in canonical form, denormalized for search filters
+    pub updated_at: Option<String>,
+
+    /// Embedding vector for similarity search
+    pub embedding: Vec<f32>,
+}
+
+/// Search result from a chunk similarity query.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChunkSearchResult {
+    /// Entity identifier
+    pub entity_id: String,
+
+    /// Entity kind
+    pub kind: Option<String>,
+
+    /// Entity title
+    pub title: String,
+
+    /// 0-based position of the chunk within the entity
+    pub chunk_index: i64,
+
+    /// Headings enclosing the chunk
+    pub heading_path: Vec<String>,
+
+    /// First line of the chunk in the file
+    pub start_line: i64,
+
+    /// Last line of the chunk in the file
+    pub end_line: i64,
+
+    /// Chunk text
+    pub text: String,
+
+    /// Entity status
+    #[serde(default)]
+    pub status: String,
+
+    /// Entity tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Entity file path relative to repo root
+    #[serde(default)]
+    pub file_path: String,
+
+    /// Distance from query vector (lower = more similar for cosine distance)
+    pub distance: f64,
+}
+
+/// Search result from a chunk full-text query.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChunkTextSearchResult {
+    /// Entity identifier
+    pub entity_id: String,
+
+    /// Entity kind
+    pub kind: Option<String>,
+
+    /// Entity title
+    pub title: String,
+
+    /// 0-based position of the chunk within the entity
+    pub chunk_index: i64,
+
+    /// Headings enclosing the chunk
+    pub heading_path: Vec<String>,
+
+    /// First line of the chunk in the file
+    pub start_line: i64,
+
+    /// Last line of the chunk in the file
+    pub end_line: i64,
+
+    /// Chunk text
+    pub text: String,
+
+    /// Entity status
+    #[serde(default)]
+    pub status: String,
+
+    /// Entity tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Entity file path relative to repo root
+    #[serde(default)]
+    pub file_path: String,
+
+    /// BM25 relevance (higher = better match)
+    pub score: f64,
+}
+
+/// Query parameters bound for search filters.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchFilterParams {
+    /// Allowed entity kinds
+    pub kinds: Vec<String>,
+
+    /// Allowed statuses, lowercase
+    pub statuses: Vec<String>,
+
+    /// Tags every hit must carry
+    pub tags: Vec<String>,
+
+    /// Inclusive lower bound on `created_at`
+    pub created_after: Option<String>,
+
+    /// Exclusive upper bound on `created_at`
+    pub created_before: Option<String>,
+
+    /// Inclusive lower bound on `updated_at`
+    pub updated_after: Option<String>,
+
+    /// Exclusive upper bound on `updated_at`
+    pub updated_before: Option<String>,
+}
+
+/// Entity with its stored embedding, for entity-level similarity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntityVectorResult {
+    /// Entity identifier
+    pub entity_id: String,
+
+    /// Entity kind
+    pub kind: Option<String>,
+
+    /// Entity title
+    pub title: String,
+
+    /// Entity status
+    #[serde(default)]
+    pub status: String,
+
+    /// Entity tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// File path relative to repo root
+    #[serde(default)]
+    pub file_path: String,
+
+    /// Pooled embedding of the entity's chunks
+    pub embedding: Vec<f32>,
+}
+
 /// Search result from vector similarity query.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SearchResult {
@@ -54,11 +242,20 @@ pub struct SearchResult {
     pub distance: f64,
 }
 
-/// Result of an outgoing/incoming neighbor query.
+/// An entity linked to the one queried, with the label of the edge.
 #[derive(Debug, Clone, Deserialize)]
-pub struct NeighborResult {
+pub struct EdgeResult {
     /// Entity identifier of the neighbor
     pub entity_id: String,
+
+    /// Entity kind of the neighbor
+    pub kind: Option<String>,
+
+    /// Entity title of the neighbor
+    pub title: String,
+
+    /// Edge label, uppercase
+    pub label: String,
 }
 
 /// Manifest record stored in `SurrealDB` for incremental sync tracking.
@@ -0,0 +1,3 @@
+use ix_storage_surrealdb::SurrealDbIndex;
+
+ix_storage_conformance::index_backend_tests!(SurrealDbIndex::open_with_embedder);